@@ -0,0 +1,502 @@
+use crate::color::{Color, ColorError};
+use crate::harmony::Scheme;
+use crate::kinds::Hue;
+use crate::mixing::{MixError, Palette};
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Write};
+
+pub const USAGE: &str = "\
+Usage: art mix PAINT[:PARTS]... [--json]
+       art harmony --complementary|--analogous|--triadic|--split-complementary|--tetradic
+                   [--ryb|--rgb] COLOR [--json]
+       art convert COLOR [--json]
+
+PAINT is a hue of the RYB wheel like red-orange, or white or black.
+COLOR is #rrggbb, #rgb, rgb(r, g, b), hsl(h, s%, l%) or a name.
+Add --no-swatches to leave out the colored blocks.";
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    /// Mix paints from the default `Palette` by parts.
+    Mix { recipe: Vec<(String, f64)> },
+    Harmony {
+        scheme: Scheme,
+        wheel: Wheel,
+        base: Color,
+    },
+    /// Show a color as hex, RGB, HSL and its nearest names.
+    Convert { color: Color },
+}
+
+/// Which color wheel harmonies go round.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Wheel {
+    /// The painter's wheel, where red's complement is green.
+    Ryb,
+    /// The screen's wheel, where red's complement is cyan.
+    Rgb,
+}
+
+/// How results are written.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Style {
+    /// For people, optionally with a block of each color in 24-bit ANSI color.
+    Text { swatches: bool },
+    /// One JSON object, for other programs.
+    Json,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Options {
+    pub command: Command,
+    pub style: Style,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CliError {
+    MissingCommand,
+    UnknownCommand(String),
+    UnknownFlag(String),
+    /// `mix` needs at least one paint, the others exactly one color.
+    WrongArguments(&'static str),
+    MissingScheme,
+    BadParts(String),
+    Color(ColorError),
+    Mix(MixError),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CliError::MissingCommand => write!(f, "expected mix, harmony or convert"),
+            CliError::UnknownCommand(command) => write!(f, "there's no `{}` command", command),
+            CliError::UnknownFlag(flag) => write!(f, "unknown flag `{}`", flag),
+            CliError::WrongArguments(expected) => write!(f, "expected {}", expected),
+            CliError::MissingScheme => write!(f, "harmony needs a scheme like --triadic"),
+            CliError::BadParts(paint) => write!(
+                f,
+                "`{}` isn't a paint with a number of parts, like red:3",
+                paint
+            ),
+            CliError::Color(err) => err.fmt(f),
+            CliError::Mix(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for CliError {}
+
+impl From<ColorError> for CliError {
+    fn from(err: ColorError) -> CliError {
+        CliError::Color(err)
+    }
+}
+
+impl From<MixError> for CliError {
+    fn from(err: MixError) -> CliError {
+        CliError::Mix(err)
+    }
+}
+
+impl Options {
+    /// Flags can go anywhere after the command, everything else is an argument.
+    pub fn from_args(mut args: impl Iterator<Item = String>) -> Result<Options, CliError> {
+        args.next();
+        let command = args.next().ok_or(CliError::MissingCommand)?;
+
+        let mut style = Style::Text { swatches: true };
+        let mut scheme = None;
+        let mut wheel = Wheel::Ryb;
+        let mut arguments = Vec::new();
+        for arg in args {
+            if let Some(name) = arg.strip_prefix("--") {
+                match name {
+                    "json" => style = Style::Json,
+                    "no-swatches" => {
+                        if let Style::Text { .. } = style {
+                            style = Style::Text { swatches: false }
+                        }
+                    }
+                    "ryb" => wheel = Wheel::Ryb,
+                    "rgb" => wheel = Wheel::Rgb,
+                    _ => match Scheme::parse(name) {
+                        Some(parsed) if command == "harmony" => scheme = Some(parsed),
+                        _ => return Err(CliError::UnknownFlag(arg)),
+                    },
+                }
+            } else {
+                arguments.push(arg);
+            }
+        }
+
+        let only = |arguments: Vec<String>, expected| -> Result<Color, CliError> {
+            match arguments.as_slice() {
+                [color] => Ok(color.parse()?),
+                _ => Err(CliError::WrongArguments(expected)),
+            }
+        };
+        let command = match command.as_str() {
+            "mix" if arguments.is_empty() => {
+                return Err(CliError::WrongArguments("paints to mix"));
+            }
+            "mix" => Command::Mix {
+                recipe: arguments
+                    .iter()
+                    .map(|paint| parts(paint))
+                    .collect::<Result<_, _>>()?,
+            },
+            "harmony" => Command::Harmony {
+                scheme: scheme.ok_or(CliError::MissingScheme)?,
+                wheel,
+                base: only(arguments, "one base color")?,
+            },
+            "convert" => Command::Convert {
+                color: only(arguments, "one color")?,
+            },
+            _ => return Err(CliError::UnknownCommand(command)),
+        };
+        Ok(Options { command, style })
+    }
+}
+
+/// Splits `red:3` into the paint and its parts, one part if there's no number.
+fn parts(paint: &str) -> Result<(String, f64), CliError> {
+    let (name, parts) = match paint.rsplit_once(':') {
+        Some((name, parts)) => {
+            let parts = parts
+                .parse::<f64>()
+                .ok()
+                .filter(|parts| parts.is_finite() && *parts >= 0.0)
+                .ok_or_else(|| CliError::BadParts(String::from(paint)))?;
+            (name, parts)
+        }
+        None => (paint, 1.0),
+    };
+    Ok((String::from(name), parts))
+}
+
+/// Works out what `command` asks for and writes it to `out`.
+pub fn run<W: Write>(command: &Command, style: Style, out: &mut W) -> Result<(), Box<dyn Error>> {
+    match command {
+        Command::Mix { recipe } => {
+            let palette = Palette::default();
+            let recipe: Vec<(&str, f64)> = recipe
+                .iter()
+                .map(|(name, parts)| (name.as_str(), *parts))
+                .collect();
+            let mixed = palette.mix(&recipe)?.color().unwrap();
+            let paints: Vec<(&str, f64, Color)> = recipe
+                .iter()
+                .map(|&(name, parts)| (name, parts, palette.get(name).unwrap().to_color()))
+                .collect();
+            write_mix(&paints, mixed, style, out)?;
+        }
+        Command::Harmony {
+            scheme,
+            wheel,
+            base,
+        } => {
+            let colors = match wheel {
+                Wheel::Ryb => scheme.ryb(*base),
+                Wheel::Rgb => scheme.rgb(*base),
+            };
+            write_harmony(*scheme, *wheel, &colors, style, out)?;
+        }
+        Command::Convert { color } => write_convert(*color, style, out)?,
+    }
+    Ok(())
+}
+
+fn write_mix<W: Write>(
+    paints: &[(&str, f64, Color)],
+    mixed: Color,
+    style: Style,
+    out: &mut W,
+) -> io::Result<()> {
+    let nearest = Hue::nearest(mixed).name();
+    match style {
+        Style::Text { swatches } => {
+            for &(name, parts, color) in paints {
+                writeln!(
+                    out,
+                    "{}{}  {} × {}",
+                    swatch(color, swatches),
+                    color,
+                    parts,
+                    name
+                )?;
+            }
+            writeln!(
+                out,
+                "{}{}  mixed, nearest {}",
+                swatch(mixed, swatches),
+                mixed,
+                nearest
+            )
+        }
+        Style::Json => {
+            let paints: Vec<String> = paints
+                .iter()
+                .map(|&(name, parts, color)| {
+                    format!(
+                        "{{\"paint\":{},\"parts\":{},\"color\":\"{}\"}}",
+                        json_string(name),
+                        parts,
+                        color
+                    )
+                })
+                .collect();
+            writeln!(
+                out,
+                "{{\"paints\":[{}],\"color\":\"{}\",\"rgb\":{},\"nearest\":\"{}\"}}",
+                paints.join(","),
+                mixed,
+                json_rgb(mixed),
+                nearest
+            )
+        }
+    }
+}
+
+fn write_harmony<W: Write>(
+    scheme: Scheme,
+    wheel: Wheel,
+    colors: &[Color],
+    style: Style,
+    out: &mut W,
+) -> io::Result<()> {
+    let wheel = match wheel {
+        Wheel::Ryb => "ryb",
+        Wheel::Rgb => "rgb",
+    };
+    match style {
+        Style::Text { swatches } => {
+            writeln!(
+                out,
+                "{} on the {} wheel:",
+                scheme.name(),
+                wheel.to_uppercase()
+            )?;
+            for &color in colors {
+                writeln!(
+                    out,
+                    "{}{}  {}  nearest {}",
+                    swatch(color, swatches),
+                    color,
+                    color.to_hsl(),
+                    Hue::nearest(color).name()
+                )?;
+            }
+            Ok(())
+        }
+        Style::Json => {
+            let colors: Vec<String> = colors
+                .iter()
+                .map(|color| format!("\"{}\"", color))
+                .collect();
+            writeln!(
+                out,
+                "{{\"scheme\":\"{}\",\"wheel\":\"{}\",\"colors\":[{}]}}",
+                scheme.name(),
+                wheel,
+                colors.join(",")
+            )
+        }
+    }
+}
+
+fn write_convert<W: Write>(color: Color, style: Style, out: &mut W) -> io::Result<()> {
+    let hsl = color.to_hsl();
+    let (name, _) = color.nearest_named();
+    let hue = Hue::nearest(color).name();
+    match style {
+        Style::Text { swatches } => {
+            writeln!(out, "{}{}", swatch(color, swatches), color)?;
+            writeln!(out, "rgb({}, {}, {})", color.r, color.g, color.b)?;
+            writeln!(out, "{}", hsl)?;
+            writeln!(out, "nearest name {}, nearest hue {}", name, hue)
+        }
+        Style::Json => writeln!(
+            out,
+            "{{\"hex\":\"{}\",\"rgb\":{},\"hsl\":[{:.1},{:.3},{:.3}],\"name\":\"{}\",\"hue\":\"{}\"}}",
+            color,
+            json_rgb(color),
+            hsl.h,
+            hsl.s,
+            hsl.l,
+            name,
+            hue
+        ),
+    }
+}
+
+/// A block of `color` in 24-bit ANSI color, followed by a space, or nothing at all.
+fn swatch(color: Color, swatches: bool) -> String {
+    if swatches {
+        format!("\x1b[48;2;{};{};{}m    \x1b[0m ", color.r, color.g, color.b)
+    } else {
+        String::new()
+    }
+}
+
+fn json_rgb(color: Color) -> String {
+    format!("[{},{},{}]", color.r, color.g, color.b)
+}
+
+fn json_string(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{run, CliError, Command, Options, Style, Wheel};
+    use crate::color::{Color, ColorError};
+    use crate::harmony::Scheme;
+    use crate::mixing::MixError;
+
+    fn options(args: &[&str]) -> Result<Options, CliError> {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        Options::from_args(args.into_iter())
+    }
+
+    fn output(args: &[&str]) -> String {
+        let options = options(args).unwrap();
+        let mut out = Vec::new();
+        run(&options.command, options.style, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn parses_commands() {
+        let mix = options(&["art", "mix", "red:3", "blue"]).unwrap();
+        assert_eq!(
+            Command::Mix {
+                recipe: vec![(String::from("red"), 3.0), (String::from("blue"), 1.0)]
+            },
+            mix.command
+        );
+        assert_eq!(Style::Text { swatches: true }, mix.style);
+
+        let harmony = options(&["art", "harmony", "--triadic", "#3366ff", "--rgb"]).unwrap();
+        assert_eq!(
+            Command::Harmony {
+                scheme: Scheme::Triadic,
+                wheel: Wheel::Rgb,
+                base: Color::rgb(0x33, 0x66, 0xff)
+            },
+            harmony.command
+        );
+
+        let convert = options(&["art", "convert", "--json", "rgb(1, 2, 3)"]).unwrap();
+        assert_eq!(
+            Command::Convert {
+                color: Color::rgb(1, 2, 3)
+            },
+            convert.command
+        );
+        assert_eq!(Style::Json, convert.style);
+
+        let plain = options(&["art", "convert", "red", "--no-swatches"]).unwrap();
+        assert_eq!(Style::Text { swatches: false }, plain.style);
+    }
+
+    #[test]
+    fn bad_arguments() {
+        assert_eq!(Err(CliError::MissingCommand), options(&["art"]));
+        assert_eq!(
+            Err(CliError::UnknownCommand(String::from("paint"))),
+            options(&["art", "paint"])
+        );
+        assert_eq!(
+            Err(CliError::WrongArguments("paints to mix")),
+            options(&["art", "mix"])
+        );
+        assert_eq!(
+            Err(CliError::BadParts(String::from("red:-1"))),
+            options(&["art", "mix", "red:-1"])
+        );
+        assert_eq!(
+            Err(CliError::MissingScheme),
+            options(&["art", "harmony", "red"])
+        );
+        // schemes are only flags of harmony
+        assert_eq!(
+            Err(CliError::UnknownFlag(String::from("--triadic"))),
+            options(&["art", "convert", "--triadic", "red"])
+        );
+        assert_eq!(
+            Err(CliError::WrongArguments("one color")),
+            options(&["art", "convert", "red", "blue"])
+        );
+        assert_eq!(
+            Err(CliError::Color(ColorError::Unparsable(String::from("#12")))),
+            options(&["art", "convert", "#12"])
+        );
+
+        let options = options(&["art", "mix", "red", "teal"]).unwrap();
+        let err = run(&options.command, options.style, &mut Vec::new()).unwrap_err();
+        assert_eq!(
+            MixError::UnknownPaint(String::from("teal")).to_string(),
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn mixing() {
+        assert_eq!(
+            "#fe2712  1 × red\n#fefe33  1 × yellow\n#fb9902  mixed, nearest orange\n",
+            output(&["art", "mix", "red", "yellow", "--no-swatches"])
+        );
+        assert_eq!(
+            "{\"paints\":[{\"paint\":\"red\",\"parts\":1,\"color\":\"#fe2712\"},\
+             {\"paint\":\"yellow\",\"parts\":1,\"color\":\"#fefe33\"}],\
+             \"color\":\"#fb9902\",\"rgb\":[251,153,2],\"nearest\":\"orange\"}\n",
+            output(&["art", "mix", "red", "yellow", "--json"])
+        );
+
+        let swatched = output(&["art", "mix", "red:3", "blue:1"]);
+        assert!(swatched.starts_with("\x1b[48;2;254;39;18m    \x1b[0m #fe2712  3 × red\n"));
+    }
+
+    #[test]
+    fn harmonies_and_conversions() {
+        assert_eq!(
+            "triadic on the RGB wheel:\n\
+             #ff0000  hsl(0, 100%, 50%)  nearest red\n\
+             #00ff00  hsl(120, 100%, 50%)  nearest green\n\
+             #0000ff  hsl(240, 100%, 50%)  nearest blue\n",
+            output(&[
+                "art",
+                "harmony",
+                "--triadic",
+                "--rgb",
+                "#f00",
+                "--no-swatches"
+            ])
+        );
+        assert_eq!(
+            "{\"scheme\":\"complementary\",\"wheel\":\"rgb\",\"colors\":[\"#ff0000\",\"#00ffff\"]}\n",
+            output(&["art", "harmony", "--complementary", "--rgb", "#f00", "--json"])
+        );
+
+        assert_eq!(
+            "#fc600a\nrgb(252, 96, 10)\nhsl(21, 98%, 51%)\nnearest name red-orange, nearest hue red-orange\n",
+            output(&["art", "convert", "red-orange", "--no-swatches"])
+        );
+        assert_eq!(
+            "{\"hex\":\"#808080\",\"rgb\":[128,128,128],\"hsl\":[0.0,0.000,0.502],\"name\":\"gray\",\"hue\":\"blue-green\"}\n",
+            output(&["art", "convert", "#808080", "--json"])
+        );
+    }
+}
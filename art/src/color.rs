@@ -0,0 +1,486 @@
+use crate::kinds::{Hue, PrimaryColor, SecondaryColor, TertiaryColor};
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// A screen color in sRGB.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+/// A screen color as hue, saturation and lightness.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hsl {
+    /// Degrees round the color circle, from 0 up to 360.
+    pub h: f64,
+    /// From 0 for gray to 1.
+    pub s: f64,
+    /// From 0 for black to 1 for white.
+    pub l: f64,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ColorError {
+    /// Not a hex code, `rgb(…)`, `hsl(…)` or a color name.
+    Unparsable(String),
+    /// Not exactly the color of any hue of the kind asked for.
+    NotNamed(Color),
+}
+
+impl fmt::Display for ColorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ColorError::Unparsable(input) => write!(
+                f,
+                "`{}` isn't a color, expected #rrggbb, rgb(r, g, b), hsl(h, s%, l%) or a name",
+                input
+            ),
+            ColorError::NotNamed(color) => write!(f, "{} isn't one of the named colors", color),
+        }
+    }
+}
+
+impl Error for ColorError {}
+
+/// How the hues of the RYB wheel look on screen, in `Hue::WHEEL` order.
+const WHEEL_COLORS: [Color; 12] = [
+    Color::rgb(0xfe, 0x27, 0x12),
+    Color::rgb(0xfc, 0x60, 0x0a),
+    Color::rgb(0xfb, 0x99, 0x02),
+    Color::rgb(0xfc, 0xcc, 0x1a),
+    Color::rgb(0xfe, 0xfe, 0x33),
+    Color::rgb(0xb2, 0xd7, 0x32),
+    Color::rgb(0x66, 0xb0, 0x32),
+    Color::rgb(0x34, 0x7c, 0x98),
+    Color::rgb(0x02, 0x47, 0xfe),
+    Color::rgb(0x44, 0x24, 0xd6),
+    Color::rgb(0x86, 0x01, 0xaf),
+    Color::rgb(0xc2, 0x14, 0x60),
+];
+
+/// The colors that aren't on the wheel but still have a name.
+const NEUTRALS: [(&str, Color); 4] = [
+    ("white", Color::rgb(0xff, 0xff, 0xff)),
+    ("gray", Color::rgb(0x80, 0x80, 0x80)),
+    ("black", Color::rgb(0x00, 0x00, 0x00)),
+    // what complements mix to
+    ("brown", Color::rgb(0x8b, 0x5a, 0x2b)),
+];
+
+/// Every color with a name, the wheel's hues first.
+pub fn named_colors() -> impl Iterator<Item = (&'static str, Color)> {
+    let hues = Hue::WHEEL.iter().map(|&hue| (hue.name(), Color::from(hue)));
+    hues.chain(NEUTRALS.iter().copied())
+}
+
+impl Color {
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Color {
+        Color { r, g, b }
+    }
+
+    /// Parses `#rrggbb` or the short `#rgb`, with or without the `#`.
+    pub fn from_hex(hex: &str) -> Result<Color, ColorError> {
+        let unparsable = || ColorError::Unparsable(String::from(hex));
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+        if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(unparsable());
+        }
+
+        let channel = |digits: &str| u8::from_str_radix(digits, 16).map_err(|_| unparsable());
+        match digits.len() {
+            6 => Ok(Color::rgb(
+                channel(&digits[0..2])?,
+                channel(&digits[2..4])?,
+                channel(&digits[4..6])?,
+            )),
+            // #f80 is short for #ff8800
+            3 => Ok(Color::rgb(
+                channel(&digits[0..1])? * 17,
+                channel(&digits[1..2])? * 17,
+                channel(&digits[2..3])? * 17,
+            )),
+            _ => Err(unparsable()),
+        }
+    }
+
+    /// Like `#ff8800`.
+    pub fn to_hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+
+    pub fn to_hsl(self) -> Hsl {
+        let [r, g, b] = [self.r, self.g, self.b].map(|c| c as f64 / 255.0);
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let l = (max + min) / 2.0;
+        let chroma = max - min;
+        if chroma == 0.0 {
+            return Hsl { h: 0.0, s: 0.0, l };
+        }
+
+        let s = chroma / (1.0 - (2.0 * l - 1.0).abs());
+        // which sixth of the circle the hue is in, measured from the strongest channel
+        let sector = if max == r {
+            ((g - b) / chroma).rem_euclid(6.0)
+        } else if max == g {
+            (b - r) / chroma + 2.0
+        } else {
+            (r - g) / chroma + 4.0
+        };
+        Hsl {
+            h: sector * 60.0,
+            s,
+            l,
+        }
+    }
+
+    /// The nearest screen color to `hsl`. Hues outside 0..360 wrap round, and saturation
+    /// and lightness are clamped to 0..=1.
+    pub fn from_hsl(hsl: Hsl) -> Color {
+        let h = hsl.h.rem_euclid(360.0) / 60.0;
+        let s = hsl.s.clamp(0.0, 1.0);
+        let l = hsl.l.clamp(0.0, 1.0);
+
+        let chroma = (1.0 - (2.0 * l - 1.0).abs()) * s;
+        let x = chroma * (1.0 - (h % 2.0 - 1.0).abs());
+        let (r, g, b) = match h as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let m = l - chroma / 2.0;
+        let channel = |c: f64| ((c + m) * 255.0).round() as u8;
+        Color::rgb(channel(r), channel(g), channel(b))
+    }
+
+    /// The color called `name`, like `red-orange` or `gray`, ignoring case.
+    pub fn named(name: &str) -> Option<Color> {
+        let name = name.to_lowercase();
+        named_colors()
+            .find(|&(candidate, _)| candidate == name)
+            .map(|(_, color)| color)
+    }
+
+    /// The named color closest to this one, measured as straight-line distance in RGB.
+    pub fn nearest_named(self) -> (&'static str, Color) {
+        named_colors()
+            .min_by_key(|&(_, color)| self.distance_squared(color))
+            .unwrap()
+    }
+
+    fn distance_squared(self, other: Color) -> u32 {
+        let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+        d(self.r, other.r) + d(self.g, other.g) + d(self.b, other.b)
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+impl fmt::Display for Hsl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "hsl({:.0}, {:.0}%, {:.0}%)",
+            self.h,
+            self.s * 100.0,
+            self.l * 100.0
+        )
+    }
+}
+
+/// The numbers between `prefix(` and `)`, separated by commas. `%` and `deg` units
+/// are allowed but ignored.
+fn arguments<'a>(input: &'a str, prefix: &str) -> Option<Vec<&'a str>> {
+    let inner = input.strip_prefix(prefix)?.strip_prefix('(')?;
+    let inner = inner.strip_suffix(')')?;
+    Some(
+        inner
+            .split(',')
+            .map(|arg| arg.trim().trim_end_matches('%').trim_end_matches("deg"))
+            .collect(),
+    )
+}
+
+/// Understands `#ff8800`, `#f80`, `rgb(255, 136, 0)`, `hsl(32, 100%, 50%)` and names
+/// like `orange`.
+impl FromStr for Color {
+    type Err = ColorError;
+
+    fn from_str(input: &str) -> Result<Color, ColorError> {
+        let unparsable = || ColorError::Unparsable(String::from(input));
+        let trimmed = input.trim().to_lowercase();
+
+        if let Some(args) = arguments(&trimmed, "rgb") {
+            let channels: Vec<u8> = args
+                .iter()
+                .map(|arg| arg.parse().map_err(|_| unparsable()))
+                .collect::<Result<_, _>>()?;
+            return match channels[..] {
+                [r, g, b] => Ok(Color::rgb(r, g, b)),
+                _ => Err(unparsable()),
+            };
+        }
+        if let Some(args) = arguments(&trimmed, "hsl") {
+            let numbers: Vec<f64> = args
+                .iter()
+                .map(|arg| arg.parse().map_err(|_| unparsable()))
+                .collect::<Result<_, _>>()?;
+            return match numbers[..] {
+                [h, s, l] => Ok(Color::from_hsl(Hsl {
+                    h,
+                    s: s / 100.0,
+                    l: l / 100.0,
+                })),
+                _ => Err(unparsable()),
+            };
+        }
+        if let Some(color) = Color::named(&trimmed) {
+            return Ok(color);
+        }
+        Color::from_hex(&trimmed).map_err(|_| unparsable())
+    }
+}
+
+impl Hue {
+    /// The hue of the wheel that looks most like `color`.
+    pub fn nearest(color: Color) -> Hue {
+        *Hue::WHEEL
+            .iter()
+            .min_by_key(|&&hue| color.distance_squared(Color::from(hue)))
+            .unwrap()
+    }
+}
+
+impl From<Hue> for Color {
+    fn from(hue: Hue) -> Color {
+        WHEEL_COLORS[hue.position()]
+    }
+}
+
+impl From<PrimaryColor> for Color {
+    fn from(color: PrimaryColor) -> Color {
+        Color::from(Hue::from(color))
+    }
+}
+
+impl From<SecondaryColor> for Color {
+    fn from(color: SecondaryColor) -> Color {
+        Color::from(Hue::from(color))
+    }
+}
+
+impl From<TertiaryColor> for Color {
+    fn from(color: TertiaryColor) -> Color {
+        Color::from(Hue::from(color))
+    }
+}
+
+impl TryFrom<Color> for Hue {
+    type Error = ColorError;
+
+    fn try_from(color: Color) -> Result<Hue, ColorError> {
+        Hue::WHEEL
+            .iter()
+            .copied()
+            .find(|&hue| Color::from(hue) == color)
+            .ok_or(ColorError::NotNamed(color))
+    }
+}
+
+impl TryFrom<Color> for PrimaryColor {
+    type Error = ColorError;
+
+    fn try_from(color: Color) -> Result<PrimaryColor, ColorError> {
+        match Hue::try_from(color)? {
+            Hue::Primary(primary) => Ok(primary),
+            _ => Err(ColorError::NotNamed(color)),
+        }
+    }
+}
+
+impl TryFrom<Color> for SecondaryColor {
+    type Error = ColorError;
+
+    fn try_from(color: Color) -> Result<SecondaryColor, ColorError> {
+        match Hue::try_from(color)? {
+            Hue::Secondary(secondary) => Ok(secondary),
+            _ => Err(ColorError::NotNamed(color)),
+        }
+    }
+}
+
+impl TryFrom<Color> for TertiaryColor {
+    type Error = ColorError;
+
+    fn try_from(color: Color) -> Result<TertiaryColor, ColorError> {
+        match Hue::try_from(color)? {
+            Hue::Tertiary(tertiary) => Ok(tertiary),
+            _ => Err(ColorError::NotNamed(color)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Color, ColorError, Hsl};
+    use crate::kinds::{Hue, PrimaryColor, SecondaryColor, TertiaryColor};
+    use std::convert::TryFrom;
+
+    fn close(expected: f64, actual: f64) -> bool {
+        (expected - actual).abs() < 1e-3
+    }
+
+    #[test]
+    fn hex() {
+        let orange = Color::rgb(0xff, 0x88, 0x00);
+        assert_eq!(Ok(orange), Color::from_hex("#ff8800"));
+        assert_eq!(Ok(orange), Color::from_hex("FF8800"));
+        assert_eq!(Ok(orange), Color::from_hex("#f80"));
+        assert_eq!("#ff8800", orange.to_hex());
+        assert_eq!("#ff8800", orange.to_string());
+
+        for bad in &[
+            "", "#", "#ff88", "#ff880", "#ff88000", "#gg8800", "#+f8800", "#ff88é",
+        ] {
+            assert_eq!(
+                Err(ColorError::Unparsable(bad.to_string())),
+                Color::from_hex(bad)
+            );
+        }
+    }
+
+    #[test]
+    fn hsl_of_known_colors() {
+        let cases = [
+            (Color::rgb(255, 0, 0), 0.0, 1.0, 0.5),
+            (Color::rgb(0, 255, 0), 120.0, 1.0, 0.5),
+            (Color::rgb(0, 0, 255), 240.0, 1.0, 0.5),
+            (Color::rgb(255, 0, 255), 300.0, 1.0, 0.5),
+            (Color::rgb(255, 255, 255), 0.0, 0.0, 1.0),
+            (Color::rgb(0, 0, 0), 0.0, 0.0, 0.0),
+            (Color::rgb(0x80, 0x80, 0x80), 0.0, 0.0, 128.0 / 255.0),
+            (Color::rgb(0xff, 0x88, 0x00), 32.0, 1.0, 0.5),
+        ];
+        for &(color, h, s, l) in cases.iter() {
+            let hsl = color.to_hsl();
+            assert!(
+                close(h, hsl.h) && close(s, hsl.s) && close(l, hsl.l),
+                "{} is {:?}",
+                color,
+                hsl
+            );
+            assert_eq!(color, Color::from_hsl(hsl));
+        }
+
+        assert_eq!(
+            "hsl(32, 100%, 50%)",
+            Color::rgb(0xff, 0x88, 0x00).to_hsl().to_string()
+        );
+        // hues wrap round and the rest is clamped
+        let red = Hsl {
+            h: -360.0,
+            s: 2.0,
+            l: 0.5,
+        };
+        assert_eq!(Color::rgb(255, 0, 0), Color::from_hsl(red));
+    }
+
+    #[test]
+    fn hsl_round_trips() {
+        for r in (0..=255).step_by(5) {
+            for g in (0..=255).step_by(5) {
+                for b in (0..=255).step_by(5) {
+                    let color = Color::rgb(r, g, b);
+                    let hsl = color.to_hsl();
+                    assert!((0.0..360.0).contains(&hsl.h), "{} is {:?}", color, hsl);
+                    assert_eq!(color, Color::from_hsl(hsl));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn parses_every_notation() {
+        let orange = Ok(Color::rgb(0xff, 0x88, 0x00));
+        assert_eq!(orange, "#ff8800".parse());
+        assert_eq!(orange, " rgb(255, 136, 0) ".parse());
+        assert_eq!(orange, "RGB(255,136,0)".parse());
+        assert_eq!(orange, "hsl(32deg, 100%, 50%)".parse());
+        assert_eq!(Ok(Color::rgb(0xfc, 0x60, 0x0a)), "Red-Orange".parse());
+        assert_eq!(Ok(Color::rgb(0x80, 0x80, 0x80)), "gray".parse());
+
+        for bad in &[
+            "rgb(256, 0, 0)",
+            "rgb(1, 2)",
+            "hsl(1, 2, x)",
+            "reddish",
+            "rgb 1 2 3",
+        ] {
+            assert_eq!(
+                Err(ColorError::Unparsable(bad.to_string())),
+                bad.parse::<Color>()
+            );
+        }
+    }
+
+    #[test]
+    fn kinds_to_and_from_screen_colors() {
+        for &hue in Hue::WHEEL.iter() {
+            let color = Color::from(hue);
+            assert_eq!(Ok(hue), Hue::try_from(color));
+            assert_eq!(Ok(color), color.to_hex().parse());
+            assert_eq!(color, Color::from_hsl(color.to_hsl()));
+            assert_eq!(Some(color), Color::named(hue.name()));
+            assert_eq!(hue, Hue::nearest(color));
+        }
+
+        for &primary in PrimaryColor::ALL.iter() {
+            assert_eq!(Ok(primary), PrimaryColor::try_from(Color::from(primary)));
+        }
+        for &secondary in SecondaryColor::ALL.iter() {
+            assert_eq!(
+                Ok(secondary),
+                SecondaryColor::try_from(Color::from(secondary))
+            );
+        }
+        for &tertiary in TertiaryColor::ALL.iter() {
+            assert_eq!(Ok(tertiary), TertiaryColor::try_from(Color::from(tertiary)));
+        }
+
+        let orange = Color::from(SecondaryColor::Orange);
+        assert_eq!(
+            Err(ColorError::NotNamed(orange)),
+            PrimaryColor::try_from(orange)
+        );
+        let white = Color::rgb(255, 255, 255);
+        assert_eq!(Err(ColorError::NotNamed(white)), Hue::try_from(white));
+    }
+
+    #[test]
+    fn nearest_named_color() {
+        assert_eq!("red", Color::rgb(0xff, 0x20, 0x20).nearest_named().0);
+        assert_eq!("blue", Color::rgb(0x00, 0x40, 0xff).nearest_named().0);
+        assert_eq!("gray", Color::rgb(0x70, 0x78, 0x80).nearest_named().0);
+        assert_eq!("brown", Color::rgb(0x90, 0x60, 0x30).nearest_named().0);
+        assert_eq!(
+            ("white", Color::rgb(255, 255, 255)),
+            Color::rgb(250, 250, 250).nearest_named()
+        );
+
+        assert_eq!(
+            Hue::Primary(PrimaryColor::Red),
+            Hue::nearest(Color::rgb(255, 0, 0))
+        );
+    }
+}
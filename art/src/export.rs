@@ -0,0 +1,465 @@
+// Named colors in the formats other tools read: a strip of swatches as a PPM or PNG image,
+// GIMP and Inkscape `.gpl` palettes, Adobe `.ase` swatch exchange files and CSS custom
+// properties. `.gpl` is plain text, so palettes made in GIMP can be read back too.
+//
+// The PNG encoder doesn't compress. Swatches are tiny, and stored deflate blocks keep it
+// to a few checksums instead of a dependency.
+
+use crate::color::Color;
+use crate::kinds::Hue;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+
+/// Colors with names, in the order they're shown.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Swatches {
+    pub name: String,
+    pub colors: Vec<(String, Color)>,
+}
+
+#[derive(Debug)]
+pub enum ImportError {
+    Io(io::Error),
+    /// Doesn't start with `GIMP Palette`.
+    NotGpl,
+    /// A line that isn't a color or a header.
+    BadLine {
+        line: usize,
+        text: String,
+    },
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImportError::Io(err) => write!(f, "couldn't read the palette: {}", err),
+            ImportError::NotGpl => write!(f, "not a GIMP palette, expected `GIMP Palette` first"),
+            ImportError::BadLine { line, text } => {
+                write!(f, "line {}: `{}` isn't `red green blue name`", line, text)
+            }
+        }
+    }
+}
+
+impl Error for ImportError {}
+
+impl From<io::Error> for ImportError {
+    fn from(err: io::Error) -> ImportError {
+        ImportError::Io(err)
+    }
+}
+
+impl Swatches {
+    pub fn new(name: &str) -> Swatches {
+        Swatches {
+            name: String::from(name),
+            colors: Vec::new(),
+        }
+    }
+
+    /// The twelve hues of the RYB wheel.
+    pub fn wheel() -> Swatches {
+        let mut swatches = Swatches::new("RYB wheel");
+        for &hue in Hue::WHEEL.iter() {
+            swatches.push(hue.name(), Color::from(hue));
+        }
+        swatches
+    }
+
+    pub fn push(&mut self, name: &str, color: Color) {
+        self.colors.push((String::from(name), color));
+    }
+
+    /// A row of `size` by `size` squares, one per color, as rows of RGB pixels.
+    fn pixels(&self, size: u32) -> io::Result<(u32, u32, Vec<u8>)> {
+        if self.colors.is_empty() || size == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "there are no swatches to draw",
+            ));
+        }
+
+        let width = size * self.colors.len() as u32;
+        let mut row = Vec::with_capacity(width as usize * 3);
+        for (_, color) in &self.colors {
+            for _ in 0..size {
+                row.extend_from_slice(&[color.r, color.g, color.b]);
+            }
+        }
+        Ok((width, size, row.repeat(size as usize)))
+    }
+
+    /// Writes the swatches as a binary PPM image, `size` pixels square each.
+    pub fn write_ppm<W: Write>(&self, size: u32, out: &mut W) -> io::Result<()> {
+        let (width, height, pixels) = self.pixels(size)?;
+        write!(out, "P6\n{} {}\n255\n", width, height)?;
+        out.write_all(&pixels)
+    }
+
+    /// Writes the swatches as a PNG image, `size` pixels square each.
+    pub fn write_png<W: Write>(&self, size: u32, out: &mut W) -> io::Result<()> {
+        let (width, height, pixels) = self.pixels(size)?;
+
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&width.to_be_bytes());
+        header.extend_from_slice(&height.to_be_bytes());
+        // 8 bits per channel of RGB, deflate, adaptive filtering and no interlacing
+        header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+        // every row starts with the filter it's encoded with, 0 for none
+        let stride = width as usize * 3;
+        let mut scanlines = Vec::with_capacity((stride + 1) * height as usize);
+        for row in pixels.chunks(stride) {
+            scanlines.push(0);
+            scanlines.extend_from_slice(row);
+        }
+
+        out.write_all(b"\x89PNG\r\n\x1a\n")?;
+        write_chunk(out, b"IHDR", &header)?;
+        write_chunk(out, b"IDAT", &zlib_stored(&scanlines))?;
+        write_chunk(out, b"IEND", &[])
+    }
+
+    /// Writes a GIMP palette, which Inkscape and Krita read too.
+    pub fn write_gpl<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "GIMP Palette")?;
+        writeln!(out, "Name: {}", self.name)?;
+        writeln!(out, "#")?;
+        for (name, color) in &self.colors {
+            writeln!(out, "{:3} {:3} {:3}\t{}", color.r, color.g, color.b, name)?;
+        }
+        Ok(())
+    }
+
+    /// Reads a GIMP palette. Colors without a name are called `Untitled`, like GIMP does.
+    pub fn read_gpl<R: BufRead>(input: R) -> Result<Swatches, ImportError> {
+        let mut lines = input.lines();
+        match lines.next().transpose()? {
+            Some(line) if line.trim() == "GIMP Palette" => {}
+            Some(_) | None => return Err(ImportError::NotGpl),
+        }
+
+        let mut swatches = Swatches::new("");
+        for (i, line) in lines.enumerate() {
+            let line = line?;
+            let text = line.trim();
+            if text.is_empty() || text.starts_with('#') {
+                continue;
+            }
+            if let Some(name) = text.strip_prefix("Name:") {
+                swatches.name = String::from(name.trim());
+                continue;
+            }
+            if text.starts_with("Columns:") {
+                continue;
+            }
+
+            // the first line is line 1 and it's the header
+            let bad_line = || ImportError::BadLine {
+                line: i + 2,
+                text: String::from(text),
+            };
+            let mut rest = text;
+            let mut channels = [0; 3];
+            for channel in channels.iter_mut() {
+                let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                *channel = rest[..end].parse().map_err(|_| bad_line())?;
+                rest = rest[end..].trim_start();
+            }
+            let name = if rest.is_empty() { "Untitled" } else { rest };
+            swatches.push(name, Color::rgb(channels[0], channels[1], channels[2]));
+        }
+        Ok(swatches)
+    }
+
+    /// Writes an Adobe swatch exchange file, as Photoshop and Illustrator import.
+    pub fn write_ase<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        out.write_all(b"ASEF")?;
+        // version 1.0
+        out.write_all(&1u16.to_be_bytes())?;
+        out.write_all(&0u16.to_be_bytes())?;
+        out.write_all(&(self.colors.len() as u32).to_be_bytes())?;
+
+        for (name, color) in &self.colors {
+            // names are UTF-16 with a terminating nul, counted in code units
+            let name: Vec<u16> = name.encode_utf16().chain(Some(0)).collect();
+            let mut block = Vec::new();
+            block.extend_from_slice(&(name.len() as u16).to_be_bytes());
+            for unit in &name {
+                block.extend_from_slice(&unit.to_be_bytes());
+            }
+            block.extend_from_slice(b"RGB ");
+            for channel in &[color.r, color.g, color.b] {
+                block.extend_from_slice(&(*channel as f32 / 255.0).to_be_bytes());
+            }
+            // a normal color rather than a global or spot one
+            block.extend_from_slice(&2u16.to_be_bytes());
+
+            out.write_all(&1u16.to_be_bytes())?;
+            out.write_all(&(block.len() as u32).to_be_bytes())?;
+            out.write_all(&block)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the colors as CSS custom properties on `:root`, like `--red-orange: #fc600a;`.
+    pub fn write_css<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, ":root {{")?;
+        for (name, color) in &self.colors {
+            writeln!(out, "  --{}: {};", css_name(name), color)?;
+        }
+        writeln!(out, "}}")
+    }
+}
+
+/// `name` as a custom property name: lowercase, with anything else between words
+/// turned into single dashes.
+fn css_name(name: &str) -> String {
+    let words: Vec<String> = name
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    if words.is_empty() {
+        String::from("color")
+    } else {
+        words.join("-")
+    }
+}
+
+fn write_chunk<W: Write>(out: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    out.write_all(&(data.len() as u32).to_be_bytes())?;
+    out.write_all(kind)?;
+    out.write_all(data)?;
+    // the checksum covers the kind as well as the data
+    let crc = crc32(kind.iter().chain(data));
+    out.write_all(&crc.to_be_bytes())
+}
+
+/// `data` in a zlib stream of uncompressed deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = 0xffff;
+
+    // deflate with a 32K window and no preset dictionary, and a check that makes it a
+    // multiple of 31
+    let mut stream = vec![0x78, 0x01];
+    let mut blocks = data.chunks(MAX_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        stream.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        stream.push(last as u8);
+        let len = block.len() as u16;
+        stream.extend_from_slice(&len.to_le_bytes());
+        stream.extend_from_slice(&(!len).to_le_bytes());
+        stream.extend_from_slice(block);
+    }
+    stream.extend_from_slice(&adler32(data).to_be_bytes());
+    stream
+}
+
+fn crc32<'a, I: IntoIterator<Item = &'a u8>>(bytes: I) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let carry = crc & 1;
+            crc >>= 1;
+            if carry == 1 {
+                crc ^= 0xedb8_8320;
+            }
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in bytes {
+        a = (a + byte as u32) % MOD;
+        b = (b + a) % MOD;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{adler32, crc32, zlib_stored, ImportError, Swatches};
+    use crate::color::Color;
+    use std::convert::TryInto;
+
+    fn two() -> Swatches {
+        let mut swatches = Swatches::new("Two");
+        swatches.push("Red Orange", Color::rgb(0xfc, 0x60, 0x0a));
+        swatches.push("sky", Color::rgb(0x33, 0x66, 0xff));
+        swatches
+    }
+
+    fn u32_at(bytes: &[u8], at: usize) -> u32 {
+        u32::from_be_bytes(bytes[at..at + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn checksums() {
+        assert_eq!(0xcbf4_3926, crc32(b"123456789"));
+        assert_eq!(0xae42_6082, crc32(b"IEND"));
+        assert_eq!(0x11e6_0398, adler32(b"Wikipedia"));
+        assert_eq!(1, adler32(b""));
+    }
+
+    #[test]
+    fn ppm() {
+        let mut out = Vec::new();
+        two().write_ppm(2, &mut out).unwrap();
+        let header = b"P6\n4 2\n255\n";
+        assert_eq!(&header[..], &out[..header.len()]);
+        let row = [
+            0xfc, 0x60, 0x0a, 0xfc, 0x60, 0x0a, 0x33, 0x66, 0xff, 0x33, 0x66, 0xff,
+        ];
+        assert_eq!([row, row].concat(), &out[header.len()..]);
+
+        let err = Swatches::new("none").write_ppm(8, &mut Vec::new());
+        assert_eq!(std::io::ErrorKind::InvalidInput, err.unwrap_err().kind());
+    }
+
+    #[test]
+    fn png() {
+        let mut out = Vec::new();
+        let swatches = two();
+        swatches.write_png(3, &mut out).unwrap();
+        assert_eq!(b"\x89PNG\r\n\x1a\n", &out[..8]);
+
+        // walk the chunks, checking each one's checksum
+        let mut chunks = Vec::new();
+        let mut at = 8;
+        while at < out.len() {
+            let len = u32_at(&out, at) as usize;
+            let kind = &out[at + 4..at + 8];
+            let data = &out[at + 8..at + 8 + len];
+            let crc = crc32(out[at + 4..at + 8 + len].iter());
+            assert_eq!(crc, u32_at(&out, at + 8 + len));
+            chunks.push((kind, data));
+            at += 12 + len;
+        }
+        let kinds: Vec<_> = chunks.iter().map(|(kind, _)| *kind).collect();
+        assert_eq!(vec![&b"IHDR"[..], b"IDAT", b"IEND"], kinds);
+
+        let header = chunks[0].1;
+        assert_eq!((6, 3), (u32_at(header, 0), u32_at(header, 4)));
+        assert_eq!(&[8, 2, 0, 0, 0], &header[8..]);
+
+        // a single stored block of every row with no filter
+        let zlib = chunks[1].1;
+        assert_eq!(0, u16::from_be_bytes([zlib[0], zlib[1]]) % 31);
+        assert_eq!(1, zlib[2]);
+        let len = u16::from_le_bytes([zlib[3], zlib[4]]) as usize;
+        assert_eq!(!len as u16, u16::from_le_bytes([zlib[5], zlib[6]]));
+        let scanlines = &zlib[7..7 + len];
+        let (_, _, pixels) = swatches.pixels(3).unwrap();
+        for (row, pixels) in scanlines.chunks(19).zip(pixels.chunks(18)) {
+            assert_eq!(0, row[0]);
+            assert_eq!(pixels, &row[1..]);
+        }
+        assert_eq!(adler32(scanlines), u32_at(zlib, 7 + len));
+    }
+
+    #[test]
+    fn stored_blocks() {
+        // a block holds 64K less one byte, so the last one has what's left over
+        let stored = zlib_stored(&vec![7; 0x1_0000]);
+        assert_eq!(0, stored[2]);
+        assert_eq!(
+            [1, 1, 0, 0xfe, 0xff],
+            stored[2 + 5 + 0xffff..2 + 2 * 5 + 0xffff]
+        );
+        assert_eq!(2 + 2 * 5 + 0x1_0000 + 4, stored.len());
+
+        assert_eq!(
+            vec![0x78, 1, 1, 0, 0, 0xff, 0xff, 0, 0, 0, 1],
+            zlib_stored(&[])
+        );
+    }
+
+    #[test]
+    fn gpl_round_trip() {
+        let mut out = Vec::new();
+        two().write_gpl(&mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert_eq!(
+            "GIMP Palette\nName: Two\n#\n252  96  10\tRed Orange\n 51 102 255\tsky\n",
+            text
+        );
+        assert_eq!(two(), Swatches::read_gpl(text.as_bytes()).unwrap());
+
+        let mut out = Vec::new();
+        Swatches::wheel().write_gpl(&mut out).unwrap();
+        assert_eq!(Swatches::wheel(), Swatches::read_gpl(&out[..]).unwrap());
+    }
+
+    #[test]
+    fn gpl_from_gimp() {
+        let gimp =
+            "GIMP Palette\r\nName: Mine\nColumns: 4\n# a comment\n\n0 0 0\n255\t255 255\twhite\n";
+        let swatches = Swatches::read_gpl(gimp.as_bytes()).unwrap();
+        assert_eq!("Mine", swatches.name);
+        assert_eq!(
+            vec![
+                (String::from("Untitled"), Color::rgb(0, 0, 0)),
+                (String::from("white"), Color::rgb(255, 255, 255)),
+            ],
+            swatches.colors
+        );
+
+        match Swatches::read_gpl("JASC-PAL\n".as_bytes()) {
+            Err(ImportError::NotGpl) => {}
+            other => panic!("{:?}", other),
+        }
+        match Swatches::read_gpl("GIMP Palette\n#\n255 0\n".as_bytes()) {
+            Err(ImportError::BadLine { line: 3, text }) => assert_eq!("255 0", text),
+            other => panic!("{:?}", other),
+        }
+        match Swatches::read_gpl("GIMP Palette\n256 0 0 too red\n".as_bytes()) {
+            Err(ImportError::BadLine { line: 2, .. }) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn ase() {
+        let mut out = Vec::new();
+        two().write_ase(&mut out).unwrap();
+        assert_eq!(b"ASEF\0\x01\0\0", &out[..8]);
+        assert_eq!(2, u32_at(&out, 8));
+
+        // a color block, its length, then the name "Red Orange" and its nul
+        assert_eq!([0, 1], out[12..14]);
+        let len = u32_at(&out, 14) as usize;
+        assert_eq!(2 + 11 * 2 + 4 + 3 * 4 + 2, len);
+        let block = &out[18..18 + len];
+        assert_eq!([0, 11, 0, b'R', 0, b'e'], block[..6]);
+        assert_eq!(b"RGB ", &block[24..28]);
+        let red = f32::from_be_bytes(block[28..32].try_into().unwrap());
+        assert!((red - 252.0 / 255.0).abs() < 1e-6);
+        assert_eq!([0, 2], block[len - 2..]);
+
+        // and the second block runs to the end
+        let second = 18 + len;
+        assert_eq!(out.len(), second + 6 + u32_at(&out, second + 2) as usize);
+    }
+
+    #[test]
+    fn css() {
+        let mut out = Vec::new();
+        let mut swatches = two();
+        swatches.push("  Sunset / Glow 2", Color::rgb(0xff, 0, 0x80));
+        swatches.push("!!", Color::rgb(0, 0, 0));
+        swatches.write_css(&mut out).unwrap();
+        assert_eq!(
+            ":root {\n  --red-orange: #fc600a;\n  --sky: #3366ff;\n  --sunset-glow-2: #ff0080;\n  --color: #000000;\n}\n",
+            String::from_utf8(out).unwrap()
+        );
+    }
+}
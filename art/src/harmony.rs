@@ -0,0 +1,260 @@
+// A harmony is a handful of hues spaced evenly round a color wheel. Painters use the RYB
+// wheel, where red's complement is green, and screens the RGB one, where it's cyan, so
+// every scheme works on either.
+//
+// The RYB wheel only has twelve hues, so to turn any color round it the hue of each is
+// pinned to its place on the wheel and hues between them are placed in proportion.
+
+use crate::color::{Color, Hsl};
+use crate::kinds::Hue;
+
+/// A way of picking hues that go together, named the way designers name them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Scheme {
+    /// The base and the hue across from it.
+    Complementary,
+    /// The base and its neighbours on either side.
+    Analogous,
+    /// Three hues a third of the wheel apart.
+    Triadic,
+    /// The base and the two neighbours of its complement.
+    SplitComplementary,
+    /// Two pairs of complements, a rectangle on the wheel.
+    Tetradic,
+}
+
+impl Scheme {
+    pub const ALL: [Scheme; 5] = [
+        Scheme::Complementary,
+        Scheme::Analogous,
+        Scheme::Triadic,
+        Scheme::SplitComplementary,
+        Scheme::Tetradic,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Scheme::Complementary => "complementary",
+            Scheme::Analogous => "analogous",
+            Scheme::Triadic => "triadic",
+            Scheme::SplitComplementary => "split-complementary",
+            Scheme::Tetradic => "tetradic",
+        }
+    }
+
+    /// The scheme called `name`, ignoring case.
+    pub fn parse(name: &str) -> Option<Scheme> {
+        let name = name.to_lowercase();
+        Scheme::ALL
+            .iter()
+            .copied()
+            .find(|scheme| scheme.name() == name)
+    }
+
+    /// Steps of a twelfth of the wheel clockwise from the base, the base first.
+    fn steps(self) -> &'static [usize] {
+        match self {
+            Scheme::Complementary => &[0, 6],
+            Scheme::Analogous => &[0, 1, 11],
+            Scheme::Triadic => &[0, 4, 8],
+            Scheme::SplitComplementary => &[0, 5, 7],
+            Scheme::Tetradic => &[0, 2, 6, 8],
+        }
+    }
+
+    /// The scheme's hues of the RYB wheel, starting with `base`.
+    pub fn hues(self, base: Hue) -> Vec<Hue> {
+        self.steps()
+            .iter()
+            .map(|step| Hue::WHEEL[(base.position() + step) % 12])
+            .collect()
+    }
+
+    /// The scheme on the RYB wheel, starting with `base`. The other colors keep its
+    /// saturation and lightness.
+    pub fn ryb(self, base: Color) -> Vec<Color> {
+        let hsl = base.to_hsl();
+        let angle = ryb_angle(hsl.h);
+        self.steps()
+            .iter()
+            .map(|&step| rotate(hsl, rgb_angle(angle + step as f64 * 30.0)))
+            .collect()
+    }
+
+    /// The scheme on the RGB wheel, starting with `base`. The other colors keep its
+    /// saturation and lightness.
+    pub fn rgb(self, base: Color) -> Vec<Color> {
+        let hsl = base.to_hsl();
+        self.steps()
+            .iter()
+            .map(|&step| rotate(hsl, hsl.h + step as f64 * 30.0))
+            .collect()
+    }
+}
+
+/// `base` turned to the RGB hue `h`.
+fn rotate(base: Hsl, h: f64) -> Color {
+    Color::from_hsl(Hsl { h, ..base })
+}
+
+/// Where the wheel's hues are on the RGB wheel, in degrees, clockwise from red.
+fn pins() -> [f64; 12] {
+    Hue::WHEEL.map(|hue| Color::from(hue).to_hsl().h)
+}
+
+/// Looks `angle` up in `from` and finds the same place between the matching `to`,
+/// both increasing round the circle apart from wrapping past 360.
+fn interpolate(angle: f64, from: [f64; 12], to: [f64; 12]) -> f64 {
+    let angle = angle.rem_euclid(360.0);
+    // degrees clockwise from the first pin, so the pins count up from 0
+    let offset = |a: f64, pins: [f64; 12]| (a - pins[0]).rem_euclid(360.0);
+    let along = offset(angle, from);
+    for i in 0..12 {
+        let start = offset(from[i], from);
+        let end = if i == 11 {
+            360.0
+        } else {
+            offset(from[i + 1], from)
+        };
+        if along <= end {
+            let (to_start, to_end) = (
+                offset(to[i], to),
+                if i == 11 {
+                    360.0
+                } else {
+                    offset(to[i + 1], to)
+                },
+            );
+            let t = (along - start) / (end - start);
+            return (to[0] + to_start + (to_end - to_start) * t).rem_euclid(360.0);
+        }
+    }
+    unreachable!("{} is past the last pin", along)
+}
+
+/// Where the wheel's hues are on the RYB wheel, a twelfth of it apart.
+fn ryb_positions() -> [f64; 12] {
+    std::array::from_fn(|i| i as f64 * 30.0)
+}
+
+/// How far round the RYB wheel an RGB hue is, in degrees clockwise from red.
+fn ryb_angle(rgb: f64) -> f64 {
+    interpolate(rgb, pins(), ryb_positions())
+}
+
+/// The RGB hue that's `ryb` degrees round the RYB wheel.
+fn rgb_angle(ryb: f64) -> f64 {
+    interpolate(ryb, ryb_positions(), pins())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{rgb_angle, ryb_angle, Scheme};
+    use crate::color::Color;
+    use crate::kinds::{Hue, PrimaryColor, SecondaryColor, TertiaryColor};
+
+    #[test]
+    fn hues_round_the_ryb_wheel() {
+        let red = Hue::from(PrimaryColor::Red);
+        let names = |scheme: Scheme| {
+            scheme
+                .hues(red)
+                .iter()
+                .map(|hue| hue.name())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec!["red", "green"], names(Scheme::Complementary));
+        assert_eq!(
+            vec!["red", "red-orange", "red-purple"],
+            names(Scheme::Analogous)
+        );
+        assert_eq!(vec!["red", "yellow", "blue"], names(Scheme::Triadic));
+        assert_eq!(
+            vec!["red", "yellow-green", "blue-green"],
+            names(Scheme::SplitComplementary)
+        );
+        assert_eq!(
+            vec!["red", "orange", "green", "blue"],
+            names(Scheme::Tetradic)
+        );
+
+        for &hue in Hue::WHEEL.iter() {
+            assert_eq!(vec![hue, hue.complement()], Scheme::Complementary.hues(hue));
+        }
+    }
+
+    #[test]
+    fn angles_round_trip() {
+        for &hue in Hue::WHEEL.iter() {
+            let rgb = Color::from(hue).to_hsl().h;
+            let ryb = hue.position() as f64 * 30.0;
+            assert!((ryb_angle(rgb) - ryb).abs() < 1e-9, "{}", hue);
+            assert!((rgb_angle(ryb) - rgb).abs() < 1e-9, "{}", hue);
+        }
+        for degrees in 0..360 {
+            let rgb = degrees as f64;
+            let back = rgb_angle(ryb_angle(rgb));
+            assert!((back - rgb).abs() < 1e-9, "{} came back as {}", rgb, back);
+        }
+        // green is a third of the RGB wheel from red but half of the RYB one
+        assert!((ryb_angle(120.0) - 180.0).abs() < 15.0);
+    }
+
+    #[test]
+    fn ryb_schemes_land_on_the_wheel() {
+        // a wheel color's complement has the hue of the complementary wheel color
+        for &hue in Hue::WHEEL.iter() {
+            let scheme = Scheme::Complementary.ryb(Color::from(hue));
+            assert_eq!(Color::from(hue), scheme[0]);
+            let expected = Color::from(hue.complement()).to_hsl().h;
+            assert!((scheme[1].to_hsl().h - expected).abs() < 1.0, "{}", hue);
+        }
+
+        let base = Hue::from(TertiaryColor::BlueGreen);
+        let triad = Scheme::Triadic.ryb(Color::from(base));
+        for (color, hue) in triad.into_iter().zip(Scheme::Triadic.hues(base)) {
+            let expected = Color::from(hue).to_hsl().h;
+            assert!((color.to_hsl().h - expected).abs() < 1.0, "{}", hue);
+        }
+
+        // a gray has no hue to turn
+        let gray = Color::rgb(0x80, 0x80, 0x80);
+        assert_eq!(vec![gray; 4], Scheme::Tetradic.ryb(gray));
+    }
+
+    #[test]
+    fn rgb_schemes() {
+        let red = Color::rgb(255, 0, 0);
+        assert_eq!(
+            vec![red, Color::rgb(0, 255, 255)],
+            Scheme::Complementary.rgb(red)
+        );
+        assert_eq!(
+            vec![red, Color::rgb(0, 255, 0), Color::rgb(0, 0, 255)],
+            Scheme::Triadic.rgb(red)
+        );
+        assert_eq!(
+            vec![red, Color::rgb(255, 128, 0), Color::rgb(255, 0, 128)],
+            Scheme::Analogous.rgb(red)
+        );
+
+        // saturation and lightness stay as they were
+        let base = Color::from(SecondaryColor::Orange);
+        for color in Scheme::SplitComplementary.rgb(base) {
+            let (hsl, expected) = (color.to_hsl(), base.to_hsl());
+            assert!((hsl.s - expected.s).abs() < 0.02 && (hsl.l - expected.l).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn parses_names() {
+        for &scheme in Scheme::ALL.iter() {
+            assert_eq!(Some(scheme), Scheme::parse(scheme.name()));
+        }
+        assert_eq!(
+            Some(Scheme::SplitComplementary),
+            Scheme::parse("Split-Complementary")
+        );
+        assert_eq!(None, Scheme::parse("square"));
+    }
+}
@@ -0,0 +1,174 @@
+use std::fmt;
+
+/// The primary colors according to the RYB color model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PrimaryColor {
+    Red,
+    Yellow,
+    Blue,
+}
+
+/// The secondary colors according to the RYB color model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SecondaryColor {
+    Orange,
+    Green,
+    Purple,
+}
+
+/// The tertiary colors according to the RYB color model,
+/// each a primary mixed with a secondary next to it on the color wheel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TertiaryColor {
+    RedOrange,
+    YellowOrange,
+    YellowGreen,
+    BlueGreen,
+    BluePurple,
+    RedPurple,
+}
+
+/// Any of the twelve colors on the RYB color wheel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Hue {
+    Primary(PrimaryColor),
+    Secondary(SecondaryColor),
+    Tertiary(TertiaryColor),
+}
+
+impl PrimaryColor {
+    pub const ALL: [PrimaryColor; 3] =
+        [PrimaryColor::Red, PrimaryColor::Yellow, PrimaryColor::Blue];
+}
+
+impl SecondaryColor {
+    pub const ALL: [SecondaryColor; 3] = [
+        SecondaryColor::Orange,
+        SecondaryColor::Green,
+        SecondaryColor::Purple,
+    ];
+}
+
+impl TertiaryColor {
+    pub const ALL: [TertiaryColor; 6] = [
+        TertiaryColor::RedOrange,
+        TertiaryColor::YellowOrange,
+        TertiaryColor::YellowGreen,
+        TertiaryColor::BlueGreen,
+        TertiaryColor::BluePurple,
+        TertiaryColor::RedPurple,
+    ];
+}
+
+impl Hue {
+    /// The color wheel, clockwise from red.
+    pub const WHEEL: [Hue; 12] = [
+        Hue::Primary(PrimaryColor::Red),
+        Hue::Tertiary(TertiaryColor::RedOrange),
+        Hue::Secondary(SecondaryColor::Orange),
+        Hue::Tertiary(TertiaryColor::YellowOrange),
+        Hue::Primary(PrimaryColor::Yellow),
+        Hue::Tertiary(TertiaryColor::YellowGreen),
+        Hue::Secondary(SecondaryColor::Green),
+        Hue::Tertiary(TertiaryColor::BlueGreen),
+        Hue::Primary(PrimaryColor::Blue),
+        Hue::Tertiary(TertiaryColor::BluePurple),
+        Hue::Secondary(SecondaryColor::Purple),
+        Hue::Tertiary(TertiaryColor::RedPurple),
+    ];
+
+    /// The name painters use, like `red-orange`.
+    pub fn name(self) -> &'static str {
+        const NAMES: [&str; 12] = [
+            "red",
+            "red-orange",
+            "orange",
+            "yellow-orange",
+            "yellow",
+            "yellow-green",
+            "green",
+            "blue-green",
+            "blue",
+            "blue-purple",
+            "purple",
+            "red-purple",
+        ];
+        NAMES[self.position()]
+    }
+
+    /// The hue called `name`, ignoring case.
+    pub fn parse(name: &str) -> Option<Hue> {
+        let name = name.to_lowercase();
+        Hue::WHEEL.iter().copied().find(|hue| hue.name() == name)
+    }
+
+    /// Where the hue is on the `WHEEL`, 0 for red up to 11 for red-purple.
+    pub fn position(self) -> usize {
+        Hue::WHEEL.iter().position(|&hue| hue == self).unwrap()
+    }
+
+    /// The hue across the wheel.
+    pub fn complement(self) -> Hue {
+        Hue::WHEEL[(self.position() + 6) % 12]
+    }
+
+    /// Mixes two hues in equal amounts, which lands halfway between them on the wheel,
+    /// going the short way round.
+    ///
+    /// Complements have no short way round, they mix to a muddy brown, and hues an odd
+    /// number of steps apart land between two hues of the wheel. Neither is a `Hue`.
+    pub fn mix(self, other: Hue) -> Option<Hue> {
+        let (from, to) = (self.position(), other.position());
+        // steps from `self` to `other` going clockwise
+        let steps = (to + 12 - from) % 12;
+        if steps % 2 == 1 || steps == 6 {
+            None
+        } else if steps < 6 {
+            Some(Hue::WHEEL[(from + steps / 2) % 12])
+        } else {
+            Some(Hue::WHEEL[(to + (12 - steps) / 2) % 12])
+        }
+    }
+}
+
+impl From<PrimaryColor> for Hue {
+    fn from(color: PrimaryColor) -> Hue {
+        Hue::Primary(color)
+    }
+}
+
+impl From<SecondaryColor> for Hue {
+    fn from(color: SecondaryColor) -> Hue {
+        Hue::Secondary(color)
+    }
+}
+
+impl From<TertiaryColor> for Hue {
+    fn from(color: TertiaryColor) -> Hue {
+        Hue::Tertiary(color)
+    }
+}
+
+impl fmt::Display for Hue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl fmt::Display for PrimaryColor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Hue::from(*self).fmt(f)
+    }
+}
+
+impl fmt::Display for SecondaryColor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Hue::from(*self).fmt(f)
+    }
+}
+
+impl fmt::Display for TertiaryColor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Hue::from(*self).fmt(f)
+    }
+}
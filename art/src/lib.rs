@@ -2,31 +2,24 @@
 //!
 //! A library for modeling artistic concepts.
 
+pub use color::{Color, Hsl};
+pub use export::Swatches;
+pub use harmony::Scheme;
+pub use kinds::Hue;
 pub use kinds::PrimaryColor;
 pub use kinds::SecondaryColor;
+pub use kinds::TertiaryColor;
+pub use mixing::{Mixture, Palette, Ryb};
+pub use perception::Lab;
 pub use utils::mix;
+pub use vision::Deficiency;
 
-pub mod kinds {
-    /// The primary colors according to the RYB color model.
-    pub enum PrimaryColor {
-        Red,
-        Yellow,
-        Blue,
-    }
-
-    /// The secondary colors according to the RYB color model.
-    pub enum SecondaryColor {
-        Orange,
-        Green,
-        Purple,
-    }
-}
-
-pub mod utils {
-    use crate::kinds::{PrimaryColor, SecondaryColor};
-
-    /// Combines two primary colors in equal amounts to create a secondary color.
-    pub fn mix(c1: PrimaryColor, c2: PrimaryColor) -> SecondaryColor {
-        SecondaryColor::Green
-    }
-}
+pub mod cli;
+pub mod color;
+pub mod export;
+pub mod harmony;
+pub mod kinds;
+pub mod mixing;
+pub mod perception;
+pub mod utils;
+pub mod vision;
@@ -1,14 +1,24 @@
-// use art::kinds::PrimaryColor;
-// use art::utils::mix;
-
-use art::{mix, PrimaryColor};
+use art::cli::{self, Options, Style};
+use std::io::{self, IsTerminal};
+use std::{env, process};
 
 fn main() {
-    // let red = PrimaryColor::Red;
-    // let yellow = PrimaryColor::Yellow;
-    // mix(red, yellow);
+    let mut options = Options::from_args(env::args()).unwrap_or_else(|err| {
+        eprintln!("Problem parsing arguments: {}", err);
+        eprintln!("{}", cli::USAGE);
+        process::exit(1);
+    });
+
+    // escape codes only mean something to a terminal, and NO_COLOR asks for none at all
+    let stdout = io::stdout();
+    if !stdout.is_terminal() || env::var_os("NO_COLOR").is_some() {
+        if let Style::Text { .. } = options.style {
+            options.style = Style::Text { swatches: false };
+        }
+    }
 
-    let red = PrimaryColor::Red;
-    let yellow = PrimaryColor::Yellow;
-    mix(red, yellow);
+    if let Err(err) = cli::run(&options.command, options.style, &mut stdout.lock()) {
+        eprintln!("Problem: {}", err);
+        process::exit(1);
+    }
 }
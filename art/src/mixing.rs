@@ -0,0 +1,382 @@
+// Paints are modeled as amounts of red, yellow and blue pigment, and a mixture's pigment is
+// the average of its paints', weighted by how many parts of each went in. That makes mixing
+// subtractive the way paint is: blue and yellow make green, not the gray that averaging
+// screen colors would.
+//
+// Pigment is turned into a screen color by interpolating across the RYB cube (Gossett and
+// Chen, "Paint Inspired Color Compositing"): each corner of the unit cube is a color, from
+// white for no pigment at all to a dark brown for all three. The corners here are the
+// wheel's own colors, so equal parts of two primaries come out exactly as the secondary
+// between them.
+
+use crate::color::Color;
+use crate::kinds::{Hue, PrimaryColor, SecondaryColor, TertiaryColor};
+use std::error::Error;
+use std::fmt;
+
+/// An amount of red, yellow and blue pigment, each from 0 to 1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ryb {
+    pub r: f64,
+    pub y: f64,
+    pub b: f64,
+}
+
+impl Ryb {
+    pub const WHITE: Ryb = Ryb::new(0.0, 0.0, 0.0);
+    pub const BLACK: Ryb = Ryb::new(1.0, 1.0, 1.0);
+
+    pub const fn new(r: f64, y: f64, b: f64) -> Ryb {
+        Ryb { r, y, b }
+    }
+
+    /// How strong the paint is: the amount of its strongest pigment.
+    fn strength(self) -> f64 {
+        self.r.max(self.y).max(self.b)
+    }
+
+    /// How the pigment looks on screen.
+    pub fn to_color(self) -> Color {
+        let corner = |color: Color| [color.r as f64, color.g as f64, color.b as f64];
+        let lerp = |from: [f64; 3], to: [f64; 3], t: f64| {
+            [0, 1, 2].map(|i| from[i] + (to[i] - from[i]) * t)
+        };
+        let white = [255.0; 3];
+        let black = corner(Color::rgb(0x33, 0x18, 0x00));
+        let red = corner(Color::from(PrimaryColor::Red));
+        let yellow = corner(Color::from(PrimaryColor::Yellow));
+        let blue = corner(Color::from(PrimaryColor::Blue));
+        let orange = corner(Color::from(SecondaryColor::Orange));
+        let green = corner(Color::from(SecondaryColor::Green));
+        let purple = corner(Color::from(SecondaryColor::Purple));
+
+        let [r, y, b] = [self.r, self.y, self.b].map(|amount| amount.clamp(0.0, 1.0));
+        // along the red edges of the cube, then yellow, then blue
+        let no_yellow_no_blue = lerp(white, red, r);
+        let yellow_no_blue = lerp(yellow, orange, r);
+        let blue_no_yellow = lerp(blue, purple, r);
+        let yellow_and_blue = lerp(green, black, r);
+        let no_blue = lerp(no_yellow_no_blue, yellow_no_blue, y);
+        let with_blue = lerp(blue_no_yellow, yellow_and_blue, y);
+        let [r, g, b] = lerp(no_blue, with_blue, b).map(|c| c.round() as u8);
+        Color::rgb(r, g, b)
+    }
+}
+
+impl From<Hue> for Ryb {
+    fn from(hue: Hue) -> Ryb {
+        // every other step round the wheel is a primary or a secondary,
+        // the tertiaries between them have half of the pigment they don't share
+        const WHEEL: [Ryb; 12] = [
+            Ryb::new(1.0, 0.0, 0.0),
+            Ryb::new(1.0, 0.5, 0.0),
+            Ryb::new(1.0, 1.0, 0.0),
+            Ryb::new(0.5, 1.0, 0.0),
+            Ryb::new(0.0, 1.0, 0.0),
+            Ryb::new(0.0, 1.0, 0.5),
+            Ryb::new(0.0, 1.0, 1.0),
+            Ryb::new(0.0, 0.5, 1.0),
+            Ryb::new(0.0, 0.0, 1.0),
+            Ryb::new(0.5, 0.0, 1.0),
+            Ryb::new(1.0, 0.0, 1.0),
+            Ryb::new(1.0, 0.0, 0.5),
+        ];
+        WHEEL[hue.position()]
+    }
+}
+
+impl From<PrimaryColor> for Ryb {
+    fn from(color: PrimaryColor) -> Ryb {
+        Ryb::from(Hue::from(color))
+    }
+}
+
+impl From<SecondaryColor> for Ryb {
+    fn from(color: SecondaryColor) -> Ryb {
+        Ryb::from(Hue::from(color))
+    }
+}
+
+impl From<TertiaryColor> for Ryb {
+    fn from(color: TertiaryColor) -> Ryb {
+        Ryb::from(Hue::from(color))
+    }
+}
+
+/// Paints mixed in any proportions, like 3 parts red to 1 part blue.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Mixture {
+    parts: Vec<(Ryb, f64)>,
+}
+
+impl Mixture {
+    pub fn new() -> Mixture {
+        Mixture::default()
+    }
+
+    /// Adds `parts` of `paint` to the mixture.
+    ///
+    /// # Panics
+    ///
+    /// If `parts` is negative or not finite.
+    pub fn add<P: Into<Ryb>>(mut self, paint: P, parts: f64) -> Mixture {
+        assert!(
+            parts.is_finite() && parts >= 0.0,
+            "can't mix in {} parts",
+            parts
+        );
+        self.parts.push((paint.into(), parts));
+        self
+    }
+
+    /// The pigment of the mixed paint, or `None` if nothing has been added yet.
+    ///
+    /// Each pigment is averaged over the parts, then the mixture is made as strong as
+    /// its paints were on average, so that two full-strength paints make another one
+    /// while white thins them down.
+    pub fn pigment(&self) -> Option<Ryb> {
+        let total: f64 = self.parts.iter().map(|&(_, parts)| parts).sum();
+        if total == 0.0 {
+            return None;
+        }
+
+        let average = |amount: fn(&Ryb) -> f64| {
+            let sum: f64 = self
+                .parts
+                .iter()
+                .map(|(paint, parts)| amount(paint) * parts)
+                .sum();
+            sum / total
+        };
+        let mixed = Ryb::new(average(|p| p.r), average(|p| p.y), average(|p| p.b));
+        let strength = average(|p| p.strength());
+        if mixed.strength() == 0.0 {
+            return Some(Ryb::WHITE);
+        }
+
+        let scale = strength / mixed.strength();
+        Some(Ryb::new(mixed.r * scale, mixed.y * scale, mixed.b * scale))
+    }
+
+    /// How the mixed paint looks on screen, or `None` if nothing has been added yet.
+    pub fn color(&self) -> Option<Color> {
+        self.pigment().map(Ryb::to_color)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum MixError {
+    UnknownPaint(String),
+    /// No parts of anything.
+    Empty,
+}
+
+impl fmt::Display for MixError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MixError::UnknownPaint(name) => write!(f, "there's no paint called `{}`", name),
+            MixError::Empty => write!(f, "there's nothing to mix"),
+        }
+    }
+}
+
+impl Error for MixError {}
+
+/// Paints with names, to mix by recipe.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Palette {
+    paints: Vec<(String, Ryb)>,
+}
+
+impl Default for Palette {
+    /// Every hue of the RYB wheel, plus white and black.
+    fn default() -> Palette {
+        let mut palette = Palette { paints: Vec::new() };
+        for &hue in Hue::WHEEL.iter() {
+            palette.add(hue.name(), hue);
+        }
+        palette.add("white", Ryb::WHITE);
+        palette.add("black", Ryb::BLACK);
+        palette
+    }
+}
+
+impl Palette {
+    /// A palette with no paints.
+    pub fn empty() -> Palette {
+        Palette { paints: Vec::new() }
+    }
+
+    /// Adds a paint, replacing any other with the same name, ignoring case like `get` does.
+    pub fn add<P: Into<Ryb>>(&mut self, name: &str, paint: P) {
+        let paint = (String::from(name), paint.into());
+        match self
+            .paints
+            .iter_mut()
+            .find(|(existing, _)| existing.eq_ignore_ascii_case(name))
+        {
+            Some(existing) => *existing = paint,
+            None => self.paints.push(paint),
+        }
+    }
+
+    /// The paint called `name`, ignoring case.
+    pub fn get(&self, name: &str) -> Option<Ryb> {
+        self.paints
+            .iter()
+            .find(|(existing, _)| existing.eq_ignore_ascii_case(name))
+            .map(|&(_, paint)| paint)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.paints.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Mixes a recipe of paint names and parts, like `[("red", 3.0), ("blue", 1.0)]`.
+    pub fn mix(&self, recipe: &[(&str, f64)]) -> Result<Mixture, MixError> {
+        let mut mixture = Mixture::new();
+        for &(name, parts) in recipe {
+            let paint = self
+                .get(name)
+                .ok_or_else(|| MixError::UnknownPaint(String::from(name)))?;
+            mixture = mixture.add(paint, parts);
+        }
+        match mixture.pigment() {
+            Some(_) => Ok(mixture),
+            None => Err(MixError::Empty),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MixError, Mixture, Palette, Ryb};
+    use crate::color::Color;
+    use crate::kinds::{Hue, PrimaryColor, SecondaryColor};
+    use crate::utils::mix;
+    use PrimaryColor::*;
+
+    fn near(expected: Color, actual: Color) -> bool {
+        let d = |a: u8, b: u8| (a as i32 - b as i32).abs();
+        d(expected.r, actual.r) <= 1 && d(expected.g, actual.g) <= 1 && d(expected.b, actual.b) <= 1
+    }
+
+    #[test]
+    fn corners_are_the_wheel() {
+        assert_eq!(Color::rgb(255, 255, 255), Ryb::WHITE.to_color());
+        for &hue in Hue::WHEEL.iter() {
+            assert!(
+                near(Color::from(hue), Ryb::from(hue).to_color()),
+                "{} is {}",
+                hue,
+                Ryb::from(hue).to_color()
+            );
+        }
+    }
+
+    #[test]
+    fn equal_parts_agree_with_the_wheel() {
+        for &c1 in PrimaryColor::ALL.iter() {
+            for &c2 in PrimaryColor::ALL.iter() {
+                let mixed = Mixture::new().add(c1, 1.0).add(c2, 1.0).color();
+                assert_eq!(Some(Color::from(mix(c1, c2))), mixed);
+            }
+        }
+
+        // a primary and a secondary next to it make the tertiary between them, further
+        // apart on the wheel the third pigment muddies the mix, as it does in real paint
+        for (i, &a) in Hue::WHEEL.iter().enumerate().step_by(2) {
+            let b = Hue::WHEEL[(i + 2) % 12];
+            let mixed = Mixture::new().add(a, 1.0).add(b, 1.0).color().unwrap();
+            assert!(
+                near(Color::from(Hue::WHEEL[(i + 1) % 12]), mixed),
+                "{} and {}",
+                a,
+                b
+            );
+        }
+    }
+
+    #[test]
+    fn ratios() {
+        let three_to_one = Mixture::new().add(Red, 3.0).add(Blue, 1.0);
+        assert_eq!(Some(Ryb::new(1.0, 0.0, 1.0 / 3.0)), three_to_one.pigment());
+        // only the proportions matter, not the amounts or the order
+        assert_eq!(
+            three_to_one.color(),
+            Mixture::new().add(Blue, 0.5).add(Red, 1.5).color()
+        );
+
+        // more red than purple makes it redder than red-purple, the halfway point
+        let red = Color::from(Red).to_hsl().h;
+        let red_purple = Color::from(Hue::from(crate::TertiaryColor::RedPurple))
+            .to_hsl()
+            .h;
+        let hue = three_to_one.color().unwrap().to_hsl().h;
+        assert!(hue > red_purple && hue < red + 360.0, "{}", hue);
+
+        // parts add up when the same paint goes in twice
+        assert_eq!(
+            Mixture::new().add(Red, 2.0).add(Blue, 1.0).color(),
+            Mixture::new()
+                .add(Red, 1.0)
+                .add(Red, 1.0)
+                .add(Blue, 1.0)
+                .color()
+        );
+    }
+
+    #[test]
+    fn white_and_black() {
+        // a lot of white makes a tint, pink here
+        let pink = Mixture::new().add(Red, 1.0).add(Ryb::WHITE, 3.0);
+        assert_eq!(Some(Ryb::new(0.25, 0.0, 0.0)), pink.pigment());
+        let pink = pink.color().unwrap().to_hsl();
+        assert!(pink.l > Color::from(Red).to_hsl().l);
+
+        // complements make black, or the brown the cube has for it
+        let complements = Mixture::new().add(Red, 1.0).add(SecondaryColor::Green, 1.0);
+        assert_eq!(Some(Ryb::BLACK), complements.pigment());
+
+        assert_eq!(
+            Some(Ryb::WHITE),
+            Mixture::new().add(Ryb::WHITE, 2.0).pigment()
+        );
+        assert_eq!(None, Mixture::new().pigment());
+        assert_eq!(None, Mixture::new().add(Red, 0.0).color());
+    }
+
+    #[test]
+    #[should_panic(expected = "can't mix in -1 parts")]
+    fn negative_parts() {
+        Mixture::new().add(Red, -1.0);
+    }
+
+    #[test]
+    fn palette_recipes() {
+        let palette = Palette::default();
+        assert_eq!(14, palette.names().count());
+        assert_eq!(Some(Ryb::new(1.0, 0.5, 0.0)), palette.get("Red-Orange"));
+
+        let recipe = palette.mix(&[("red", 3.0), ("blue", 1.0)]).unwrap();
+        assert_eq!(
+            Mixture::new().add(Red, 3.0).add(Blue, 1.0).color(),
+            recipe.color()
+        );
+
+        assert_eq!(
+            Err(MixError::UnknownPaint(String::from("teal"))),
+            palette.mix(&[("red", 1.0), ("teal", 1.0)])
+        );
+        assert_eq!(Err(MixError::Empty), palette.mix(&[]));
+
+        let mut palette = Palette::empty();
+        palette.add("mine", Ryb::new(0.2, 0.4, 0.6));
+        palette.add("mine", Ryb::new(0.1, 0.1, 0.1));
+        assert_eq!(vec!["mine"], palette.names().collect::<Vec<_>>());
+        assert_eq!(Some(Ryb::new(0.1, 0.1, 0.1)), palette.get("mine"));
+        palette.add("Mine", Ryb::new(0.3, 0.3, 0.3));
+        assert_eq!(vec!["Mine"], palette.names().collect::<Vec<_>>());
+        assert_eq!(Some(Ryb::new(0.3, 0.3, 0.3)), palette.get("mine"));
+    }
+}
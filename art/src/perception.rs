@@ -0,0 +1,386 @@
+// How different colors look to people rather than how far apart their numbers are.
+//
+// CIELAB spaces colors so that distance roughly follows perceived difference, and ΔE
+// measures it: CIE76 is the plain distance, CIEDE2000 corrects it where Lab is uneven,
+// in the blues and for saturated colors. A ΔE of about 2.3 is just noticeable.
+//
+// WCAG contrast compares the relative luminance of text and its background, from 1:1 for
+// the same color up to 21:1 for black on white.
+
+use crate::color::Color;
+use std::f64::consts::PI;
+
+/// A color in CIELAB under the D65 white point of sRGB.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Lab {
+    /// Lightness, from 0 for black to 100 for white.
+    pub l: f64,
+    /// From green when negative to red.
+    pub a: f64,
+    /// From blue when negative to yellow.
+    pub b: f64,
+}
+
+/// The D65 white point in XYZ.
+const WHITE: [f64; 3] = [0.95047, 1.0, 1.08883];
+
+/// Where the cube root in Lab gives way to a straight line near black.
+const DELTA: f64 = 6.0 / 29.0;
+
+fn linear(channel: u8) -> f64 {
+    let c = channel as f64 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn gamma(linear: f64) -> u8 {
+    let c = if linear <= 0.0031308 {
+        linear * 12.92
+    } else {
+        1.055 * linear.powf(1.0 / 2.4) - 0.055
+    };
+    (c.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+impl Color {
+    /// The channels without sRGB's gamma, proportional to the light they give off.
+    pub(crate) fn to_linear(self) -> [f64; 3] {
+        [self.r, self.g, self.b].map(linear)
+    }
+
+    /// The nearest screen color to linear channels, clamping them to what it can show.
+    pub(crate) fn from_linear(rgb: [f64; 3]) -> Color {
+        let [r, g, b] = rgb.map(gamma);
+        Color::rgb(r, g, b)
+    }
+
+    /// The color in CIE XYZ, with Y from 0 to 1.
+    fn to_xyz(self) -> [f64; 3] {
+        let [r, g, b] = self.to_linear();
+        [
+            0.4124564 * r + 0.3575761 * g + 0.1804375 * b,
+            0.2126729 * r + 0.7151522 * g + 0.0721750 * b,
+            0.0193339 * r + 0.1191920 * g + 0.9503041 * b,
+        ]
+    }
+
+    pub fn to_lab(self) -> Lab {
+        let f = |t: f64| {
+            if t > DELTA.powi(3) {
+                t.cbrt()
+            } else {
+                t / (3.0 * DELTA * DELTA) + 4.0 / 29.0
+            }
+        };
+        let xyz = self.to_xyz();
+        let [fx, fy, fz] = [0, 1, 2].map(|i| f(xyz[i] / WHITE[i]));
+        Lab {
+            l: 116.0 * fy - 16.0,
+            a: 500.0 * (fx - fy),
+            b: 200.0 * (fy - fz),
+        }
+    }
+
+    /// The nearest screen color to `lab`, clamping anything a screen can't show.
+    pub fn from_lab(lab: Lab) -> Color {
+        let f_inverse = |t: f64| {
+            if t > DELTA {
+                t.powi(3)
+            } else {
+                3.0 * DELTA * DELTA * (t - 4.0 / 29.0)
+            }
+        };
+        let fy = (lab.l + 16.0) / 116.0;
+        let x = WHITE[0] * f_inverse(fy + lab.a / 500.0);
+        let y = WHITE[1] * f_inverse(fy);
+        let z = WHITE[2] * f_inverse(fy - lab.b / 200.0);
+        let r = 3.2404542 * x - 1.5371385 * y - 0.4985314 * z;
+        let g = -0.9692660 * x + 1.8760108 * y + 0.0415560 * z;
+        let b = 0.0556434 * x - 0.2040259 * y + 1.0572252 * z;
+        Color::from_linear([r, g, b])
+    }
+
+    /// How bright the color is to the eye, from 0 for black to 1 for white, as WCAG
+    /// defines it.
+    pub fn relative_luminance(self) -> f64 {
+        let [r, g, b] = self.to_linear();
+        0.2126 * r + 0.7152 * g + 0.0722 * b
+    }
+
+    /// The WCAG contrast ratio between this color and `other`, from 1 to 21. It's the
+    /// same whichever way round they are.
+    pub fn contrast(self, other: Color) -> f64 {
+        let (a, b) = (self.relative_luminance(), other.relative_luminance());
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+}
+
+impl Lab {
+    /// ΔE*ab, the straight-line distance between two colors in Lab.
+    pub fn delta_e_76(self, other: Lab) -> f64 {
+        ((self.l - other.l).powi(2) + (self.a - other.a).powi(2) + (self.b - other.b).powi(2))
+            .sqrt()
+    }
+
+    /// ΔE00, following Sharma, Wu and Dalal's notes on implementing it, with the
+    /// weighting factors all 1.
+    pub fn delta_e_2000(self, other: Lab) -> f64 {
+        let chroma = |lab: Lab| lab.a.hypot(lab.b);
+        let mean_chroma = (chroma(self) + chroma(other)) / 2.0;
+        let c7 = mean_chroma.powi(7);
+        // stretch a* so that grays get hues that behave
+        let g = 0.5 * (1.0 - (c7 / (c7 + 25f64.powi(7))).sqrt());
+
+        let prime = |lab: Lab| {
+            let a = lab.a * (1.0 + g);
+            let c = a.hypot(lab.b);
+            let h = if a == 0.0 && lab.b == 0.0 {
+                0.0
+            } else {
+                lab.b.atan2(a).to_degrees().rem_euclid(360.0)
+            };
+            (c, h)
+        };
+        let (c1, h1) = prime(self);
+        let (c2, h2) = prime(other);
+
+        let delta_l = other.l - self.l;
+        let delta_c = c2 - c1;
+        let delta_h_angle = if c1 * c2 == 0.0 {
+            0.0
+        } else if (h2 - h1).abs() <= 180.0 {
+            h2 - h1
+        } else if h2 <= h1 {
+            h2 - h1 + 360.0
+        } else {
+            h2 - h1 - 360.0
+        };
+        let delta_h = 2.0 * (c1 * c2).sqrt() * (delta_h_angle.to_radians() / 2.0).sin();
+
+        let mean_l = (self.l + other.l) / 2.0;
+        let mean_c = (c1 + c2) / 2.0;
+        let mean_h = if c1 * c2 == 0.0 {
+            h1 + h2
+        } else if (h1 - h2).abs() <= 180.0 {
+            (h1 + h2) / 2.0
+        } else if h1 + h2 < 360.0 {
+            (h1 + h2 + 360.0) / 2.0
+        } else {
+            (h1 + h2 - 360.0) / 2.0
+        };
+
+        let t = 1.0 - 0.17 * (mean_h - 30.0).to_radians().cos()
+            + 0.24 * (2.0 * mean_h).to_radians().cos()
+            + 0.32 * (3.0 * mean_h + 6.0).to_radians().cos()
+            - 0.20 * (4.0 * mean_h - 63.0).to_radians().cos();
+        let s_l = 1.0 + 0.015 * (mean_l - 50.0).powi(2) / (20.0 + (mean_l - 50.0).powi(2)).sqrt();
+        let s_c = 1.0 + 0.045 * mean_c;
+        let s_h = 1.0 + 0.015 * mean_c * t;
+
+        // the blues need their hue and chroma differences turned towards each other
+        let delta_theta = 30.0 * (-((mean_h - 275.0) / 25.0).powi(2)).exp();
+        let mean_c7 = mean_c.powi(7);
+        let r_c = 2.0 * (mean_c7 / (mean_c7 + 25f64.powi(7))).sqrt();
+        let r_t = -r_c * (2.0 * delta_theta * PI / 180.0).sin();
+
+        let (l, c, h) = (delta_l / s_l, delta_c / s_c, delta_h / s_h);
+        (l * l + c * c + h * h + r_t * c * h).sqrt()
+    }
+}
+
+/// How big the text is, since large text can get away with less contrast. Large is at
+/// least 18pt, or 14pt in bold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextSize {
+    Normal,
+    Large,
+}
+
+/// The WCAG conformance levels for contrast, from the weaker to the stronger.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    AA,
+    AAA,
+}
+
+impl Level {
+    /// The least contrast ratio text of `size` needs to meet the level.
+    pub fn minimum(self, size: TextSize) -> f64 {
+        match (self, size) {
+            (Level::AA, TextSize::Large) => 3.0,
+            (Level::AA, TextSize::Normal) | (Level::AAA, TextSize::Large) => 4.5,
+            (Level::AAA, TextSize::Normal) => 7.0,
+        }
+    }
+
+    /// The best level `ratio` meets for text of `size`, or `None` if it fails.
+    pub fn of(ratio: f64, size: TextSize) -> Option<Level> {
+        [Level::AAA, Level::AA]
+            .iter()
+            .copied()
+            .find(|level| ratio >= level.minimum(size))
+    }
+}
+
+/// How well one pair of a palette's colors works as text on a background.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pairing {
+    /// Indexes into the palette, the first the smaller.
+    pub colors: (usize, usize),
+    pub ratio: f64,
+    pub level: Option<Level>,
+}
+
+/// Checks every pair of `colors` as text of `size` on a background of the other. Contrast
+/// is the same either way round, so each pair comes up once.
+pub fn check_contrast(colors: &[Color], size: TextSize) -> Vec<Pairing> {
+    let mut pairings = Vec::new();
+    for (i, &first) in colors.iter().enumerate() {
+        for (j, &second) in colors.iter().enumerate().skip(i + 1) {
+            let ratio = first.contrast(second);
+            pairings.push(Pairing {
+                colors: (i, j),
+                ratio,
+                level: Level::of(ratio, size),
+            });
+        }
+    }
+    pairings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_contrast, Lab, Level, TextSize};
+    use crate::color::Color;
+    use crate::kinds::Hue;
+
+    fn lab(l: f64, a: f64, b: f64) -> Lab {
+        Lab { l, a, b }
+    }
+
+    fn close(expected: f64, actual: f64, within: f64) -> bool {
+        (expected - actual).abs() < within
+    }
+
+    #[test]
+    fn lab_conversions() {
+        let white = Color::rgb(255, 255, 255).to_lab();
+        assert!(
+            close(100.0, white.l, 1e-3) && close(0.0, white.a, 1e-3) && close(0.0, white.b, 1e-3)
+        );
+        assert_eq!(lab(0.0, 0.0, 0.0), Color::rgb(0, 0, 0).to_lab());
+
+        let red = Color::rgb(255, 0, 0).to_lab();
+        assert!(close(53.2408, red.l, 1e-3), "{:?}", red);
+        assert!(close(80.0925, red.a, 1e-3), "{:?}", red);
+        assert!(close(67.2032, red.b, 1e-3), "{:?}", red);
+
+        for &hue in Hue::WHEEL.iter() {
+            let color = Color::from(hue);
+            assert_eq!(color, Color::from_lab(color.to_lab()));
+        }
+        // out of what a screen shows
+        assert_eq!(
+            Color::rgb(255, 255, 255),
+            Color::from_lab(lab(120.0, 0.0, 0.0))
+        );
+    }
+
+    #[test]
+    fn ciede2000_reference_pairs() {
+        // from Sharma, Wu and Dalal's test data
+        let pairs = [
+            (
+                lab(50.0, 2.6772, -79.7751),
+                lab(50.0, 0.0, -82.7485),
+                2.0425,
+            ),
+            (
+                lab(50.0, 3.1571, -77.2803),
+                lab(50.0, 0.0, -82.7485),
+                2.8615,
+            ),
+            (lab(50.0, 0.0, 0.0), lab(50.0, -1.0, 2.0), 2.3669),
+            (lab(50.0, -1.0, 2.0), lab(50.0, 0.0, 0.0), 2.3669),
+            (lab(50.0, 2.49, -0.001), lab(50.0, -2.49, 0.0011), 7.2195),
+            (lab(50.0, 2.5, 0.0), lab(73.0, 25.0, -18.0), 27.1492),
+            (
+                lab(60.2574, -34.0099, 36.2677),
+                lab(60.4626, -34.1751, 39.4387),
+                1.2644,
+            ),
+            (
+                lab(2.0776, 0.0795, -1.135),
+                lab(0.9033, -0.0636, -0.5514),
+                0.9082,
+            ),
+        ];
+        for &(first, second, expected) in pairs.iter() {
+            let delta = first.delta_e_2000(second);
+            assert!(
+                close(expected, delta, 1e-4),
+                "{:?} {:?} {}",
+                first,
+                second,
+                delta
+            );
+        }
+        assert_eq!(
+            0.0,
+            lab(50.0, 10.0, 10.0).delta_e_2000(lab(50.0, 10.0, 10.0))
+        );
+    }
+
+    #[test]
+    fn cie76() {
+        assert_eq!(5.0, lab(50.0, 3.0, 0.0).delta_e_76(lab(50.0, 0.0, 4.0)));
+        let (red, orange) = (
+            Color::rgb(255, 0, 0).to_lab(),
+            Color::rgb(255, 128, 0).to_lab(),
+        );
+        // CIEDE2000 counts saturated differences for less
+        assert!(red.delta_e_2000(orange) < red.delta_e_76(orange));
+    }
+
+    #[test]
+    fn contrast_levels() {
+        let (black, white) = (Color::rgb(0, 0, 0), Color::rgb(255, 255, 255));
+        assert!(close(21.0, black.contrast(white), 1e-9));
+        assert!(close(1.0, white.contrast(white), 1e-9));
+
+        // the gray that famously just misses AA on white
+        let gray = Color::rgb(0x77, 0x77, 0x77);
+        let ratio = gray.contrast(white);
+        assert!(close(4.48, ratio, 0.01), "{}", ratio);
+        assert_eq!(ratio, white.contrast(gray));
+        assert_eq!(None, Level::of(ratio, TextSize::Normal));
+        assert_eq!(Some(Level::AA), Level::of(ratio, TextSize::Large));
+        assert_eq!(Some(Level::AAA), Level::of(7.0, TextSize::Normal));
+        assert_eq!(Some(Level::AAA), Level::of(4.5, TextSize::Large));
+        assert_eq!(Some(Level::AA), Level::of(4.5, TextSize::Normal));
+        assert!(Level::AAA > Level::AA);
+    }
+
+    #[test]
+    fn palette_pairs() {
+        let palette = [
+            Color::rgb(0, 0, 0),
+            Color::rgb(255, 255, 255),
+            Color::from(Hue::parse("yellow").unwrap()),
+        ];
+        let pairings = check_contrast(&palette, TextSize::Normal);
+        let levels: Vec<_> = pairings.iter().map(|p| (p.colors, p.level)).collect();
+        assert_eq!(
+            vec![
+                ((0, 1), Some(Level::AAA)),
+                ((0, 2), Some(Level::AAA)),
+                ((1, 2), None)
+            ],
+            levels
+        );
+        assert!(check_contrast(&palette[..1], TextSize::Large).is_empty());
+    }
+}
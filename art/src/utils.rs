@@ -0,0 +1,126 @@
+use crate::kinds::{Hue, PrimaryColor, SecondaryColor, TertiaryColor};
+
+/// Combines two primary colors in equal amounts to create a secondary color,
+/// or the same primary if they're the same color.
+pub fn mix(c1: PrimaryColor, c2: PrimaryColor) -> Hue {
+    use PrimaryColor::*;
+
+    match (c1, c2) {
+        (Red, Yellow) | (Yellow, Red) => Hue::Secondary(SecondaryColor::Orange),
+        (Yellow, Blue) | (Blue, Yellow) => Hue::Secondary(SecondaryColor::Green),
+        (Blue, Red) | (Red, Blue) => Hue::Secondary(SecondaryColor::Purple),
+        (Red, Red) | (Yellow, Yellow) | (Blue, Blue) => Hue::Primary(c1),
+    }
+}
+
+/// Combines a primary color with a secondary color next to it on the color wheel
+/// to create a tertiary color. Any other secondary is across the wheel and mixes to brown.
+pub fn mix_tertiary(primary: PrimaryColor, secondary: SecondaryColor) -> Option<TertiaryColor> {
+    use PrimaryColor::*;
+    use SecondaryColor::*;
+
+    match (primary, secondary) {
+        (Red, Orange) => Some(TertiaryColor::RedOrange),
+        (Yellow, Orange) => Some(TertiaryColor::YellowOrange),
+        (Yellow, Green) => Some(TertiaryColor::YellowGreen),
+        (Blue, Green) => Some(TertiaryColor::BlueGreen),
+        (Blue, Purple) => Some(TertiaryColor::BluePurple),
+        (Red, Purple) => Some(TertiaryColor::RedPurple),
+        (Red, Green) | (Yellow, Purple) | (Blue, Orange) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{mix, mix_tertiary};
+    use crate::kinds::{Hue, PrimaryColor, SecondaryColor, TertiaryColor};
+    use PrimaryColor::*;
+    use SecondaryColor::*;
+
+    #[test]
+    fn every_pair_of_primaries() {
+        let expected = [
+            (Red, Red, Hue::Primary(Red)),
+            (Red, Yellow, Hue::Secondary(Orange)),
+            (Red, Blue, Hue::Secondary(Purple)),
+            (Yellow, Yellow, Hue::Primary(Yellow)),
+            (Yellow, Blue, Hue::Secondary(Green)),
+            (Blue, Blue, Hue::Primary(Blue)),
+        ];
+        for &(c1, c2, hue) in expected.iter() {
+            assert_eq!(hue, mix(c1, c2), "{:?} and {:?}", c1, c2);
+            assert_eq!(hue, mix(c2, c1), "{:?} and {:?}", c2, c1);
+        }
+
+        for &c1 in PrimaryColor::ALL.iter() {
+            for &c2 in PrimaryColor::ALL.iter() {
+                // the wheel agrees with the table
+                assert_eq!(Some(mix(c1, c2)), Hue::from(c1).mix(Hue::from(c2)));
+            }
+        }
+    }
+
+    #[test]
+    fn every_primary_with_every_secondary() {
+        let mut tertiaries = Vec::new();
+        for &primary in PrimaryColor::ALL.iter() {
+            for &secondary in SecondaryColor::ALL.iter() {
+                let tertiary = mix_tertiary(primary, secondary);
+                assert_eq!(
+                    tertiary.map(Hue::from),
+                    Hue::from(primary).mix(Hue::from(secondary)),
+                    "{:?} and {:?}",
+                    primary,
+                    secondary
+                );
+                match tertiary {
+                    Some(tertiary) => tertiaries.push(tertiary),
+                    // only the complement doesn't make a tertiary
+                    None => assert_eq!(Hue::from(primary).complement(), Hue::from(secondary)),
+                }
+            }
+        }
+        // and every tertiary comes from exactly one pair
+        assert_eq!(TertiaryColor::ALL.to_vec(), {
+            tertiaries.sort_by_key(|&tertiary| Hue::from(tertiary).position());
+            tertiaries
+        });
+    }
+
+    #[test]
+    fn every_pair_of_hues() {
+        for &a in Hue::WHEEL.iter() {
+            assert_eq!(Some(a), a.mix(a));
+            assert_eq!(None, a.mix(a.complement()));
+            assert_eq!(a, a.complement().complement());
+
+            for &b in Hue::WHEEL.iter() {
+                let mixed = a.mix(b);
+                assert_eq!(mixed, b.mix(a), "{:?} and {:?}", a, b);
+
+                let apart = (b.position() + 12 - a.position()) % 12;
+                let apart = apart.min(12 - apart);
+                match mixed {
+                    Some(mixed) => {
+                        assert_eq!(0, apart % 2);
+                        // halfway between them both
+                        let to_a = (mixed.position() + 12 - a.position()) % 12;
+                        let to_b = (mixed.position() + 12 - b.position()) % 12;
+                        assert_eq!(apart / 2, to_a.min(12 - to_a));
+                        assert_eq!(apart / 2, to_b.min(12 - to_b));
+                    }
+                    None => assert!(apart % 2 == 1 || apart == 6),
+                }
+            }
+        }
+
+        assert_eq!(
+            Some(Hue::Tertiary(TertiaryColor::RedPurple)),
+            Hue::Primary(Red).mix(Hue::Secondary(Purple))
+        );
+        assert_eq!(
+            Some(Hue::Primary(Red)),
+            Hue::Tertiary(TertiaryColor::RedOrange).mix(Hue::Tertiary(TertiaryColor::RedPurple))
+        );
+    }
+}
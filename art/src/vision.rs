@@ -0,0 +1,229 @@
+// What colors look like to people missing one kind of cone: protanopes lack the long,
+// red-sensitive ones, deuteranopes the medium, green-sensitive ones, and tritanopes the
+// short, blue-sensitive ones.
+//
+// Simulating it is a matrix applied to linear RGB, the ones Machado, Oliveira and
+// Fernandes derived for complete loss ("A Physiologically-based Model for Simulation of
+// Color Vision Deficiency", 2009). Whether two simulated colors can still be told apart
+// is then a question for CIEDE2000.
+
+use crate::color::Color;
+use crate::export::Swatches;
+
+/// A kind of color blindness.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Deficiency {
+    Protanopia,
+    Deuteranopia,
+    Tritanopia,
+}
+
+/// The CIEDE2000 difference below which colors are hard to tell apart in a chart or a
+/// map. Just noticeable is nearer 2, but nobody should have to squint.
+pub const CONFUSABLE: f64 = 10.0;
+
+/// Two colors of a palette that look alike to someone with a deficiency.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Confusion {
+    /// Indexes into the palette, the first the smaller.
+    pub colors: (usize, usize),
+    /// CIEDE2000 between them with full color vision.
+    pub before: f64,
+    /// CIEDE2000 between them as simulated.
+    pub after: f64,
+}
+
+impl Deficiency {
+    pub const ALL: [Deficiency; 3] = [
+        Deficiency::Protanopia,
+        Deficiency::Deuteranopia,
+        Deficiency::Tritanopia,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Deficiency::Protanopia => "protanopia",
+            Deficiency::Deuteranopia => "deuteranopia",
+            Deficiency::Tritanopia => "tritanopia",
+        }
+    }
+
+    /// The deficiency called `name`, ignoring case.
+    pub fn parse(name: &str) -> Option<Deficiency> {
+        let name = name.to_lowercase();
+        Deficiency::ALL
+            .iter()
+            .copied()
+            .find(|deficiency| deficiency.name() == name)
+    }
+
+    fn matrix(self) -> [[f64; 3]; 3] {
+        match self {
+            Deficiency::Protanopia => [
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998],
+            ],
+            Deficiency::Deuteranopia => [
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881],
+            ],
+            Deficiency::Tritanopia => [
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.147602],
+                [0.004733, 0.691367, 0.303900],
+            ],
+        }
+    }
+
+    /// How `color` looks with the deficiency.
+    pub fn simulate(self, color: Color) -> Color {
+        let rgb = color.to_linear();
+        let [r, g, b] = self
+            .matrix()
+            .map(|row| row[0] * rgb[0] + row[1] * rgb[1] + row[2] * rgb[2]);
+        Color::from_linear([r, g, b])
+    }
+
+    /// How every color of `colors` looks with the deficiency, in the same order.
+    pub fn simulate_all(self, colors: &[Color]) -> Vec<Color> {
+        colors.iter().map(|&color| self.simulate(color)).collect()
+    }
+
+    /// The swatches as they look with the deficiency, under the same names.
+    pub fn simulate_swatches(self, swatches: &Swatches) -> Swatches {
+        Swatches {
+            name: format!("{} ({})", swatches.name, self.name()),
+            colors: swatches
+                .colors
+                .iter()
+                .map(|(name, color)| (name.clone(), self.simulate(*color)))
+                .collect(),
+        }
+    }
+
+    /// The pairs of `colors` that can be told apart with full color vision but become
+    /// closer than `threshold` with the deficiency, like `CONFUSABLE`.
+    pub fn confusions(self, colors: &[Color], threshold: f64) -> Vec<Confusion> {
+        let seen: Vec<_> = colors.iter().map(|color| color.to_lab()).collect();
+        let simulated: Vec<_> = colors
+            .iter()
+            .map(|&color| self.simulate(color).to_lab())
+            .collect();
+
+        let mut confusions = Vec::new();
+        for i in 0..colors.len() {
+            for j in i + 1..colors.len() {
+                let before = seen[i].delta_e_2000(seen[j]);
+                let after = simulated[i].delta_e_2000(simulated[j]);
+                if before >= threshold && after < threshold {
+                    confusions.push(Confusion {
+                        colors: (i, j),
+                        before,
+                        after,
+                    });
+                }
+            }
+        }
+        confusions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Deficiency, CONFUSABLE};
+    use crate::color::Color;
+    use crate::export::Swatches;
+    use crate::kinds::Hue;
+
+    fn hue(name: &str) -> Color {
+        Color::from(Hue::parse(name).unwrap())
+    }
+
+    #[test]
+    fn grays_stay_gray() {
+        for &deficiency in Deficiency::ALL.iter() {
+            for &level in &[0, 0x40, 0x80, 0xc0, 0xff] {
+                let gray = Color::rgb(level, level, level);
+                assert_eq!(gray, deficiency.simulate(gray), "{}", deficiency.name());
+            }
+        }
+    }
+
+    #[test]
+    fn red_and_green_run_together() {
+        let (red, green) = (hue("red"), hue("green"));
+        let difference = |a: Color, b: Color| a.to_lab().delta_e_2000(b.to_lab());
+        let full = difference(red, green);
+        for &deficiency in &[Deficiency::Protanopia, Deficiency::Deuteranopia] {
+            let simulated = difference(deficiency.simulate(red), deficiency.simulate(green));
+            assert!(simulated < full / 2.0, "{}", deficiency.name());
+        }
+
+        // tritanopes see red and green well enough, but lose the blue that sets
+        // red-purple apart from red-orange
+        let tritan = |color| Deficiency::Tritanopia.simulate(color);
+        assert!(difference(tritan(red), tritan(green)) > full / 2.0);
+        let (orange, purple) = (hue("red-orange"), hue("red-purple"));
+        assert!(difference(tritan(orange), tritan(purple)) < difference(orange, purple) / 2.0);
+    }
+
+    #[test]
+    fn wheel_confusions() {
+        let wheel: Vec<Color> = Hue::WHEEL.iter().map(|&hue| Color::from(hue)).collect();
+        let names = |deficiency: Deficiency| -> Vec<(&str, &str)> {
+            deficiency
+                .confusions(&wheel, CONFUSABLE)
+                .iter()
+                .map(|confusion| {
+                    let (i, j) = confusion.colors;
+                    assert!(confusion.before >= CONFUSABLE && confusion.after < CONFUSABLE);
+                    (Hue::WHEEL[i].name(), Hue::WHEEL[j].name())
+                })
+                .collect()
+        };
+
+        let protanopia = names(Deficiency::Protanopia);
+        assert!(
+            protanopia.contains(&("orange", "green")),
+            "{:?}",
+            protanopia
+        );
+        assert!(protanopia.contains(&("yellow-orange", "yellow-green")));
+        assert!(names(Deficiency::Deuteranopia).contains(&("red", "red-orange")));
+        assert!(!names(Deficiency::Tritanopia).contains(&("red", "green")));
+
+        // colors that already looked alike aren't the deficiency's doing
+        let alike = [Color::rgb(200, 0, 0), Color::rgb(201, 0, 0)];
+        assert!(Deficiency::Protanopia
+            .confusions(&alike, CONFUSABLE)
+            .is_empty());
+    }
+
+    #[test]
+    fn palettes() {
+        let colors = [hue("red"), hue("blue")];
+        assert_eq!(
+            vec![
+                Deficiency::Deuteranopia.simulate(colors[0]),
+                Deficiency::Deuteranopia.simulate(colors[1])
+            ],
+            Deficiency::Deuteranopia.simulate_all(&colors)
+        );
+
+        let swatches = Deficiency::Tritanopia.simulate_swatches(&Swatches::wheel());
+        assert_eq!("RYB wheel (tritanopia)", swatches.name);
+        assert_eq!("red", swatches.colors[0].0);
+        assert_eq!(
+            Deficiency::Tritanopia.simulate(hue("red")),
+            swatches.colors[0].1
+        );
+
+        assert_eq!(
+            Some(Deficiency::Tritanopia),
+            Deficiency::parse("Tritanopia")
+        );
+        assert_eq!(None, Deficiency::parse("achromatopsia"));
+    }
+}
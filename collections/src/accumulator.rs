@@ -0,0 +1,455 @@
+// Statistics of values that arrive one at a time, too many to keep.
+//
+// The count, mean and variance are exact, kept up with Welford's method so the variance
+// doesn't lose precision subtracting big sums of squares. Medians and percentiles need
+// the values in order, so they're estimated from a t-digest: the values are merged into
+// clusters that are small near the ends, where percentiles are sensitive, and big in the
+// middle, so memory stays bounded by the compression however many values come in.
+//
+// Both halves merge, so each thread can keep its own accumulator and add them up at the end.
+
+use crate::stats::{Number, StatsError, Variance};
+use std::f64::consts::PI;
+
+/// Running statistics of the values pushed so far.
+#[derive(Debug, Clone)]
+pub struct StatsAccumulator {
+    count: u64,
+    mean: f64,
+    /// The sum of squared differences from the mean.
+    m2: f64,
+    min: f64,
+    max: f64,
+    digest: Digest,
+}
+
+impl Default for StatsAccumulator {
+    fn default() -> StatsAccumulator {
+        StatsAccumulator::with_compression(Digest::DEFAULT_COMPRESSION)
+    }
+}
+
+impl StatsAccumulator {
+    pub fn new() -> StatsAccumulator {
+        StatsAccumulator::default()
+    }
+
+    /// An accumulator keeping about `compression` clusters for percentiles. More is more
+    /// accurate and takes more memory, 100 is plenty for most uses.
+    ///
+    /// # Panics
+    ///
+    /// If `compression` is less than 10.
+    pub fn with_compression(compression: f64) -> StatsAccumulator {
+        assert!(
+            compression >= 10.0,
+            "a compression of {} is too small",
+            compression
+        );
+        StatsAccumulator {
+            count: 0,
+            mean: 0.0,
+            m2: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            digest: Digest::new(compression),
+        }
+    }
+
+    /// Adds a value. NaN is turned away, there's nothing it could add.
+    pub fn push<T: Number>(&mut self, value: T) -> Result<(), StatsError> {
+        let value = value.to_f64();
+        if value.is_nan() {
+            return Err(StatsError::NotANumber);
+        }
+
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.digest.push(value);
+        Ok(())
+    }
+
+    /// Adds every value, stopping at the first NaN.
+    pub fn extend<T: Number, I: IntoIterator<Item = T>>(
+        &mut self,
+        values: I,
+    ) -> Result<(), StatsError> {
+        values.into_iter().try_for_each(|value| self.push(value))
+    }
+
+    /// Adds in everything `other` has seen, as if it had all been pushed here.
+    pub fn merge(&mut self, other: &StatsAccumulator) {
+        if other.count == 0 {
+            return;
+        }
+        let count = self.count + other.count;
+        let delta = other.mean - self.mean;
+        let (a, b) = (self.count as f64, other.count as f64);
+        self.mean += delta * b / count as f64;
+        self.m2 += other.m2 + delta * delta * a * b / count as f64;
+        self.count = count;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.digest.merge(&other.digest);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// The average, or `None` before any values.
+    pub fn mean(&self) -> Option<f64> {
+        if self.count == 0 {
+            None
+        } else {
+            Some(self.mean)
+        }
+    }
+
+    pub fn variance(&self, kind: Variance) -> Result<f64, StatsError> {
+        let divisor = match kind {
+            Variance::Population => self.count,
+            Variance::Sample => self.count.saturating_sub(1),
+        };
+        if self.count == 0 {
+            Err(StatsError::Empty)
+        } else if divisor == 0 {
+            Err(StatsError::TooFew { needed: 2, got: 1 })
+        } else {
+            Ok(self.m2 / divisor as f64)
+        }
+    }
+
+    pub fn std_dev(&self, kind: Variance) -> Result<f64, StatsError> {
+        self.variance(kind).map(f64::sqrt)
+    }
+
+    pub fn min(&self) -> Option<f64> {
+        self.mean().map(|_| self.min)
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        self.mean().map(|_| self.max)
+    }
+
+    /// About the middle value. Exact while there are too few values to need clustering.
+    pub fn median(&self) -> Result<f64, StatsError> {
+        self.percentile(50.0)
+    }
+
+    /// About the value `p` percent of the way through, from 0 for the smallest to 100 for
+    /// the largest, which are exact.
+    pub fn percentile(&self, p: f64) -> Result<f64, StatsError> {
+        if !(0.0..=100.0).contains(&p) {
+            return Err(StatsError::OutOfRange(p));
+        }
+        if self.count == 0 {
+            return Err(StatsError::Empty);
+        }
+        Ok(self.digest.quantile(p / 100.0, self.min, self.max))
+    }
+}
+
+/// A cluster of values, standing in for all of them at their mean.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Centroid {
+    mean: f64,
+    weight: f64,
+}
+
+/// A merging t-digest, after Dunning and Ertl's "Computing Extremely Accurate Quantiles
+/// Using t-Digests".
+#[derive(Debug, Clone)]
+struct Digest {
+    compression: f64,
+    /// Sorted by mean.
+    centroids: Vec<Centroid>,
+    /// Values not yet merged into the centroids.
+    buffer: Vec<f64>,
+}
+
+impl Digest {
+    const DEFAULT_COMPRESSION: f64 = 100.0;
+
+    fn new(compression: f64) -> Digest {
+        Digest {
+            compression,
+            centroids: Vec::new(),
+            buffer: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, value: f64) {
+        self.buffer.push(value);
+        if self.buffer.len() >= self.buffer_size() {
+            self.centroids = self.compressed();
+            self.buffer.clear();
+        }
+    }
+
+    fn merge(&mut self, other: &Digest) {
+        self.centroids.extend_from_slice(&other.centroids);
+        self.buffer.extend_from_slice(&other.buffer);
+        self.centroids = self.compressed();
+        self.buffer.clear();
+    }
+
+    /// Merging a few values at a time would sort the centroids over and over.
+    fn buffer_size(&self) -> usize {
+        (self.compression * 5.0) as usize
+    }
+
+    /// The scale function: how many clusters' worth of the digest lie below quantile `q`.
+    /// It's steepest at the ends so the clusters there stay small.
+    fn k(&self, q: f64) -> f64 {
+        self.compression / (2.0 * PI) * (2.0 * q - 1.0).asin()
+    }
+
+    fn k_inverse(&self, k: f64) -> f64 {
+        ((k * 2.0 * PI / self.compression).sin() + 1.0) / 2.0
+    }
+
+    /// The centroids with the buffer merged in, clustered as tightly as the scale allows.
+    fn compressed(&self) -> Vec<Centroid> {
+        let mut all = self.centroids.clone();
+        all.extend(
+            self.buffer
+                .iter()
+                .map(|&mean| Centroid { mean, weight: 1.0 }),
+        );
+        all.sort_by(|a, b| a.mean.total_cmp(&b.mean));
+        let total: f64 = all.iter().map(|c| c.weight).sum();
+
+        let mut merged: Vec<Centroid> = Vec::new();
+        let mut all = all.into_iter();
+        let mut current = match all.next() {
+            Some(first) => first,
+            None => return merged,
+        };
+        // the weight before `current`, and how far it can grow before its cluster is full
+        let mut below = 0.0;
+        let mut limit = total * self.k_inverse(self.k(0.0) + 1.0);
+        for next in all {
+            if below + current.weight + next.weight <= limit {
+                let weight = current.weight + next.weight;
+                current.mean += (next.mean - current.mean) * next.weight / weight;
+                current.weight = weight;
+            } else {
+                below += current.weight;
+                merged.push(current);
+                limit = total * self.k_inverse(self.k(below / total) + 1.0);
+                current = next;
+            }
+        }
+        merged.push(current);
+        merged
+    }
+
+    /// Interpolates between the centroids, each taken to sit at the middle of its
+    /// weight, and out to `min` and `max` at the ends.
+    fn quantile(&self, q: f64, min: f64, max: f64) -> f64 {
+        let centroids = if self.buffer.is_empty() {
+            self.centroids.clone()
+        } else {
+            self.compressed()
+        };
+        let total: f64 = centroids.iter().map(|c| c.weight).sum();
+        let target = q * total;
+
+        let first = centroids[0];
+        if target <= first.weight / 2.0 {
+            return interpolate(min, first.mean, target / (first.weight / 2.0));
+        }
+
+        let mut below = 0.0;
+        for pair in centroids.windows(2) {
+            let (left, right) = (pair[0], pair[1]);
+            let (from, to) = (
+                below + left.weight / 2.0,
+                below + left.weight + right.weight / 2.0,
+            );
+            if target <= to {
+                return interpolate(left.mean, right.mean, (target - from) / (to - from));
+            }
+            below += left.weight;
+        }
+
+        let last = centroids[centroids.len() - 1];
+        let from = total - last.weight / 2.0;
+        interpolate(last.mean, max, (target - from) / (last.weight / 2.0))
+    }
+}
+
+fn interpolate(from: f64, to: f64, t: f64) -> f64 {
+    // exactly on a centroid gives its mean, not something a rounding error away
+    if t <= 0.0 {
+        from
+    } else if t >= 1.0 {
+        to
+    } else {
+        from + (to - from) * t
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StatsAccumulator;
+    use crate::stats::{self, StatsError, Variance};
+    use proptest::prelude::*;
+    use std::thread;
+
+    fn accumulate(values: &[f64]) -> StatsAccumulator {
+        let mut accumulator = StatsAccumulator::new();
+        accumulator.extend(values.iter().copied()).unwrap();
+        accumulator
+    }
+
+    fn close(expected: f64, actual: f64, within: f64) -> bool {
+        (expected - actual).abs() <= within
+    }
+
+    #[test]
+    fn empty_and_single() {
+        let mut accumulator = StatsAccumulator::new();
+        assert_eq!(0, accumulator.count());
+        assert_eq!(None, accumulator.mean());
+        assert_eq!(None, accumulator.min());
+        assert_eq!(Err(StatsError::Empty), accumulator.median());
+        assert_eq!(
+            Err(StatsError::Empty),
+            accumulator.variance(Variance::Population)
+        );
+
+        accumulator.push(7u8).unwrap();
+        assert_eq!(Some(7.0), accumulator.mean());
+        assert_eq!(Ok(0.0), accumulator.variance(Variance::Population));
+        assert_eq!(
+            Err(StatsError::TooFew { needed: 2, got: 1 }),
+            accumulator.variance(Variance::Sample)
+        );
+        assert_eq!(Ok(7.0), accumulator.median());
+        assert_eq!(Ok(7.0), accumulator.percentile(99.0));
+
+        assert_eq!(Err(StatsError::NotANumber), accumulator.push(f64::NAN));
+        assert_eq!(1, accumulator.count());
+        assert_eq!(
+            Err(StatsError::OutOfRange(100.5)),
+            accumulator.percentile(100.5)
+        );
+    }
+
+    #[test]
+    fn the_exercise_list() {
+        let mut accumulator = StatsAccumulator::new();
+        accumulator
+            .extend(vec![1, 2, 9, 3, 4, 6, 5, 8, 7, 5, 6])
+            .unwrap();
+        assert_eq!(11, accumulator.count());
+        assert!(close(56.0 / 11.0, accumulator.mean().unwrap(), 1e-12));
+        assert_eq!(Ok(5.0), accumulator.median());
+        assert_eq!(
+            (Some(1.0), Some(9.0)),
+            (accumulator.min(), accumulator.max())
+        );
+    }
+
+    #[test]
+    fn many_values_in_bounded_memory() {
+        // a million values spread evenly over 0..1000, in a scrambled order
+        let values: Vec<f64> = (0..1_000_000u64)
+            .map(|i| (i * 7919 % 1_000_000) as f64 / 1000.0)
+            .collect();
+        let accumulator = accumulate(&values);
+
+        assert!(accumulator.digest.centroids.len() <= 200);
+        assert!(accumulator.digest.buffer.len() < 500);
+        assert!(close(499.9995, accumulator.mean().unwrap(), 1e-6));
+        for &p in &[1.0, 10.0, 25.0, 50.0, 75.0, 90.0, 99.0, 99.9] {
+            let estimate = accumulator.percentile(p).unwrap();
+            assert!(close(p * 10.0, estimate, 1.0), "{}: {}", p, estimate);
+        }
+        assert_eq!(Ok(0.0), accumulator.percentile(0.0));
+        assert_eq!(Ok(999.999), accumulator.percentile(100.0));
+    }
+
+    #[test]
+    fn merging_across_threads() {
+        let values: Vec<f64> = (0..200_000u64)
+            .map(|i| ((i * 104_729) % 200_000) as f64)
+            .collect();
+        let handles: Vec<_> = values
+            .chunks(50_000)
+            .map(|chunk| {
+                let chunk = chunk.to_vec();
+                thread::spawn(move || accumulate(&chunk))
+            })
+            .collect();
+
+        let mut merged = StatsAccumulator::new();
+        for handle in handles {
+            merged.merge(&handle.join().unwrap());
+        }
+        let whole = accumulate(&values);
+
+        assert_eq!(whole.count(), merged.count());
+        assert!(close(whole.mean().unwrap(), merged.mean().unwrap(), 1e-6));
+        let variance = whole.variance(Variance::Sample).unwrap();
+        assert!(close(
+            variance,
+            merged.variance(Variance::Sample).unwrap(),
+            variance * 1e-9
+        ));
+        assert_eq!((whole.min(), whole.max()), (merged.min(), merged.max()));
+        for &p in &[5.0, 50.0, 95.0] {
+            let estimate = merged.percentile(p).unwrap();
+            assert!(close(p * 2000.0, estimate, 200.0), "{}: {}", p, estimate);
+        }
+
+        // merging nothing changes nothing
+        let before = merged.mean();
+        merged.merge(&StatsAccumulator::new());
+        assert_eq!(before, merged.mean());
+    }
+
+    #[test]
+    #[should_panic(expected = "a compression of 1 is too small")]
+    fn tiny_compression() {
+        StatsAccumulator::with_compression(1.0);
+    }
+
+    proptest! {
+        #[test]
+        fn agrees_with_stats(values in prop::collection::vec(-1.0e6f64..1.0e6, 1..50)) {
+            let accumulator = accumulate(&values);
+            let mean = stats::mean(&values).unwrap();
+            prop_assert!(close(mean, accumulator.mean().unwrap(), 1e-6));
+            let variance = stats::variance(&values, Variance::Population).unwrap();
+            prop_assert!(close(variance, accumulator.variance(Variance::Population).unwrap(), 1e-6 * variance.max(1.0)));
+            // too few values to cluster, so the median is exact
+            prop_assert_eq!(stats::median(&values).unwrap(), accumulator.median().unwrap());
+            prop_assert_eq!(stats::percentile(&values, 0.0).unwrap(), accumulator.percentile(0.0).unwrap());
+            prop_assert_eq!(stats::percentile(&values, 100.0).unwrap(), accumulator.percentile(100.0).unwrap());
+        }
+
+        #[test]
+        fn merging_is_like_pushing(values in prop::collection::vec(-1000i32..1000, 0..2000), split in 0usize..2000) {
+            let split = split.min(values.len());
+            let (mut left, mut right) = (StatsAccumulator::new(), StatsAccumulator::new());
+            left.extend(values[..split].iter().copied()).unwrap();
+            right.extend(values[split..].iter().copied()).unwrap();
+            left.merge(&right);
+
+            let mut whole = StatsAccumulator::new();
+            whole.extend(values.iter().copied()).unwrap();
+            prop_assert_eq!(whole.count(), left.count());
+            prop_assert_eq!((whole.min(), whole.max()), (left.min(), left.max()));
+            if let (Some(a), Some(b)) = (whole.mean(), left.mean()) {
+                prop_assert!(close(a, b, 1e-9));
+            }
+        }
+    }
+}
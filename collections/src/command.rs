@@ -0,0 +1,670 @@
+// The commands the employee directory understands, one a line:
+//
+//     Add Sally to Engineering
+//     Remove Sally from Engineering
+//     Move Sally from Engineering to Sales
+//     Rename department Sales to Marketing
+//     List Sales
+//     List all
+//
+// The words of the commands can be in any case. A name with spaces in it goes in double
+// quotes, as does one that's spelt like a word of the commands, like a department called
+// "All", and a quote or backslash inside the quotes is written with a backslash before it.
+// A line break in a name is written `\n`, or `\r` for a carriage return, so a command always
+// fits on one line. Every command prints back the same way, so it reads in again as it was.
+
+use crate::directory::{Directory, DirectoryError};
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+
+pub const HELP: &str = "\
+Commands:
+  Add EMPLOYEE to DEPARTMENT
+  Remove EMPLOYEE from DEPARTMENT
+  Move EMPLOYEE from DEPARTMENT to DEPARTMENT
+  Rename department DEPARTMENT to NAME
+  List DEPARTMENT
+  List all
+  help
+  quit
+Put names with spaces in double quotes, like \"Sally Smith\".";
+
+/// The words that mean something in a command when they aren't quoted.
+const KEYWORDS: [&str; 4] = ["all", "department", "from", "to"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Add {
+        employee: String,
+        department: String,
+    },
+    Remove {
+        employee: String,
+        department: String,
+    },
+    Move {
+        employee: String,
+        from: String,
+        to: String,
+    },
+    Rename {
+        department: String,
+        name: String,
+    },
+    List {
+        department: String,
+    },
+    ListAll,
+    Help,
+    Quit,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The line had nothing on it.
+    Empty,
+    /// The line starts with a word that isn't a command.
+    UnknownCommand(String),
+    /// A quote was opened and never closed.
+    UnclosedQuote,
+    /// Something else was needed where the line ended.
+    Missing(&'static str),
+    /// Something else was needed where this was.
+    Expected {
+        expected: &'static str,
+        found: String,
+    },
+    /// The command was over but the line went on.
+    Unexpected(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "no command"),
+            ParseError::UnknownCommand(word) => write!(f, "there's no command {:?}", word),
+            ParseError::UnclosedQuote => write!(f, "a quote is never closed"),
+            ParseError::Missing(expected) => write!(f, "expected {} at the end", expected),
+            ParseError::Expected { expected, found } => {
+                write!(f, "expected {}, found {:?}", expected, found)
+            }
+            ParseError::Unexpected(found) => write!(f, "{:?} after the end of the command", found),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// A word of a command line, and whether it was in quotes.
+#[derive(Debug)]
+struct Token {
+    text: String,
+    quoted: bool,
+}
+
+fn tokenize(line: &str) -> Result<Vec<Token>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = line.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '"' {
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some('\\') => match chars.next().ok_or(ParseError::UnclosedQuote)? {
+                        'n' => text.push('\n'),
+                        'r' => text.push('\r'),
+                        c => text.push(c),
+                    },
+                    Some(c) => text.push(c),
+                    None => return Err(ParseError::UnclosedQuote),
+                }
+            }
+            tokens.push(Token { text, quoted: true });
+        } else {
+            let mut text = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() || c == '"' {
+                    break;
+                }
+                text.push(c);
+                chars.next();
+            }
+            tokens.push(Token {
+                text,
+                quoted: false,
+            });
+        }
+    }
+    Ok(tokens)
+}
+
+/// Goes through the words of a command one at a time.
+struct Parser {
+    tokens: std::vec::IntoIter<Token>,
+}
+
+impl Parser {
+    /// The next word, which has to be the keyword when it isn't quoted. The keyword is
+    /// given in quotes, like `"\"to\""`, the way errors show it.
+    fn keyword(&mut self, expected: &'static str) -> Result<(), ParseError> {
+        let keyword = expected.trim_matches('"');
+        match self.tokens.next() {
+            Some(token) if !token.quoted && token.text.eq_ignore_ascii_case(keyword) => Ok(()),
+            Some(token) => Err(ParseError::Expected {
+                expected,
+                found: token.text,
+            }),
+            None => Err(ParseError::Missing(expected)),
+        }
+    }
+
+    /// The next word as a name, which can't be empty or a keyword unless it's quoted.
+    fn name(&mut self, what: &'static str) -> Result<String, ParseError> {
+        match self.tokens.next() {
+            Some(token) if token.quoted && !token.text.is_empty() => Ok(token.text),
+            Some(token) if !token.quoted && !is_keyword(&token.text) => Ok(token.text),
+            Some(token) => Err(ParseError::Expected {
+                expected: what,
+                found: token.text,
+            }),
+            None => Err(ParseError::Missing(what)),
+        }
+    }
+
+    fn end(mut self, command: Command) -> Result<Command, ParseError> {
+        match self.tokens.next() {
+            Some(token) => Err(ParseError::Unexpected(token.text)),
+            None => Ok(command),
+        }
+    }
+}
+
+fn is_keyword(word: &str) -> bool {
+    KEYWORDS
+        .iter()
+        .any(|keyword| keyword.eq_ignore_ascii_case(word))
+}
+
+impl FromStr for Command {
+    type Err = ParseError;
+
+    fn from_str(line: &str) -> Result<Command, ParseError> {
+        let mut tokens = tokenize(line)?.into_iter();
+        let first = tokens.next().ok_or(ParseError::Empty)?;
+        let mut parser = Parser { tokens };
+        if first.quoted {
+            return Err(ParseError::UnknownCommand(first.text));
+        }
+
+        let command = match first.text.to_lowercase().as_str() {
+            "add" => {
+                let employee = parser.name("an employee")?;
+                parser.keyword("\"to\"")?;
+                let department = parser.name("a department")?;
+                Command::Add {
+                    employee,
+                    department,
+                }
+            }
+            "remove" => {
+                let employee = parser.name("an employee")?;
+                parser.keyword("\"from\"")?;
+                let department = parser.name("a department")?;
+                Command::Remove {
+                    employee,
+                    department,
+                }
+            }
+            "move" => {
+                let employee = parser.name("an employee")?;
+                parser.keyword("\"from\"")?;
+                let from = parser.name("a department")?;
+                parser.keyword("\"to\"")?;
+                let to = parser.name("a department")?;
+                Command::Move { employee, from, to }
+            }
+            "rename" => {
+                parser.keyword("\"department\"")?;
+                let department = parser.name("a department")?;
+                parser.keyword("\"to\"")?;
+                let name = parser.name("a new name")?;
+                Command::Rename { department, name }
+            }
+            "list" => match parser.tokens.as_slice().first() {
+                Some(token) if !token.quoted && token.text.eq_ignore_ascii_case("all") => {
+                    parser.tokens.next();
+                    Command::ListAll
+                }
+                _ => Command::List {
+                    department: parser.name("a department or all")?,
+                },
+            },
+            "help" => Command::Help,
+            "quit" | "exit" => Command::Quit,
+            _ => return Err(ParseError::UnknownCommand(first.text)),
+        };
+        parser.end(command)
+    }
+}
+
+/// A name as it has to be written in a command.
+struct Quoted<'a>(&'a str);
+
+impl fmt::Display for Quoted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = self.0;
+        let plain = !name.is_empty()
+            && !is_keyword(name)
+            && !name
+                .chars()
+                .any(|c| c.is_whitespace() || c == '"' || c == '\\');
+        if plain {
+            return write!(f, "{}", name);
+        }
+        write!(f, "\"")?;
+        for c in name.chars() {
+            match c {
+                '"' | '\\' => write!(f, "\\{}", c)?,
+                '\n' => write!(f, "\\n")?,
+                '\r' => write!(f, "\\r")?,
+                c => write!(f, "{}", c)?,
+            }
+        }
+        write!(f, "\"")
+    }
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Command::Add {
+                employee,
+                department,
+            } => write!(f, "Add {} to {}", Quoted(employee), Quoted(department)),
+            Command::Remove {
+                employee,
+                department,
+            } => write!(f, "Remove {} from {}", Quoted(employee), Quoted(department)),
+            Command::Move { employee, from, to } => write!(
+                f,
+                "Move {} from {} to {}",
+                Quoted(employee),
+                Quoted(from),
+                Quoted(to)
+            ),
+            Command::Rename { department, name } => write!(
+                f,
+                "Rename department {} to {}",
+                Quoted(department),
+                Quoted(name)
+            ),
+            Command::List { department } => write!(f, "List {}", Quoted(department)),
+            Command::ListAll => write!(f, "List all"),
+            Command::Help => write!(f, "help"),
+            Command::Quit => write!(f, "quit"),
+        }
+    }
+}
+
+/// What commands are run on: a directory in memory, or one that's kept somewhere and has to
+/// hear about every change.
+pub trait Session {
+    fn directory(&self) -> &Directory;
+
+    /// Makes the command's change, if it makes one.
+    fn apply(&mut self, command: &Command) -> Result<(), Box<dyn Error>>;
+
+    /// Something that went wrong after the last command was carried out, which doesn't
+    /// undo it but is worth telling about.
+    fn warning(&mut self) -> Option<String> {
+        None
+    }
+}
+
+impl Session for Directory {
+    fn directory(&self) -> &Directory {
+        self
+    }
+
+    fn apply(&mut self, command: &Command) -> Result<(), Box<dyn Error>> {
+        command.apply(self).map_err(Box::from)
+    }
+}
+
+impl Command {
+    /// Whether the command changes the directory rather than just looking at it.
+    pub fn changes(&self) -> bool {
+        matches!(
+            self,
+            Command::Add { .. }
+                | Command::Remove { .. }
+                | Command::Move { .. }
+                | Command::Rename { .. }
+        )
+    }
+
+    /// The command that takes this one's change back, once it's been made. A department
+    /// that closed or opened with it opens or closes again too.
+    pub fn inverse(&self) -> Option<Command> {
+        match self {
+            Command::Add {
+                employee,
+                department,
+            } => Some(Command::Remove {
+                employee: employee.clone(),
+                department: department.clone(),
+            }),
+            Command::Remove {
+                employee,
+                department,
+            } => Some(Command::Add {
+                employee: employee.clone(),
+                department: department.clone(),
+            }),
+            Command::Move { employee, from, to } => Some(Command::Move {
+                employee: employee.clone(),
+                from: to.clone(),
+                to: from.clone(),
+            }),
+            Command::Rename { department, name } => Some(Command::Rename {
+                department: name.clone(),
+                name: department.clone(),
+            }),
+            Command::List { .. } | Command::ListAll | Command::Help | Command::Quit => None,
+        }
+    }
+
+    /// Makes the command's change to the directory, if it makes one.
+    pub fn apply(&self, directory: &mut Directory) -> Result<(), DirectoryError> {
+        match self {
+            Command::Add {
+                employee,
+                department,
+            } => directory.add(employee, department),
+            Command::Remove {
+                employee,
+                department,
+            } => directory.remove(employee, department),
+            Command::Move { employee, from, to } => directory.transfer(employee, from, to),
+            Command::Rename { department, name } => directory.rename(department, name),
+            Command::List { .. } | Command::ListAll | Command::Help | Command::Quit => Ok(()),
+        }
+    }
+
+    /// Carries the command out and says what happened, or what it found.
+    pub fn run(
+        &self,
+        session: &mut impl Session,
+        mut out: impl Write,
+    ) -> Result<(), Box<dyn Error>> {
+        session.apply(self)?;
+        let warning = session.warning();
+        let directory = session.directory();
+        match self {
+            Command::Add {
+                employee,
+                department,
+            } => writeln!(out, "Added {} to {}.", employee, department)?,
+            Command::Remove {
+                employee,
+                department,
+            } => writeln!(out, "Removed {} from {}.", employee, department)?,
+            Command::Move { employee, from, to } => {
+                writeln!(out, "Moved {} from {} to {}.", employee, from, to)?
+            }
+            Command::Rename { department, name } => {
+                writeln!(out, "Renamed {} to {}.", department, name)?
+            }
+            Command::List { department } => {
+                list(&mut out, department, directory.employees(department)?)?
+            }
+            Command::ListAll if directory.is_empty() => writeln!(out, "Nobody works here yet.")?,
+            Command::ListAll => {
+                for (department, employees) in directory.departments() {
+                    list(&mut out, department, employees)?;
+                }
+            }
+            Command::Help => writeln!(out, "{}", HELP)?,
+            Command::Quit => {}
+        }
+        if let Some(warning) = warning {
+            writeln!(out, "Warning: {}.", warning)?;
+        }
+        Ok(())
+    }
+}
+
+fn list(mut out: impl Write, department: &str, employees: &[String]) -> io::Result<()> {
+    writeln!(out, "{}:", department)?;
+    for employee in employees {
+        writeln!(out, "  {}", employee)?;
+    }
+    Ok(())
+}
+
+/// Reads commands from `input` and runs them on the session until `quit` or the end of
+/// the input. A command that can't be read or carried out is reported and the rest go on.
+pub fn repl(
+    session: &mut impl Session,
+    input: impl BufRead,
+    mut output: impl Write,
+) -> io::Result<()> {
+    write!(output, "> ")?;
+    output.flush()?;
+    for line in input.lines() {
+        match line?.parse::<Command>() {
+            Ok(Command::Quit) => return Ok(()),
+            Ok(command) => {
+                if let Err(err) = command.run(session, &mut output) {
+                    writeln!(output, "Error: {}.", err)?;
+                }
+            }
+            Err(ParseError::Empty) => {}
+            Err(err) => writeln!(output, "Error: {}. Type help for the commands.", err)?,
+        }
+        write!(output, "> ")?;
+        output.flush()?;
+    }
+    writeln!(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{repl, Command, ParseError};
+    use crate::directory::Directory;
+
+    fn parse(line: &str) -> Result<Command, ParseError> {
+        line.parse()
+    }
+
+    #[test]
+    fn commands() {
+        assert_eq!(
+            Ok(Command::Add {
+                employee: String::from("Sally"),
+                department: String::from("Engineering")
+            }),
+            parse("Add Sally to Engineering")
+        );
+        assert_eq!(
+            Ok(Command::Move {
+                employee: String::from("Sally Smith"),
+                from: String::from("Research and Development"),
+                to: String::from("Sales")
+            }),
+            parse("  move \"Sally Smith\" FROM \"Research and Development\" to Sales ")
+        );
+        assert_eq!(
+            Ok(Command::Rename {
+                department: String::from("Sales"),
+                name: String::from("All")
+            }),
+            parse("Rename department Sales to \"All\"")
+        );
+        assert_eq!(Ok(Command::ListAll), parse("list ALL"));
+        assert_eq!(
+            Ok(Command::List {
+                department: String::from("all")
+            }),
+            parse("List \"all\"")
+        );
+        assert_eq!(
+            Ok(Command::Remove {
+                employee: String::from("Dwayne \"The Rock\" Johnson"),
+                department: String::from("C:\\"),
+            }),
+            parse(r#"Remove "Dwayne \"The Rock\" Johnson" from "C:\\""#)
+        );
+        assert_eq!(Ok(Command::Help), parse("help"));
+        assert_eq!(Ok(Command::Quit), parse("exit"));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(Err(ParseError::Empty), parse(" \t"));
+        assert_eq!(
+            Err(ParseError::UnknownCommand(String::from("Hire"))),
+            parse("Hire Sally")
+        );
+        assert_eq!(
+            Err(ParseError::UnclosedQuote),
+            parse("Add \"Sally to Sales")
+        );
+        assert_eq!(Err(ParseError::UnclosedQuote), parse("Add \"Sally\\"));
+        assert_eq!(
+            Err(ParseError::Missing("a department")),
+            parse("Add Sally to")
+        );
+        assert_eq!(
+            Err(ParseError::Expected {
+                expected: "\"to\"",
+                found: String::from("Smith")
+            }),
+            parse("Add Sally Smith to Sales")
+        );
+        assert_eq!(
+            Err(ParseError::Expected {
+                expected: "\"from\"",
+                found: String::from("to")
+            }),
+            parse("Remove Sally to Sales")
+        );
+        assert_eq!(Err(ParseError::Missing("\"department\"")), parse("Rename"));
+        assert_eq!(
+            Err(ParseError::Expected {
+                expected: "an employee",
+                found: String::from("to")
+            }),
+            parse("Add to Sales")
+        );
+        assert_eq!(
+            Err(ParseError::Expected {
+                expected: "an employee",
+                found: String::new()
+            }),
+            parse("Add \"\" to Sales")
+        );
+        assert_eq!(
+            Err(ParseError::Unexpected(String::from("please"))),
+            parse("List all please")
+        );
+    }
+
+    #[test]
+    fn commands_read_back_as_written() {
+        let commands = [
+            "Add Sally to Engineering",
+            "Add \"Sally Smith\" to \"all\"",
+            r#"Remove "Dwayne \"The Rock\" Johnson" from "C:\\""#,
+            "Move Leon from Sales to \"From\"",
+            "Rename department Sales to Marketing",
+            "List \"Research and Development\"",
+            r#"List "Research\nand\r\nDevelopment""#,
+            "List all",
+        ];
+        for &line in commands.iter() {
+            let command = parse(line).unwrap();
+            assert_eq!(line, command.to_string());
+            assert_eq!(Ok(command.clone()), parse(&command.to_string()));
+        }
+    }
+
+    #[test]
+    fn a_session() {
+        let input = "\
+Add Sally to Engineering
+Add \"Amir Khan\" to Sales
+add Leon to Sales
+
+Hire Bob
+Move Leon from Sales to Engineering
+Remove Sally from Sales
+List Sales
+List all
+quit
+Add Nobody to Sales
+";
+        let mut directory = Directory::new();
+        let mut output = Vec::new();
+        repl(&mut directory, input.as_bytes(), &mut output).unwrap();
+        assert_eq!(
+            "\
+> Added Sally to Engineering.
+> Added Amir Khan to Sales.
+> Added Leon to Sales.
+> > Error: there's no command \"Hire\". Type help for the commands.
+> Moved Leon from Sales to Engineering.
+> Error: Sally isn't in Sales.
+> Sales:
+  Amir Khan
+> Engineering:
+  Leon
+  Sally
+Sales:
+  Amir Khan
+> ",
+            String::from_utf8(output).unwrap()
+        );
+        assert!(!directory.works_in("Nobody", "Sales"));
+    }
+
+    #[test]
+    fn what_changes() {
+        assert!(parse("Add Sally to Sales").unwrap().changes());
+        assert!(parse("Rename department Sales to Marketing")
+            .unwrap()
+            .changes());
+        assert!(!parse("List all").unwrap().changes());
+        assert!(!parse("help").unwrap().changes());
+    }
+
+    #[test]
+    fn inverses_take_changes_back() {
+        let mut directory = Directory::new();
+        directory.add("Sally", "Engineering").unwrap();
+        directory.add("Leon", "Sales").unwrap();
+        let commands = [
+            "Add Amir to Sales",
+            "Add Amir to Legal",
+            "Remove Leon from Sales",
+            "Move Leon from Sales to Engineering",
+            "Move Sally from Engineering to Sales",
+            "Rename department Sales to Marketing",
+        ];
+        for &line in commands.iter() {
+            let command = parse(line).unwrap();
+            let mut changed = directory.clone();
+            command.apply(&mut changed).unwrap();
+            assert_ne!(directory, changed, "{}", line);
+            command.inverse().unwrap().apply(&mut changed).unwrap();
+            assert_eq!(directory, changed, "{}", line);
+        }
+        assert_eq!(None, parse("List all").unwrap().inverse());
+    }
+}
@@ -0,0 +1,279 @@
+// Which employees work in which department of a company.
+//
+// Departments are kept in order of name and so are the employees in each, so listing them
+// never needs a sort. A department exists for as long as somebody works in it. The same
+// person can be in more than one department, but only once in each.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DirectoryError {
+    /// Nobody works in a department of that name.
+    UnknownDepartment(String),
+    /// The employee isn't in the department.
+    NotInDepartment {
+        employee: String,
+        department: String,
+    },
+    /// The employee is in the department already.
+    AlreadyInDepartment {
+        employee: String,
+        department: String,
+    },
+    /// A department can't be renamed to one that already exists.
+    DepartmentExists(String),
+}
+
+impl fmt::Display for DirectoryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DirectoryError::UnknownDepartment(department) => {
+                write!(f, "there's no department called {}", department)
+            }
+            DirectoryError::NotInDepartment {
+                employee,
+                department,
+            } => write!(f, "{} isn't in {}", employee, department),
+            DirectoryError::AlreadyInDepartment {
+                employee,
+                department,
+            } => write!(f, "{} is in {} already", employee, department),
+            DirectoryError::DepartmentExists(department) => {
+                write!(f, "there's a department called {} already", department)
+            }
+        }
+    }
+}
+
+impl Error for DirectoryError {}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Directory {
+    departments: BTreeMap<String, Vec<String>>,
+}
+
+impl Directory {
+    pub fn new() -> Directory {
+        Directory::default()
+    }
+
+    /// Puts `employee` in `department`, starting the department if it's new.
+    pub fn add(&mut self, employee: &str, department: &str) -> Result<(), DirectoryError> {
+        let employees = self
+            .departments
+            .entry(String::from(department))
+            .or_default();
+        match employees.binary_search_by(|e| e.as_str().cmp(employee)) {
+            Ok(_) => Err(DirectoryError::AlreadyInDepartment {
+                employee: String::from(employee),
+                department: String::from(department),
+            }),
+            Err(at) => {
+                employees.insert(at, String::from(employee));
+                Ok(())
+            }
+        }
+    }
+
+    /// Takes `employee` out of `department`, which closes when they were the last one in it.
+    pub fn remove(&mut self, employee: &str, department: &str) -> Result<(), DirectoryError> {
+        let employees = self
+            .departments
+            .get_mut(department)
+            .ok_or_else(|| DirectoryError::UnknownDepartment(String::from(department)))?;
+        let at = employees
+            .binary_search_by(|e| e.as_str().cmp(employee))
+            .map_err(|_| DirectoryError::NotInDepartment {
+                employee: String::from(employee),
+                department: String::from(department),
+            })?;
+        employees.remove(at);
+        if employees.is_empty() {
+            self.departments.remove(department);
+        }
+        Ok(())
+    }
+
+    /// Moves `employee` from one department to another, or leaves everything as it was if
+    /// they can't be.
+    pub fn transfer(&mut self, employee: &str, from: &str, to: &str) -> Result<(), DirectoryError> {
+        if !self.works_in(employee, from) {
+            self.employees(from)?;
+            return Err(DirectoryError::NotInDepartment {
+                employee: String::from(employee),
+                department: String::from(from),
+            });
+        }
+        if self.works_in(employee, to) {
+            return Err(DirectoryError::AlreadyInDepartment {
+                employee: String::from(employee),
+                department: String::from(to),
+            });
+        }
+        self.remove(employee, from)?;
+        self.add(employee, to)
+    }
+
+    /// Gives a department a new name, keeping everyone in it.
+    pub fn rename(&mut self, department: &str, name: &str) -> Result<(), DirectoryError> {
+        if self.departments.contains_key(name) {
+            return Err(DirectoryError::DepartmentExists(String::from(name)));
+        }
+        let employees = self
+            .departments
+            .remove(department)
+            .ok_or_else(|| DirectoryError::UnknownDepartment(String::from(department)))?;
+        self.departments.insert(String::from(name), employees);
+        Ok(())
+    }
+
+    /// Everyone in `department`, in alphabetical order.
+    pub fn employees(&self, department: &str) -> Result<&[String], DirectoryError> {
+        self.departments
+            .get(department)
+            .map(Vec::as_slice)
+            .ok_or_else(|| DirectoryError::UnknownDepartment(String::from(department)))
+    }
+
+    /// Every department with everyone in it, both in alphabetical order.
+    pub fn departments(&self) -> impl Iterator<Item = (&str, &[String])> {
+        self.departments
+            .iter()
+            .map(|(department, employees)| (department.as_str(), employees.as_slice()))
+    }
+
+    /// Everyone in the company in alphabetical order, once each however many departments
+    /// they're in.
+    pub fn everyone(&self) -> Vec<&str> {
+        let mut everyone: Vec<&str> = self
+            .departments
+            .values()
+            .flatten()
+            .map(String::as_str)
+            .collect();
+        everyone.sort_unstable();
+        everyone.dedup();
+        everyone
+    }
+
+    pub fn works_in(&self, employee: &str, department: &str) -> bool {
+        self.departments
+            .get(department)
+            .is_some_and(|employees| employees.iter().any(|e| e == employee))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.departments.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Directory, DirectoryError};
+
+    fn company() -> Directory {
+        let mut directory = Directory::new();
+        directory.add("Sally", "Engineering").unwrap();
+        directory.add("Leon", "Sales").unwrap();
+        directory.add("Amir", "Sales").unwrap();
+        directory
+    }
+
+    #[test]
+    fn adding_keeps_everyone_in_order() {
+        let directory = company();
+        assert_eq!(
+            Ok(&[String::from("Amir"), String::from("Leon")][..]),
+            directory.employees("Sales")
+        );
+        let departments: Vec<&str> = directory.departments().map(|(d, _)| d).collect();
+        assert_eq!(vec!["Engineering", "Sales"], departments);
+        assert_eq!(vec!["Amir", "Leon", "Sally"], directory.everyone());
+    }
+
+    #[test]
+    fn nobody_twice_in_a_department() {
+        let mut directory = company();
+        assert_eq!(
+            Err(DirectoryError::AlreadyInDepartment {
+                employee: String::from("Leon"),
+                department: String::from("Sales")
+            }),
+            directory.add("Leon", "Sales")
+        );
+        // but in two departments is fine, and still counts once
+        directory.add("Leon", "Engineering").unwrap();
+        assert_eq!(vec!["Amir", "Leon", "Sally"], directory.everyone());
+    }
+
+    #[test]
+    fn removing_the_last_one_closes_the_department() {
+        let mut directory = company();
+        directory.remove("Sally", "Engineering").unwrap();
+        assert_eq!(
+            Err(DirectoryError::UnknownDepartment(String::from(
+                "Engineering"
+            ))),
+            directory.employees("Engineering")
+        );
+        assert_eq!(
+            Err(DirectoryError::NotInDepartment {
+                employee: String::from("Sally"),
+                department: String::from("Sales")
+            }),
+            directory.remove("Sally", "Sales")
+        );
+        assert_eq!(
+            Err(DirectoryError::UnknownDepartment(String::from("Legal"))),
+            directory.remove("Sally", "Legal")
+        );
+    }
+
+    #[test]
+    fn transfers() {
+        let mut directory = company();
+        directory.transfer("Leon", "Sales", "Engineering").unwrap();
+        assert!(directory.works_in("Leon", "Engineering"));
+        assert!(!directory.works_in("Leon", "Sales"));
+
+        // a transfer that can't happen changes nothing
+        let before = directory.clone();
+        assert_eq!(
+            Err(DirectoryError::AlreadyInDepartment {
+                employee: String::from("Sally"),
+                department: String::from("Engineering")
+            }),
+            directory.transfer("Sally", "Engineering", "Engineering")
+        );
+        assert_eq!(
+            Err(DirectoryError::UnknownDepartment(String::from("Legal"))),
+            directory.transfer("Amir", "Legal", "Sales")
+        );
+        assert_eq!(before, directory);
+
+        // moving the last one out closes the department
+        directory.transfer("Amir", "Sales", "Legal").unwrap();
+        assert!(directory.employees("Sales").is_err());
+    }
+
+    #[test]
+    fn renames() {
+        let mut directory = company();
+        directory.rename("Sales", "Marketing").unwrap();
+        assert!(directory.works_in("Amir", "Marketing"));
+        assert_eq!(
+            Err(DirectoryError::DepartmentExists(String::from(
+                "Engineering"
+            ))),
+            directory.rename("Marketing", "Engineering")
+        );
+        assert_eq!(
+            Err(DirectoryError::UnknownDepartment(String::from("Sales"))),
+            directory.rename("Sales", "Support")
+        );
+        assert!(!Directory::new().works_in("Amir", "Sales"));
+        assert!(Directory::new().is_empty());
+    }
+}
@@ -1,3 +1,5 @@
+pub use hashmap::interactive as directory;
+
 pub fn main() {
     list::exercise();
     string::exercise();
@@ -5,151 +7,91 @@ pub fn main() {
 }
 
 mod list {
-    use std::collections::HashMap;
+    use collections::accumulator::StatsAccumulator;
+    use collections::stats::{self, Variance};
 
     pub fn exercise() {
-        let mut a = vec![1, 2, 9, 3, 4, 6, 5, 8, 7, 5, 6];
-        println!("vec {:?}\nmean: {}", a, list_mean(&a));
-        println!("median: {}", list_median(&mut a));
-        println!("vec {:?}", a);
-        println!("vec mode: {}", list_mode(&a));
-    }
-
-    fn list_mean(v: &Vec<i32>) -> i32 {
-        let mut sum = 0;
-        for i in v {
-            sum += *i;
-        }
-        sum / v.len() as i32
-    }
-
-    fn list_median(v: &mut Vec<i32>) -> i32 {
-        v.sort();
-        if v.len() % 2 == 0 {
-            return (*v.get((v.len() - 1) / 2).unwrap() + *v.get((v.len() + 1) / 2).unwrap()) / 2;
-        }
-        return *v.get((v.len() + 1) / 2).unwrap();
-    }
-
-    fn list_mode(v: &Vec<i32>) -> i32 {
-        let mut hashmap = HashMap::new();
-        for i in v {
-            *hashmap.entry(*i).or_insert(0) += 1;
-            //*hashmap.get_mut(i).unwrap() += 1;
-        }
+        let list = vec![1, 2, 9, 3, 4, 6, 5, 8, 7, 5, 6];
+        println!("vec {:?}\nmean: {:?}", list, stats::mean(&list));
+        println!("median: {:?}", stats::median(&list));
+        println!("modes: {:?}", stats::modes(&list));
+        println!(
+            "standard deviation: {:?}",
+            stats::std_dev(&list, Variance::Population)
+        );
+        println!("90th percentile: {:?}", stats::percentile(&list, 90.0));
 
-        // println!("{:?}", hashmap);
-        let mut mode = 0;
-        let mut mode_count = 0;
-        for x in hashmap {
-            if x.1 > mode_count {
-                mode_count = x.1;
-                mode = x.0;
-            }
+        // the same without keeping the list, one value at a time
+        let mut accumulator = StatsAccumulator::new();
+        for &value in &list {
+            accumulator.push(value).expect("not NaN");
         }
-
-        mode
+        println!(
+            "streamed: {} values, mean {:?}, median {:?}",
+            accumulator.count(),
+            accumulator.mean(),
+            accumulator.median()
+        );
     }
 }
 
 mod string {
-    use unicode_segmentation::UnicodeSegmentation;
+    use collections::pig_latin::{from_pig_latin, pig_latin};
 
     pub fn exercise() {
-        let a = String::from("first");
-        println!("{} {}", a, string_convert(&a));
-        let a = String::from("apply");
-        println!("{} {}", a, string_convert(&a));
-    }
-
-    fn string_convert(str: &String) -> String {
-        let graphemes = UnicodeSegmentation::graphemes(str.as_str(), true).collect::<Vec<&str>>();
-        match graphemes.get(0) {
-            Some(&"a") | Some(&"e") | Some(&"i") | Some(&"o") | Some(&"u") => {
-                let mut res = String::from(str);
-                res.push_str("-fay");
-
-                res
-            }
-            _ => {
-                let mut res = String::from("");
-                for &x in graphemes.iter().skip(1) {
-                    res.push_str(x);
-                }
-                res.push_str("-");
-                res.push_str(*graphemes.get(0).unwrap());
-                res.push_str("ay");
-
-                res
-            }
-        }
+        let text = "First, apply the string: \"Quiet, yellow rhythm!\"";
+        let translated = pig_latin(text);
+        println!("{}\n{}\n{}", text, translated, from_pig_latin(&translated));
     }
 }
 
 mod hashmap {
-    use std::collections::HashMap;
+    use collections::command;
+    use collections::directory::Directory;
+    use collections::storage::Store;
+    use std::io;
+    use std::path::Path;
+    use std::process;
 
     pub fn exercise() {
-        let mut department_employees: HashMap<String, Vec<String>> = HashMap::new();
-        add_employee_by_text_interface(
-            &mut department_employees,
-            "Add Sally to Engineering".to_string(),
-        );
-        add_employee_by_text_interface(&mut department_employees, "Add Leon to Sales".to_string());
-        add_employee_by_text_interface(&mut department_employees, "Add Amir to Sales".to_string());
-        println!("{:?}", department_employees);
-        println!(
-            "{:?}",
-            get_department_employees(&department_employees, "Sales".to_string()).unwrap()
-        );
-        println!("{:?}", get_all_people(&department_employees));
+        let commands = "\
+Add Sally to Engineering
+Add Leon to Sales
+Add \"Amir Khan\" to Sales
+List Sales
+Move Leon from Sales to Engineering
+List all
+";
+        let mut directory = Directory::new();
+        command::repl(&mut directory, commands.as_bytes(), io::stdout().lock())
+            .expect("Failed to write");
+        println!("everyone: {:?}", directory.everyone());
     }
 
-    fn add_employee_by_text_interface(
-        department_employees: &mut HashMap<String, Vec<String>>,
-        text: String,
-    ) {
-        let instruction = text.split_whitespace().collect::<Vec<&str>>();
-        if instruction.len() != 4 {
-            std::panic!("invalid instruction, you should input: Add {employee} to {department}");
-        }
-
-        let employee = *instruction.get(1).unwrap();
-        let department = *instruction.get(3).unwrap();
-        department_employees
-            .entry(String::from(department))
-            .or_insert(Vec::new());
-        department_employees
-            .get_mut(department)
-            .unwrap()
-            .push(String::from(employee));
-    }
-
-    fn get_department_employees(
-        department_employees: &HashMap<String, Vec<String>>,
-        department: String,
-    ) -> Option<Vec<String>> {
-        match department_employees.get(department.as_str()) {
-            Some(employees) => {
-                let mut e = employees.clone();
-                e.sort();
-                Some(e)
-            }
-            None => None,
-        }
-    }
-
-    fn get_all_people(department_employees: &HashMap<String, Vec<String>>) -> Option<Vec<String>> {
-        let mut employees: Vec<String> = Vec::new();
-        for x in department_employees {
-            for e in x.1 {
-                employees.push(e.clone());
+    /// Runs the directory on the commands typed in, for `cargo run -- directory [FILE]`,
+    /// keeping it in `FILE` when there is one.
+    pub fn interactive(path: Option<&Path>) {
+        println!("{}", command::HELP);
+        let path = match path {
+            Some(path) => path,
+            None => {
+                let mut directory = Directory::new();
+                command::repl(&mut directory, io::stdin().lock(), io::stdout().lock())
+                    .expect("Failed to read line");
+                return;
             }
+        };
+
+        let mut store = Store::open(path).unwrap_or_else(|err| {
+            eprintln!("Problem opening {}: {}", path.display(), err);
+            process::exit(1);
+        });
+        command::repl(&mut store, io::stdin().lock(), io::stdout().lock())
+            .expect("Failed to read line");
+        // every change is in the journal already, this just folds it into the file
+        if let Err(err) = store.close() {
+            eprintln!("Problem saving {}: {}", path.display(), err);
+            process::exit(1);
         }
-        if employees.len() > 0 {
-            employees.sort();
-            return Some(employees);
-        }
-        None
     }
 }
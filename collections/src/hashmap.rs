@@ -9,7 +9,9 @@ pub fn main() {
     // println!("{:?}", scores.get(String::from("Yellow"))); // expected &str, found String
 
     // we can construct HashMap by using the collect method on a vector of tuples
+    #[allow(clippy::useless_vec)]
     let teams = vec![String::from("Blue"), String::from("Yellow")];
+    #[allow(clippy::useless_vec)]
     let initial_scores = vec![10, 50];
     // we use HashMap<_,_> because Rust can infer the types based on the types of the data in te vec
     let scores: HashMap<_, _> = teams.iter().zip(initial_scores.iter()).collect();
@@ -0,0 +1,11 @@
+//! # Collections
+//!
+//! The exercises at the end of the Rust Book's chapter on common collections, grown into
+//! something worth calling from other code.
+
+pub mod accumulator;
+pub mod command;
+pub mod directory;
+pub mod pig_latin;
+pub mod stats;
+pub mod storage;
@@ -9,7 +9,15 @@ mod hashmap;
 mod string;
 mod vector;
 
+use std::path::Path;
+
 fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("directory") {
+        exercise::directory(args.get(2).map(Path::new));
+        return;
+    }
+
     vector::main();
     string::main();
     hashmap::main();
@@ -0,0 +1,318 @@
+// Pig Latin moves the consonants a word starts with to its end and adds "ay", so "string"
+// becomes "ingstray", and adds "yay" to words that start with a vowel, so "apple" becomes
+// "appleyay". A "u" after a "q" goes with it ("queen" is "eenquay"), and "y" is a
+// consonant at the start of a word but a vowel after one ("yellow" is "ellowyay", "my"
+// is "ymay").
+//
+// Text is taken a grapheme at a time, so accented letters stay whole, and only words in
+// the Latin alphabet are translated. Everything between them, punctuation, whitespace,
+// digits and other scripts, is left exactly as it was. A word's case is kept: "Hello" is
+// "Ellohay" and "NASA" is "ASANAY".
+//
+// Going back has to guess where the moved consonants began. It takes the longest run of
+// them that English words start with, so "ingstray" is "string" again, but a word like
+// "lists" comes back as "slist". And "esyay" could be "es" or "yes": it's taken to be
+// the word that starts with a vowel.
+
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The clusters of more than one consonant that English words start with.
+const ONSETS: [&str; 49] = [
+    "bl", "br", "ch", "cl", "cr", "dr", "fl", "fr", "gh", "gl", "gn", "gr", "kl", "kn", "kr", "ph",
+    "pl", "pn", "pr", "ps", "qu", "rh", "sc", "sh", "sk", "sl", "sm", "sn", "sp", "st", "sv", "sw",
+    "th", "tr", "tw", "wh", "wr", "chr", "phr", "sch", "scr", "shr", "sph", "spl", "spr", "squ",
+    "str", "thr", "thw",
+];
+
+/// Translates every word of `text` into Pig Latin.
+pub fn pig_latin(text: &str) -> String {
+    translate(text, to_pig_latin)
+}
+
+/// Translates every word of `text` out of Pig Latin, as well as it can be guessed. Words
+/// that aren't Pig Latin are left alone.
+pub fn from_pig_latin(text: &str) -> String {
+    translate(text, from_pig_latin_word)
+}
+
+/// Runs `word` over each word of `text`, copying everything else as it is.
+fn translate(text: &str, word: fn(&[&str]) -> String) -> String {
+    let graphemes: Vec<&str> = text.graphemes(true).collect();
+    let mut translated = String::with_capacity(text.len() * 3 / 2);
+    let mut i = 0;
+    while i < graphemes.len() {
+        if !is_letter(graphemes[i]) {
+            translated.push_str(graphemes[i]);
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        // an apostrophe between letters is part of the word, like in "don't"
+        while i < graphemes.len()
+            && (is_letter(graphemes[i])
+                || is_apostrophe(graphemes[i])
+                    && graphemes.get(i + 1).is_some_and(|next| is_letter(next)))
+        {
+            i += 1;
+        }
+        translated.push_str(&word(&graphemes[start..i]));
+    }
+    translated
+}
+
+fn to_pig_latin(word: &[&str]) -> String {
+    let (case, word) = lowercase(word);
+    let cluster = cluster_len(&word);
+    let translated = if cluster == 0 {
+        word.concat() + "yay"
+    } else {
+        word[cluster..].concat() + &word[..cluster].concat() + "ay"
+    };
+    case.apply(&translated)
+}
+
+fn from_pig_latin_word(word: &[&str]) -> String {
+    let (case, lower) = lowercase(word);
+    let len = lower.len();
+    if len < 3 || !is(&lower[len - 2], 'a') || !is(&lower[len - 1], 'y') {
+        return word.concat();
+    }
+
+    let body = &lower[..len - 2];
+    let original = if body.len() >= 2 && is(&body[body.len() - 1], 'y') && is_vowel(&body[0]) {
+        body[..body.len() - 1].concat()
+    } else if !body.iter().any(|g| is_vowel(g) || is(g, 'y')) {
+        // a word with no vowels had nothing to move
+        body.concat()
+    } else {
+        match onset(body) {
+            Some(start) => body[start..].concat() + &body[..start].concat(),
+            None => return word.concat(),
+        }
+    };
+    case.apply(&original)
+}
+
+/// How many graphemes the consonants at the start of `word` take up.
+fn cluster_len(word: &[String]) -> usize {
+    let mut len = 0;
+    while len < word.len() {
+        let grapheme = &word[len];
+        if len > 0 && is(&word[len - 1], 'q') && is(grapheme, 'u') {
+            len += 1;
+            continue;
+        }
+        if is_vowel(grapheme) || len > 0 && is(grapheme, 'y') || is_apostrophe(grapheme) {
+            break;
+        }
+        len += 1;
+    }
+    len
+}
+
+/// Where the moved consonants begin at the end of `body`: the longest run that English
+/// words start with, or any one consonant.
+fn onset(body: &[String]) -> Option<usize> {
+    (1..=3)
+        .rev()
+        .filter(|&len| len < body.len())
+        .find_map(|len| {
+            let start = body.len() - len;
+            let cluster = body[start..].concat().to_lowercase();
+            let single_consonant = len == 1 && !is_vowel(&cluster) && !is_apostrophe(&cluster);
+            if single_consonant || ONSETS.contains(&cluster.as_str()) {
+                Some(start)
+            } else {
+                None
+            }
+        })
+}
+
+/// A letter of the Latin alphabet, with or without accents.
+fn is_letter(grapheme: &str) -> bool {
+    grapheme.chars().next().is_some_and(is_latin)
+}
+
+fn is_latin(c: char) -> bool {
+    c.is_alphabetic()
+        && (c.is_ascii_alphabetic()
+            // Latin-1 Supplement, Latin Extended-A and B
+            || ('\u{c0}'..='\u{24f}').contains(&c)
+            // Latin Extended Additional, C, D and E, where some of the others' capitals
+            // and small letters are
+            || ('\u{1e00}'..='\u{1eff}').contains(&c)
+            || ('\u{2c60}'..='\u{2c7f}').contains(&c)
+            || ('\u{a720}'..='\u{a7ff}').contains(&c)
+            || ('\u{ab30}'..='\u{ab6f}').contains(&c))
+}
+
+fn is_apostrophe(grapheme: &str) -> bool {
+    grapheme == "'" || grapheme == "\u{2019}"
+}
+
+/// Whether a grapheme is a, e, i, o or u, accented or not, in either case.
+fn is_vowel(grapheme: &str) -> bool {
+    const VOWELS: &str = "aeiouàáâãäåæèéêëìíîïòóôõöøùúûüāăąēĕėęěĩīĭįıōŏőœũūŭůűų";
+    grapheme
+        .chars()
+        .next()
+        .is_some_and(|c| c.to_lowercase().any(|c| VOWELS.contains(c)))
+}
+
+/// Whether a grapheme is the plain lowercase `letter` or its capital.
+fn is(grapheme: &str, letter: char) -> bool {
+    grapheme.len() == 1 && grapheme.eq_ignore_ascii_case(letter.encode_utf8(&mut [0; 4]))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Case {
+    /// Left however it was.
+    AsIs,
+    /// The first letter capitalized, like "Hello".
+    Title,
+    /// Every letter capitalized, like "NASA".
+    Upper,
+}
+
+impl Case {
+    fn apply(self, word: &str) -> String {
+        match self {
+            Case::AsIs => String::from(word),
+            Case::Upper => word
+                .graphemes(true)
+                .map(|g| recase(g, str::to_uppercase))
+                .collect(),
+            Case::Title => {
+                let mut graphemes = word.graphemes(true);
+                let first = recase(graphemes.next().unwrap_or_default(), str::to_uppercase);
+                first + graphemes.as_str()
+            }
+        }
+    }
+}
+
+/// The grapheme changed to the other case, unless that would change more than its
+/// letters, like a Greek accent that capitalizes to a separate letter.
+fn recase(grapheme: &str, change: fn(&str) -> String) -> String {
+    let changed = change(grapheme);
+    let others = |g: &str| -> String { g.chars().filter(|&c| !is_latin(c)).collect() };
+    if others(&changed) == others(grapheme) {
+        changed
+    } else {
+        String::from(grapheme)
+    }
+}
+
+/// The word's case, and its graphemes in lowercase unless its case is something to keep
+/// as it is, like "iPhone".
+fn lowercase(word: &[&str]) -> (Case, Vec<String>) {
+    let upper = |g: &&str| g.chars().next().is_some_and(char::is_uppercase);
+    let letters: Vec<&str> = word.iter().copied().filter(|g| is_letter(g)).collect();
+    let case = if letters.len() > 1 && letters.iter().all(upper) {
+        Case::Upper
+    } else if letters.first().is_some_and(upper) && !letters[1..].iter().any(upper) {
+        Case::Title
+    } else {
+        Case::AsIs
+    };
+
+    let graphemes = word
+        .iter()
+        .map(|g| match case {
+            Case::AsIs => String::from(*g),
+            Case::Title | Case::Upper => recase(g, str::to_lowercase),
+        })
+        .collect();
+    (case, graphemes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_pig_latin, is_letter, pig_latin};
+    use proptest::prelude::*;
+    use unicode_segmentation::UnicodeSegmentation;
+
+    #[test]
+    fn words() {
+        let expected = [
+            ("string", "ingstray"),
+            ("first", "irstfay"),
+            ("apple", "appleyay"),
+            ("queen", "eenquay"),
+            ("square", "aresquay"),
+            ("yellow", "ellowyay"),
+            ("rhythm", "ythmrhay"),
+            ("my", "ymay"),
+            ("a", "ayay"),
+            ("hmm", "hmmay"),
+            ("don't", "on'tday"),
+            ("école", "écoleyay"),
+            ("naïve", "aïvenay"),
+        ];
+        for &(word, translated) in expected.iter() {
+            assert_eq!(translated, pig_latin(word), "{}", word);
+        }
+    }
+
+    #[test]
+    fn case_is_kept() {
+        assert_eq!("Ellohay", pig_latin("Hello"));
+        assert_eq!("ASANAY", pig_latin("NASA"));
+        assert_eq!("Iyay", pig_latin("I"));
+        assert_eq!("iPhoneyay", pig_latin("iPhone"));
+        assert_eq!("onaldMcDay", pig_latin("McDonald"));
+    }
+
+    #[test]
+    fn everything_else_is_kept() {
+        assert_eq!("", pig_latin(""));
+        assert_eq!(
+            "Ellohay, orldway! Owhay areyay ouyay?\n\t(42 imestay)",
+            pig_latin("Hello, world! How are you?\n\t(42 times)")
+        );
+        assert_eq!("Здравствуйте, 世界!", pig_latin("Здравствуйте, 世界!"));
+        // a trailing apostrophe is punctuation, not part of the word
+        assert_eq!("ogsday'", pig_latin("dogs'"));
+        // an e with a combining acute accent is still one letter
+        assert_eq!("afe\u{301}cay", pig_latin("cafe\u{301}"));
+    }
+
+    #[test]
+    fn back_again() {
+        let text = "Hello, world! The string of the queen squeals; my rhythm is high, NASA isn't.";
+        assert_eq!(text, from_pig_latin(&pig_latin(text)));
+        assert_eq!("Apple", from_pig_latin("Appleyay"));
+        assert_eq!("hmm", from_pig_latin("hmmay"));
+        // not Pig Latin, so nothing to undo
+        assert_eq!("banana split", from_pig_latin("banana split"));
+    }
+
+    #[test]
+    fn guesses_going_back() {
+        // "yes" and "es" both become "esyay", taken as the one that starts with a vowel
+        assert_eq!("esyay", pig_latin("yes"));
+        assert_eq!("es", from_pig_latin("esyay"));
+        // "sl" starts words too, so the "s" of "lists" is taken as moved as well
+        assert_eq!("slist", from_pig_latin(&pig_latin("lists")));
+    }
+
+    proptest! {
+        #[test]
+        fn only_words_change(text in any::<String>()) {
+            let others = |text: &str| -> String {
+                text.graphemes(true).filter(|g| !is_letter(g) && *g != "'").collect()
+            };
+            let translated = pig_latin(&text);
+            prop_assert_eq!(others(&text), others(&translated));
+            prop_assert_eq!(others(&text), others(&from_pig_latin(&translated)));
+        }
+
+        #[test]
+        fn simple_words_come_back(word in "[bcdfghjklmnprstvwz][aeiou][a-z]{0,6}[aeiou]") {
+            // one consonant to move and a vowel before it once moved, so there's only one
+            // way back
+            prop_assert_eq!(&word, &from_pig_latin(&pig_latin(&word)));
+        }
+    }
+}
@@ -0,0 +1,320 @@
+// Summary statistics over slices of any of the primitive number types.
+//
+// Everything is worked out in f64, so means and medians of integers aren't truncated.
+// An empty slice has no mean, median or mode, and anything that has to sort the values
+// can't when one of them is NaN, so those come back as errors rather than panics or
+// made-up numbers.
+
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+
+/// A primitive number the statistics can be taken of.
+pub trait Number: Copy + PartialOrd {
+    fn to_f64(self) -> f64;
+}
+
+macro_rules! number {
+    ($($t:ty),*) => {
+        $(impl Number for $t {
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+        })*
+    };
+}
+
+number!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StatsError {
+    /// There are no values to summarize.
+    Empty,
+    /// A value is NaN, so the values can't be put in order.
+    NotANumber,
+    /// The sample variance needs at least two values.
+    TooFew { needed: usize, got: usize },
+    /// Percentiles go from 0 to 100.
+    OutOfRange(f64),
+}
+
+impl fmt::Display for StatsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StatsError::Empty => write!(f, "there are no values"),
+            StatsError::NotANumber => write!(f, "a value isn't a number"),
+            StatsError::TooFew { needed, got } => {
+                write!(f, "needed at least {} values, got {}", needed, got)
+            }
+            StatsError::OutOfRange(p) => write!(f, "{} isn't a percentile from 0 to 100", p),
+        }
+    }
+}
+
+impl Error for StatsError {}
+
+/// Whether the values are everything there is or a sample of it, which decides what the
+/// sum of squared deviations is divided by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variance {
+    /// Divide by n.
+    Population,
+    /// Divide by n - 1, Bessel's correction for estimating from a sample.
+    Sample,
+}
+
+/// The average, or `None` for no values.
+pub fn mean<T: Number>(values: &[T]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    let sum: f64 = values.iter().map(|v| v.to_f64()).sum();
+    Some(sum / values.len() as f64)
+}
+
+/// A sorted copy of `values`.
+fn sorted<T: Number>(values: &[T]) -> Result<Vec<T>, StatsError> {
+    if values.is_empty() {
+        return Err(StatsError::Empty);
+    }
+    // only NaN isn't equal to itself
+    if values.iter().any(|v| v.partial_cmp(v).is_none()) {
+        return Err(StatsError::NotANumber);
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    Ok(sorted)
+}
+
+/// The middle value, or the mean of the two middle values when there's an even number.
+pub fn median<T: Number>(values: &[T]) -> Result<f64, StatsError> {
+    percentile_of_sorted(&sorted(values)?, 50.0)
+}
+
+/// Every value that comes up most often, smallest first. All of them when none repeats.
+pub fn modes<T: Number>(values: &[T]) -> Result<Vec<T>, StatsError> {
+    let sorted = sorted(values)?;
+
+    let mut modes = Vec::new();
+    let mut most = 0;
+    let mut start = 0;
+    while start < sorted.len() {
+        // the run of values equal to this one
+        let end = sorted[start..]
+            .iter()
+            .position(|v| *v != sorted[start])
+            .map_or(sorted.len(), |len| start + len);
+        let count = end - start;
+        if count > most {
+            most = count;
+            modes.clear();
+        }
+        if count == most {
+            modes.push(sorted[start]);
+        }
+        start = end;
+    }
+    Ok(modes)
+}
+
+/// How spread out the values are: the mean squared distance from the mean.
+pub fn variance<T: Number>(values: &[T], kind: Variance) -> Result<f64, StatsError> {
+    let needed = match kind {
+        Variance::Population => 1,
+        Variance::Sample => 2,
+    };
+    if values.is_empty() {
+        return Err(StatsError::Empty);
+    }
+    if values.len() < needed {
+        return Err(StatsError::TooFew {
+            needed,
+            got: values.len(),
+        });
+    }
+
+    let mean = mean(values).unwrap();
+    let squares: f64 = values.iter().map(|v| (v.to_f64() - mean).powi(2)).sum();
+    let divisor = match kind {
+        Variance::Population => values.len(),
+        Variance::Sample => values.len() - 1,
+    };
+    Ok(squares / divisor as f64)
+}
+
+/// The square root of the variance, in the same units as the values.
+pub fn std_dev<T: Number>(values: &[T], kind: Variance) -> Result<f64, StatsError> {
+    variance(values, kind).map(f64::sqrt)
+}
+
+/// The value `p` percent of the way through the values, from 0 for the smallest to 100
+/// for the largest, interpolating between the two values either side. It's the same
+/// definition spreadsheets use for `PERCENTILE.INC`.
+pub fn percentile<T: Number>(values: &[T], p: f64) -> Result<f64, StatsError> {
+    check_percentile(p)?;
+    percentile_of_sorted(&sorted(values)?, p)
+}
+
+/// Several percentiles at once, sorting the values just the once.
+pub fn percentiles<T: Number>(values: &[T], ps: &[f64]) -> Result<Vec<f64>, StatsError> {
+    for &p in ps {
+        check_percentile(p)?;
+    }
+    let sorted = sorted(values)?;
+    ps.iter()
+        .map(|&p| percentile_of_sorted(&sorted, p))
+        .collect()
+}
+
+fn check_percentile(p: f64) -> Result<(), StatsError> {
+    if (0.0..=100.0).contains(&p) {
+        Ok(())
+    } else {
+        Err(StatsError::OutOfRange(p))
+    }
+}
+
+fn percentile_of_sorted<T: Number>(sorted: &[T], p: f64) -> Result<f64, StatsError> {
+    if sorted.is_empty() {
+        return Err(StatsError::Empty);
+    }
+    let rank = p / 100.0 * (sorted.len() - 1) as f64;
+    let below = rank.floor() as usize;
+    let above = rank.ceil() as usize;
+    let (low, high) = (sorted[below].to_f64(), sorted[above].to_f64());
+    Ok(low + (high - low) * (rank - below as f64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{mean, median, modes, percentile, percentiles, std_dev, variance};
+    use super::{StatsError, Variance};
+    use proptest::prelude::*;
+
+    #[test]
+    fn the_exercise_list() {
+        let list = [1, 2, 9, 3, 4, 6, 5, 8, 7, 5, 6];
+        assert_eq!(Some(56.0 / 11.0), mean(&list));
+        // eleven values, so the sixth once sorted
+        assert_eq!(Ok(5.0), median(&list));
+        // 5 and 6 both come up twice
+        assert_eq!(Ok(vec![5, 6]), modes(&list));
+    }
+
+    #[test]
+    fn medians() {
+        assert_eq!(Ok(2.5), median(&[4, 1, 3, 2]));
+        assert_eq!(Ok(7.0), median(&[7u8]));
+        assert_eq!(Ok(-1.5), median(&[-1.0, -2.0]));
+        assert_eq!(Err(StatsError::Empty), median::<i32>(&[]));
+        assert_eq!(Err(StatsError::NotANumber), median(&[1.0, f64::NAN]));
+    }
+
+    #[test]
+    fn means_dont_truncate_or_overflow() {
+        assert_eq!(Some(1.5), mean(&[1, 2]));
+        assert_eq!(Some(i64::MAX as f64), mean(&[i64::MAX, i64::MAX]));
+        assert_eq!(None, mean::<f32>(&[]));
+    }
+
+    #[test]
+    fn all_the_modes() {
+        assert_eq!(Ok(vec![3]), modes(&[3, 1, 3, 2]));
+        assert_eq!(Ok(vec![1, 2, 3]), modes(&[3, 2, 1]));
+        assert_eq!(Ok(vec![0.5]), modes(&[0.5, 0.25, 0.5]));
+        assert_eq!(Err(StatsError::Empty), modes::<u8>(&[]));
+    }
+
+    #[test]
+    fn spread() {
+        let values = [2, 4, 4, 4, 5, 5, 7, 9];
+        assert_eq!(Ok(4.0), variance(&values, Variance::Population));
+        assert_eq!(Ok(2.0), std_dev(&values, Variance::Population));
+        assert_eq!(Ok(32.0 / 7.0), variance(&values, Variance::Sample));
+
+        assert_eq!(Ok(0.0), variance(&[5], Variance::Population));
+        assert_eq!(
+            Err(StatsError::TooFew { needed: 2, got: 1 }),
+            variance(&[5], Variance::Sample)
+        );
+        assert_eq!(
+            Err(StatsError::Empty),
+            std_dev::<i32>(&[], Variance::Sample)
+        );
+    }
+
+    #[test]
+    fn percentile_interpolates() {
+        let values = [15, 20, 35, 40, 50];
+        assert_eq!(Ok(15.0), percentile(&values, 0.0));
+        assert_eq!(Ok(29.0), percentile(&values, 40.0));
+        assert_eq!(Ok(50.0), percentile(&values, 100.0));
+        assert_eq!(
+            Ok(vec![20.0, 35.0, 40.0]),
+            percentiles(&values, &[25.0, 50.0, 75.0])
+        );
+        assert_eq!(
+            Err(StatsError::OutOfRange(101.0)),
+            percentile(&values, 101.0)
+        );
+        assert_eq!(
+            Err(StatsError::OutOfRange(-1.0)),
+            percentiles(&values, &[50.0, -1.0])
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn mean_is_between_min_and_max(values in prop::collection::vec(-1_000_000i32..1_000_000, 1..100)) {
+            let mean = mean(&values).unwrap();
+            let min = *values.iter().min().unwrap() as f64;
+            let max = *values.iter().max().unwrap() as f64;
+            prop_assert!(min <= mean && mean <= max);
+        }
+
+        #[test]
+        fn median_splits_the_values(values in prop::collection::vec(-1000i64..1000, 1..100)) {
+            let median = median(&values).unwrap();
+            let below = values.iter().filter(|&&v| (v as f64) < median).count();
+            let above = values.iter().filter(|&&v| (v as f64) > median).count();
+            prop_assert!(below <= values.len() / 2 && above <= values.len() / 2);
+        }
+
+        #[test]
+        fn order_doesnt_matter(mut values in prop::collection::vec(any::<u16>(), 1..50)) {
+            let before = (median(&values), modes(&values), percentile(&values, 90.0));
+            values.reverse();
+            prop_assert_eq!(before, (median(&values), modes(&values), percentile(&values, 90.0)));
+        }
+
+        #[test]
+        fn modes_are_the_most_common(values in prop::collection::vec(0u8..10, 1..50)) {
+            let count = |x: u8| values.iter().filter(|&&v| v == x).count();
+            let modes = modes(&values).unwrap();
+            let most = (0..10).map(count).max().unwrap();
+            for &mode in &modes {
+                prop_assert_eq!(most, count(mode));
+            }
+            prop_assert_eq!((0..10).filter(|&x| count(x) == most).count(), modes.len());
+        }
+
+        #[test]
+        fn variance_ignores_shifts(values in prop::collection::vec(-1000.0f64..1000.0, 2..50), shift in -1000.0f64..1000.0) {
+            let shifted: Vec<f64> = values.iter().map(|v| v + shift).collect();
+            let (a, b) = (
+                variance(&values, Variance::Sample).unwrap(),
+                variance(&shifted, Variance::Sample).unwrap(),
+            );
+            prop_assert!(a >= 0.0);
+            prop_assert!((a - b).abs() <= 1e-6 * a.max(1.0));
+        }
+
+        #[test]
+        fn percentiles_are_in_order(values in prop::collection::vec(any::<i16>(), 1..50), p in 0.0f64..100.0) {
+            let [low, middle, high] = [0.0, p, 100.0].map(|p| percentile(&values, p).unwrap());
+            prop_assert!(low <= middle && middle <= high);
+            prop_assert_eq!(low, *values.iter().min().unwrap() as f64);
+            prop_assert_eq!(high, *values.iter().max().unwrap() as f64);
+        }
+    }
+}
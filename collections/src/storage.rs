@@ -0,0 +1,988 @@
+// Keeping the employee directory on disk between runs.
+//
+// A directory is saved whole as a snapshot, in CSV with a row per employee and department
+//
+//     department,employee
+//     Engineering,Sally
+//     Sales,"Khan, Amir"
+//
+// or as JSON, an object of departments each with a list of employees. Saving one writes a
+// temporary file and renames it over the old, so a crash mid-write leaves the previous
+// snapshot intact.
+//
+// Rewriting the whole snapshot for every change would be slow, so a `Store` writes each
+// command that changes the directory to a journal beside it first, one a line, and only
+// says the command is done once the line is synced to disk:
+//
+//     collections journal v1 <hash of the snapshot it follows>
+//     Add Sally to Engineering
+//     Move Sally from Engineering to Sales
+//
+// Opening the store again replays the journal on the snapshot, so nothing acknowledged is
+// lost whenever the program stopped. Every so often, and on closing, the journal is folded
+// into a new snapshot and started again empty. The hash in its first line says which
+// snapshot a journal goes with. Before the new snapshot replaces the old, the journal gets
+// one more line with the new snapshot's hash,
+//
+//     checkpoint <hash of the new snapshot>
+//
+// so a crash after the new snapshot is in place but before the journal starts again leaves
+// one that's known to be part of the snapshot already, and it's skipped rather than
+// replayed twice. Any other journal that doesn't go with the snapshot is an error, rather
+// than a reason to lose what's in it.
+
+use crate::command::{Command, Session};
+use crate::directory::{Directory, DirectoryError};
+use serde_json::{Map, Value};
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{self, BufRead, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+const CSV_HEADER: &str = "department,employee";
+const JOURNAL_HEADER: &str = "collections journal v1";
+const CHECKPOINT_MARK: &str = "checkpoint";
+
+/// How many commands the journal holds before it's folded into the snapshot.
+pub const CHECKPOINT_EVERY: usize = 100;
+
+#[derive(Debug)]
+pub enum StorageError {
+    Io(io::Error),
+    /// Another process has the directory open.
+    Locked(PathBuf),
+    /// A snapshot that can't be read, at a line of the file counting from 1.
+    Corrupt {
+        line: usize,
+        reason: String,
+    },
+    /// A command in the journal that can't be read or carried out.
+    Journal {
+        line: usize,
+        reason: String,
+    },
+    /// A command that can't be carried out on the directory.
+    Directory(DirectoryError),
+    /// A write to the journal failed and what it left couldn't be taken back, so nothing
+    /// more is journaled until the directory is opened again.
+    Unusable,
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StorageError::Io(e) => write!(f, "{}", e),
+            StorageError::Locked(path) => {
+                write!(f, "{} is open somewhere else", path.display())
+            }
+            StorageError::Corrupt { line, reason } => {
+                write!(f, "the directory is corrupt at line {}: {}", line, reason)
+            }
+            StorageError::Journal { line, reason } => {
+                write!(f, "the journal is corrupt at line {}: {}", line, reason)
+            }
+            StorageError::Directory(e) => write!(f, "{}", e),
+            StorageError::Unusable => {
+                write!(f, "the journal can't be written until it's opened again")
+            }
+        }
+    }
+}
+
+impl Error for StorageError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            StorageError::Io(e) => Some(e),
+            StorageError::Directory(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for StorageError {
+    fn from(e: io::Error) -> StorageError {
+        StorageError::Io(e)
+    }
+}
+
+impl From<DirectoryError> for StorageError {
+    fn from(e: DirectoryError) -> StorageError {
+        StorageError::Directory(e)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Csv,
+    Json,
+}
+
+impl Format {
+    /// JSON for a `.json` file, whatever the case, and CSV for anything else.
+    pub fn of(path: &Path) -> Format {
+        match path.extension() {
+            Some(extension) if extension.eq_ignore_ascii_case("json") => Format::Json,
+            _ => Format::Csv,
+        }
+    }
+
+    pub fn write(self, directory: &Directory, out: impl Write) -> io::Result<()> {
+        match self {
+            Format::Csv => write_csv(directory, out),
+            Format::Json => write_json(directory, out),
+        }
+    }
+
+    pub fn read(self, input: impl BufRead) -> Result<Directory, StorageError> {
+        match self {
+            Format::Csv => read_csv(input),
+            Format::Json => read_json(input),
+        }
+    }
+}
+
+/// A CSV field, quoted if it has to be.
+fn csv_field(field: &str) -> String {
+    let plain = !field.contains([',', '"', '\n', '\r']) && field.trim() == field;
+    if plain {
+        String::from(field)
+    } else {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    }
+}
+
+pub fn write_csv(directory: &Directory, mut out: impl Write) -> io::Result<()> {
+    writeln!(out, "{}", CSV_HEADER)?;
+    for (department, employees) in directory.departments() {
+        for employee in employees {
+            writeln!(out, "{},{}", csv_field(department), csv_field(employee))?;
+        }
+    }
+    Ok(())
+}
+
+/// The records of a CSV file, each with the line it starts on. A quoted field can go over
+/// more than one line.
+fn csv_records(text: &str) -> Result<Vec<(usize, Vec<String>)>, StorageError> {
+    let mut records = Vec::new();
+    let mut chars = text.chars().peekable();
+    let mut line = 1;
+    while chars.peek().is_some() {
+        let start = line;
+        let mut fields = Vec::new();
+        let mut field = String::new();
+        loop {
+            match chars.next() {
+                Some('"') if field.is_empty() => loop {
+                    match chars.next() {
+                        Some('"') if chars.peek() == Some(&'"') => {
+                            chars.next();
+                            field.push('"');
+                        }
+                        Some('"') => break,
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            field.push(c);
+                        }
+                        None => {
+                            return Err(StorageError::Corrupt {
+                                line: start,
+                                reason: String::from("a quote is never closed"),
+                            })
+                        }
+                    }
+                },
+                Some(',') => fields.push(std::mem::take(&mut field)),
+                Some('\r') if chars.peek() == Some(&'\n') => {}
+                Some('\n') | None => {
+                    line += 1;
+                    fields.push(field);
+                    break;
+                }
+                Some(c) => field.push(c),
+            }
+        }
+        records.push((start, fields));
+    }
+    Ok(records)
+}
+
+pub fn read_csv(mut input: impl BufRead) -> Result<Directory, StorageError> {
+    let mut text = String::new();
+    input.read_to_string(&mut text)?;
+    let mut records = csv_records(&text)?.into_iter();
+    match records.next() {
+        None => return Ok(Directory::new()),
+        Some((_, header)) if header.join(",") == CSV_HEADER => {}
+        Some(_) => {
+            return Err(StorageError::Corrupt {
+                line: 1,
+                reason: format!("the header has to be {}", CSV_HEADER),
+            })
+        }
+    }
+
+    let mut directory = Directory::new();
+    for (line, fields) in records {
+        let corrupt = |reason: &str| StorageError::Corrupt {
+            line,
+            reason: String::from(reason),
+        };
+        match fields.as_slice() {
+            [blank] if blank.is_empty() => continue,
+            [department, employee] => {
+                if department.is_empty() || employee.is_empty() {
+                    return Err(corrupt("a name is empty"));
+                }
+                directory
+                    .add(employee, department)
+                    .map_err(|e| corrupt(&e.to_string()))?;
+            }
+            _ => return Err(corrupt("expected a department and an employee")),
+        }
+    }
+    Ok(directory)
+}
+
+pub fn write_json(directory: &Directory, mut out: impl Write) -> io::Result<()> {
+    let departments: Map<String, Value> = directory
+        .departments()
+        .map(|(department, employees)| (String::from(department), Value::from(employees)))
+        .collect();
+    serde_json::to_writer_pretty(&mut out, &departments)?;
+    writeln!(out)
+}
+
+pub fn read_json(input: impl BufRead) -> Result<Directory, StorageError> {
+    let value: Value = serde_json::from_reader(input).map_err(|e| StorageError::Corrupt {
+        line: e.line(),
+        reason: e.to_string(),
+    })?;
+    // serde_json can't say where in the file a value was once it's read
+    let corrupt = |reason: String| StorageError::Corrupt { line: 1, reason };
+
+    let departments = value
+        .as_object()
+        .ok_or_else(|| corrupt(String::from("expected an object of departments")))?;
+    let mut directory = Directory::new();
+    for (department, employees) in departments {
+        let employees = employees
+            .as_array()
+            .ok_or_else(|| corrupt(format!("expected a list of employees in {}", department)))?;
+        for employee in employees {
+            match employee.as_str() {
+                Some(employee) if !employee.is_empty() && !department.is_empty() => directory
+                    .add(employee, department)
+                    .map_err(|e| corrupt(e.to_string()))?,
+                _ => {
+                    return Err(corrupt(format!(
+                        "expected employees' names in {}",
+                        department
+                    )))
+                }
+            }
+        }
+    }
+    Ok(directory)
+}
+
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// Writes `contents` to `path` through a temporary file, so it's either all there or not
+/// changed at all.
+fn replace(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let tmp = sibling(path, &format!(".tmp{}", std::process::id()));
+    let written = (|| -> io::Result<()> {
+        let mut file = File::create(&tmp)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        fs::rename(&tmp, path)?;
+        sync_parent(path)
+    })();
+    if written.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    written
+}
+
+/// Syncs the directory `path` is in, so a file renamed to `path` stays renamed after a crash.
+fn sync_parent(path: &Path) -> io::Result<()> {
+    if cfg!(windows) {
+        // directories can't be opened like files there, and renames are synced already
+        return Ok(());
+    }
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    File::open(parent)?.sync_all()
+}
+
+/// Saves `directory` to `path`, in JSON or CSV depending on its extension.
+pub fn save(directory: &Directory, path: &Path) -> Result<(), StorageError> {
+    let mut contents = Vec::new();
+    Format::of(path).write(directory, &mut contents)?;
+    replace(path, &contents)?;
+    Ok(())
+}
+
+/// The directory saved in `path`. A missing file is an empty directory.
+pub fn load(path: &Path) -> Result<Directory, StorageError> {
+    Ok(read_snapshot(path)?.0)
+}
+
+/// The directory saved in `path` and the hash of the file it was read from.
+fn read_snapshot(path: &Path) -> Result<(Directory, u64), StorageError> {
+    let contents = match fs::read(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(e.into()),
+    };
+    let directory = match Format::of(path) {
+        // an empty file has nothing in it yet, which isn't a JSON object
+        Format::Json if contents.is_empty() => Directory::new(),
+        format => format.read(contents.as_slice())?,
+    };
+    Ok((directory, fnv1a(&contents)))
+}
+
+/// The 64-bit FNV-1a hash, which unlike `DefaultHasher` is the same from one build to the
+/// next.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// A directory kept in a snapshot file with a journal of the changes since.
+#[derive(Debug)]
+pub struct Store {
+    path: PathBuf,
+    directory: Directory,
+    journal: File,
+    /// How many commands the journal has.
+    entries: usize,
+    /// Whether the journal may end in a line that was never acknowledged.
+    unusable: bool,
+    /// The header the journal still has to be started again with, when a checkpoint saved
+    /// the snapshot but couldn't start the journal for it.
+    restart: Option<String>,
+    /// Why the last checkpoint made along the way failed, until it's been asked for.
+    checkpoint_error: Option<StorageError>,
+}
+
+impl Store {
+    /// Opens the directory saved in `path`, with every change journaled since, and keeps
+    /// other processes from opening it until it's closed. A missing file is an empty
+    /// directory.
+    pub fn open(path: &Path) -> Result<Store, StorageError> {
+        let mut journal = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .read(true)
+            .write(true)
+            .open(sibling(path, ".journal"))?;
+        match journal.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => return Err(StorageError::Locked(path.to_path_buf())),
+            Err(TryLockError::Error(e)) => return Err(e.into()),
+        }
+
+        let (mut directory, hash) = read_snapshot(path)?;
+        let mut contents = String::new();
+        journal.read_to_string(&mut contents)?;
+        let header = format!("{} {:016x}", JOURNAL_HEADER, hash);
+
+        let mut entries = 0;
+        match contents.split_once('\n') {
+            Some((first, commands)) if first == header => {
+                // a command is only done once its whole line is written, so anything after
+                // the last newline was cut off by a crash before it could be
+                let complete = commands.rfind('\n').map_or(0, |end| end + 1);
+                for (i, line) in commands[..complete].lines().enumerate() {
+                    // left by a checkpoint that didn't get as far as replacing the snapshot
+                    if checkpoint_of(line).is_some() {
+                        continue;
+                    }
+                    let corrupt = |reason: String| StorageError::Journal {
+                        line: i + 2,
+                        reason,
+                    };
+                    let command: Command = line.parse().map_err(|e| corrupt(format!("{}", e)))?;
+                    command
+                        .apply(&mut directory)
+                        .map_err(|e| corrupt(e.to_string()))?;
+                    entries += 1;
+                }
+                let end = first.len() + 1 + complete;
+                if end < contents.len() {
+                    journal.set_len(end as u64)?;
+                }
+                journal.seek(SeekFrom::Start(end as u64))?;
+            }
+            Some((first, commands)) if first.starts_with(JOURNAL_HEADER) => {
+                // a journal for another snapshot, which is only safe to start again when
+                // there's nothing in it or a checkpoint put all of it in this snapshot
+                let complete = &commands[..commands.rfind('\n').map_or(0, |end| end + 1)];
+                let folded = complete.lines().last().and_then(checkpoint_of) == Some(hash);
+                if !complete.is_empty() && !folded {
+                    return Err(StorageError::Journal {
+                        line: 1,
+                        reason: String::from("it goes with a different snapshot"),
+                    });
+                }
+                start_journal(&mut journal, &header)?;
+            }
+            Some(_) => {
+                return Err(StorageError::Journal {
+                    line: 1,
+                    reason: String::from("it doesn't start with a journal header"),
+                })
+            }
+            // a new journal, or one that never got its header written
+            None => start_journal(&mut journal, &header)?,
+        }
+
+        Ok(Store {
+            path: path.to_path_buf(),
+            directory,
+            journal,
+            entries,
+            unusable: false,
+            restart: None,
+            checkpoint_error: None,
+        })
+    }
+
+    pub fn directory(&self) -> &Directory {
+        &self.directory
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Carries out `command`, and only once it's in the journal on disk. The directory is
+    /// checkpointed every so often along the way, and if that fails the command still counts:
+    /// the checkpoint is tried again with the next command, and `take_checkpoint_error` says
+    /// what went wrong.
+    pub fn execute(&mut self, command: &Command) -> Result<(), StorageError> {
+        if !command.changes() {
+            return Ok(());
+        }
+        // a command that fails leaves the directory as it was and is never journaled, and
+        // one that can't be journaled is taken back again
+        command.apply(&mut self.directory)?;
+        if let Err(e) = self.append(&format!("{}\n", command)) {
+            if let Some(inverse) = command.inverse() {
+                inverse
+                    .apply(&mut self.directory)
+                    .expect("undoing a command that was just carried out");
+            }
+            return Err(e);
+        }
+        self.entries += 1;
+
+        if self.entries >= CHECKPOINT_EVERY {
+            self.checkpoint_error = self.checkpoint().err();
+        }
+        Ok(())
+    }
+
+    /// Why the last checkpoint `execute` made failed, if it did and nothing's asked since.
+    pub fn take_checkpoint_error(&mut self) -> Option<StorageError> {
+        self.checkpoint_error.take()
+    }
+
+    /// Saves the whole directory to its snapshot and starts the journal again.
+    pub fn checkpoint(&mut self) -> Result<(), StorageError> {
+        let mut contents = Vec::new();
+        Format::of(&self.path).write(&self.directory, &mut contents)?;
+        let hash = fnv1a(&contents);
+        self.append(&format!("{} {:016x}\n", CHECKPOINT_MARK, hash))?;
+        replace(&self.path, &contents)?;
+        // everything is in the snapshot now, whatever happens to the journal
+        self.entries = 0;
+        let header = format!("{} {:016x}", JOURNAL_HEADER, hash);
+        if let Err(e) = start_journal(&mut self.journal, &header) {
+            // what's left of the journal doesn't go with the new snapshot, so nothing can
+            // be added to it until it's started again
+            self.restart = Some(header);
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    /// Writes `line` to the end of the journal and syncs it, or leaves the journal as it
+    /// was: a line that's partly written would be joined by the next one, and one that's
+    /// written but not synced would be replayed without ever having been acknowledged.
+    fn append(&mut self, line: &str) -> Result<(), StorageError> {
+        if self.unusable {
+            return Err(StorageError::Unusable);
+        }
+        if let Some(header) = &self.restart {
+            start_journal(&mut self.journal, header)?;
+            self.restart = None;
+        }
+        let end = self.journal.stream_position()?;
+        let written = self
+            .journal
+            .write_all(line.as_bytes())
+            .and_then(|()| self.journal.sync_data());
+        if let Err(e) = written {
+            let undone = self
+                .journal
+                .set_len(end)
+                .and_then(|()| self.journal.seek(SeekFrom::Start(end)))
+                .and_then(|_| self.journal.sync_data());
+            self.unusable = undone.is_err();
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    /// Saves the directory and lets other processes open it.
+    pub fn close(mut self) -> Result<(), StorageError> {
+        self.checkpoint()
+    }
+}
+
+/// The hash of the snapshot a checkpoint line of the journal was written for.
+fn checkpoint_of(line: &str) -> Option<u64> {
+    let hash = line.strip_prefix(CHECKPOINT_MARK)?.strip_prefix(' ')?;
+    u64::from_str_radix(hash, 16).ok()
+}
+
+/// Empties the journal down to its header.
+fn start_journal(journal: &mut File, header: &str) -> io::Result<()> {
+    journal.set_len(0)?;
+    journal.seek(SeekFrom::Start(0))?;
+    journal.write_all(format!("{}\n", header).as_bytes())?;
+    journal.sync_data()
+}
+
+impl Session for Store {
+    fn directory(&self) -> &Directory {
+        &self.directory
+    }
+
+    fn apply(&mut self, command: &Command) -> Result<(), Box<dyn Error>> {
+        self.execute(command).map_err(Box::from)
+    }
+
+    fn warning(&mut self) -> Option<String> {
+        let e = self.take_checkpoint_error()?;
+        Some(format!(
+            "couldn't save the directory, the journal keeps the changes: {}",
+            e
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a file `name` in a directory of its own, removed with everything in it when the
+    // test is over
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> TempDir {
+            let dir =
+                std::env::temp_dir().join(format!("collections-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            TempDir(dir.join(name))
+        }
+
+        fn path(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            if let Some(dir) = self.0.parent() {
+                let _ = fs::remove_dir_all(dir);
+            }
+        }
+    }
+
+    fn company() -> Directory {
+        let mut directory = Directory::new();
+        directory.add("Sally", "Engineering").unwrap();
+        directory.add("Khan, Amir", "Sales").unwrap();
+        directory
+            .add("Dwayne \"The Rock\" Johnson", "Sales")
+            .unwrap();
+        directory.add(" Leon", "Research\nand Development").unwrap();
+        directory
+    }
+
+    fn command(line: &str) -> Command {
+        line.parse().unwrap()
+    }
+
+    #[test]
+    fn csv() {
+        let mut out = Vec::new();
+        write_csv(&company(), &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert_eq!(
+            "\
+department,employee
+Engineering,Sally
+\"Research
+and Development\",\" Leon\"
+Sales,\"Dwayne \"\"The Rock\"\" Johnson\"
+Sales,\"Khan, Amir\"
+",
+            text
+        );
+        assert_eq!(company(), read_csv(text.as_bytes()).unwrap());
+        // written elsewhere, with Windows line endings and a blank line at the end
+        let windows = read_csv(&b"department,employee\r\nSales,Amir\r\n\r\n"[..]).unwrap();
+        assert_eq!(vec!["Amir"], windows.everyone());
+        assert_eq!(Directory::new(), read_csv(&b""[..]).unwrap());
+    }
+
+    #[test]
+    fn corrupt_csv() {
+        let line = |text: &str| match read_csv(text.as_bytes()) {
+            Err(StorageError::Corrupt { line, .. }) => line,
+            other => panic!("unexpected {:?}", other),
+        };
+        assert_eq!(1, line("employee,department\nSally,Engineering\n"));
+        assert_eq!(3, line("department,employee\nSales,Amir\nSales\n"));
+        // the record with the empty department starts on line 4, the quoted one before it
+        // taking up two
+        assert_eq!(4, line("department,employee\n\"Sa\nles\",Amir\n,Leon\n"));
+        assert_eq!(3, line("department,employee\nSales,Amir\nSales,Amir\n"));
+        assert_eq!(2, line("department,employee\nSales,\"Amir\n"));
+    }
+
+    #[test]
+    fn json() {
+        let mut out = Vec::new();
+        write_json(&company(), &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.starts_with("{\n  \"Engineering\": [\n    \"Sally\"\n  ],"));
+        assert_eq!(company(), read_json(text.as_bytes()).unwrap());
+
+        let corrupt = |text: &str| {
+            matches!(
+                read_json(text.as_bytes()),
+                Err(StorageError::Corrupt { .. })
+            )
+        };
+        assert!(corrupt("[\"Sally\"]"));
+        assert!(corrupt("{\"Sales\": \"Amir\"}"));
+        assert!(corrupt("{\"Sales\": [\"Amir\", 7]}"));
+        assert!(corrupt("{\"Sales\": [\"Amir\", \"Amir\"]}"));
+        assert!(corrupt("{\"Sales\": [\"Amir\""));
+    }
+
+    #[test]
+    fn save_and_load_by_extension() {
+        for name in &["company.csv", "company.JSON"] {
+            let dir = TempDir::new(name);
+            let path = dir.path();
+            assert_eq!(Directory::new(), load(path).unwrap());
+            save(&company(), path).unwrap();
+            assert_eq!(company(), load(path).unwrap());
+        }
+        let dir = TempDir::new("company.json");
+        let path = dir.path();
+        save(&company(), path).unwrap();
+        assert!(fs::read_to_string(path).unwrap().starts_with('{'));
+    }
+
+    #[test]
+    fn every_command_survives_a_crash() {
+        let dir = TempDir::new("crash.csv");
+        let path = dir.path();
+        let mut store = Store::open(path).unwrap();
+        store.execute(&command("Add Sally to Engineering")).unwrap();
+        store.execute(&command("Add Leon to Sales")).unwrap();
+        store
+            .execute(&command("Move Leon from Sales to Engineering"))
+            .unwrap();
+        // a command that fails isn't journaled, so it isn't replayed either
+        assert!(store.execute(&command("Remove Amir from Sales")).is_err());
+        store.execute(&command("List all")).unwrap();
+        let expected = store.directory().clone();
+        // gone without being closed, like the process was killed
+        drop(store);
+        assert!(!path.exists());
+
+        let store = Store::open(path).unwrap();
+        assert_eq!(&expected, store.directory());
+        store.close().unwrap();
+        assert_eq!(expected, load(path).unwrap());
+        assert_eq!(
+            format!(
+                "{} {:016x}\n",
+                JOURNAL_HEADER,
+                fnv1a(&fs::read(path).unwrap())
+            ),
+            fs::read_to_string(sibling(path, ".journal")).unwrap()
+        );
+
+        let store = Store::open(path).unwrap();
+        assert_eq!(&expected, store.directory());
+    }
+
+    #[test]
+    fn names_with_line_breaks() {
+        let dir = TempDir::new("lines.csv");
+        let path = dir.path();
+        let mut store = Store::open(path).unwrap();
+        let add = Command::Add {
+            employee: String::from("Leon"),
+            department: String::from("Research\nand\r\nDevelopment"),
+        };
+        store.execute(&add).unwrap();
+        drop(store);
+
+        let store = Store::open(path).unwrap();
+        assert!(store
+            .directory()
+            .works_in("Leon", "Research\nand\r\nDevelopment"));
+    }
+
+    #[test]
+    fn a_line_cut_off_is_dropped() {
+        let dir = TempDir::new("torn.json");
+        let path = dir.path();
+        let mut store = Store::open(path).unwrap();
+        store.execute(&command("Add Sally to Engineering")).unwrap();
+        drop(store);
+
+        let journal = sibling(path, ".journal");
+        let mut file = OpenOptions::new().append(true).open(&journal).unwrap();
+        file.write_all(b"Add Leon to Sa").unwrap();
+        drop(file);
+
+        let mut store = Store::open(path).unwrap();
+        assert!(!store.directory().works_in("Leon", "Sales"));
+        // and the next command goes where it was, not after it
+        store.execute(&command("Add Amir to Sales")).unwrap();
+        drop(store);
+        let store = Store::open(path).unwrap();
+        assert_eq!(vec!["Amir", "Sally"], store.directory().everyone());
+    }
+
+    #[test]
+    fn a_journal_already_in_the_snapshot_is_skipped() {
+        let dir = TempDir::new("checkpoint.csv");
+        let path = dir.path();
+        let mut store = Store::open(path).unwrap();
+        store.execute(&command("Add Sally to Engineering")).unwrap();
+        let mut journal = fs::read_to_string(sibling(path, ".journal")).unwrap();
+        store.checkpoint().unwrap();
+        drop(store);
+
+        // as if the crash came after the new snapshot was in place but before the journal
+        // was started again
+        let hash = fnv1a(&fs::read(path).unwrap());
+        journal.push_str(&format!("{} {:016x}\n", CHECKPOINT_MARK, hash));
+        fs::write(sibling(path, ".journal"), &journal).unwrap();
+        let mut store = Store::open(path).unwrap();
+        assert_eq!(vec!["Sally"], store.directory().everyone());
+        store.execute(&command("Add Leon to Sales")).unwrap();
+        drop(store);
+
+        // and as if it came before the new snapshot was in place, where the checkpoint line
+        // is left in the journal and the commands after it still count
+        let journal = fs::read_to_string(sibling(path, ".journal")).unwrap();
+        let (header, commands) = journal.split_once('\n').unwrap();
+        fs::write(
+            sibling(path, ".journal"),
+            format!("{}\n{} {:016x}\n{}", header, CHECKPOINT_MARK, 1, commands),
+        )
+        .unwrap();
+        let store = Store::open(path).unwrap();
+        assert_eq!(vec!["Leon", "Sally"], store.directory().everyone());
+    }
+
+    #[test]
+    fn a_journal_for_another_snapshot() {
+        let dir = TempDir::new("other.csv");
+        let path = dir.path();
+        let mut store = Store::open(path).unwrap();
+        store.execute(&command("Add Sally to Engineering")).unwrap();
+        drop(store);
+
+        // the snapshot changed without the journal being folded into it
+        save(&company(), path).unwrap();
+        match Store::open(path) {
+            Err(StorageError::Journal { line: 1, .. }) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+
+        // but with nothing in the journal, there's nothing to lose
+        let journal = fs::read_to_string(sibling(path, ".journal")).unwrap();
+        let header = journal.lines().next().unwrap();
+        fs::write(sibling(path, ".journal"), format!("{}\n", header)).unwrap();
+        let store = Store::open(path).unwrap();
+        assert_eq!(&company(), store.directory());
+
+        drop(store);
+        fs::write(sibling(path, ".journal"), "Add Leon to Sales\n").unwrap();
+        assert!(Store::open(path).is_err());
+    }
+
+    #[test]
+    fn corrupt_journal() {
+        let dir = TempDir::new("corrupt.csv");
+        let path = dir.path();
+        drop(Store::open(path).unwrap());
+        let journal = sibling(path, ".journal");
+        let mut contents = fs::read_to_string(&journal).unwrap();
+        contents.push_str("Add Sally to Engineering\nHire Leon\n");
+        fs::write(&journal, contents).unwrap();
+        match Store::open(path) {
+            Err(StorageError::Journal { line: 3, .. }) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn checkpoints_as_it_goes() {
+        let dir = TempDir::new("busy.csv");
+        let path = dir.path();
+        let mut store = Store::open(path).unwrap();
+        for i in 0..CHECKPOINT_EVERY + 1 {
+            store
+                .execute(&command(&format!("Add Employee{} to Sales", i)))
+                .unwrap();
+        }
+        assert_eq!(CHECKPOINT_EVERY, load(path).unwrap().everyone().len());
+        assert_eq!(1, store.entries);
+    }
+
+    #[test]
+    fn a_failed_write_is_never_replayed() {
+        let dir = TempDir::new("failing.csv");
+        let path = dir.path();
+        let mut store = Store::open(path).unwrap();
+        store.execute(&command("Add Sally to Engineering")).unwrap();
+
+        // a journal that can't be written to, and can't be cut back either
+        store.journal = File::open(sibling(path, ".journal")).unwrap();
+        store.journal.seek(SeekFrom::End(0)).unwrap();
+        assert!(store.execute(&command("Add Leon to Sales")).is_err());
+        assert!(!store.directory().works_in("Leon", "Sales"));
+        match store.execute(&command("Add Amir to Sales")) {
+            Err(StorageError::Unusable) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+        drop(store);
+
+        let store = Store::open(path).unwrap();
+        assert_eq!(vec!["Sally"], store.directory().everyone());
+    }
+
+    #[test]
+    fn a_failed_checkpoint_still_carries_out_the_command() {
+        let dir = TempDir::new("stuck.csv");
+        let path = dir.path();
+        let mut store = Store::open(path).unwrap();
+        for i in 0..CHECKPOINT_EVERY - 1 {
+            store
+                .execute(&command(&format!("Add Employee{} to Sales", i)))
+                .unwrap();
+        }
+
+        // a directory in the way of the file the new snapshot is written to first
+        let tmp = sibling(path, &format!(".tmp{}", std::process::id()));
+        fs::create_dir(&tmp).unwrap();
+        let mut output = Vec::new();
+        crate::command::repl(&mut store, "Add Leon to Sales\n".as_bytes(), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(
+            output.contains("Added Leon to Sales.\nWarning: couldn't save"),
+            "{}",
+            output
+        );
+        assert!(store.directory().works_in("Leon", "Sales"));
+        assert!(!path.exists());
+
+        // and it's tried again with the next command
+        fs::remove_dir(&tmp).unwrap();
+        store.execute(&command("Add Amir to Sales")).unwrap();
+        assert!(store.take_checkpoint_error().is_none());
+        assert_eq!(0, store.entries);
+        assert_eq!(CHECKPOINT_EVERY + 1, load(path).unwrap().everyone().len());
+        drop(store);
+        let store = Store::open(path).unwrap();
+        assert_eq!(CHECKPOINT_EVERY + 1, store.directory().everyone().len());
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn a_journal_that_cant_be_started_again() {
+        use std::os::unix::io::FromRawFd;
+
+        let dir = TempDir::new("restart.csv");
+        let path = dir.path();
+        let mut store = Store::open(path).unwrap();
+        store.execute(&command("Add Sally to Engineering")).unwrap();
+
+        // the same journal in a file with room for the checkpoint line, that can be cut
+        // back but not grow past that, so the header after `set_len(0)` can't be written
+        let journal = fs::read(sibling(path, ".journal")).unwrap();
+        let mut sealed = unsafe {
+            let fd = libc::memfd_create(b"journal\0".as_ptr().cast(), libc::MFD_ALLOW_SEALING);
+            assert!(fd >= 0);
+            File::from_raw_fd(fd)
+        };
+        sealed.write_all(&journal).unwrap();
+        sealed.set_len(4096).unwrap();
+        sealed.seek(SeekFrom::Start(journal.len() as u64)).unwrap();
+        let fd = std::os::unix::io::AsRawFd::as_raw_fd(&sealed);
+        assert_eq!(0, unsafe {
+            libc::fcntl(fd, libc::F_ADD_SEALS, libc::F_SEAL_GROW)
+        });
+        store.journal = sealed;
+
+        assert!(store.checkpoint().is_err());
+        assert_eq!(vec!["Sally"], load(path).unwrap().everyone());
+        // not added after what's left of the old journal
+        assert!(store.execute(&command("Add Leon to Sales")).is_err());
+        assert!(!store.directory().works_in("Leon", "Sales"));
+
+        // once the journal can be written again, it's started again first
+        store.journal = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(sibling(path, ".journal"))
+            .unwrap();
+        store.journal.seek(SeekFrom::End(0)).unwrap();
+        store.execute(&command("Add Amir to Sales")).unwrap();
+        drop(store);
+        let store = Store::open(path).unwrap();
+        assert_eq!(vec!["Amir", "Sally"], store.directory().everyone());
+    }
+
+    #[test]
+    fn one_process_at_a_time() {
+        let dir = TempDir::new("locked.csv");
+        let path = dir.path();
+        let store = Store::open(path).unwrap();
+        match Store::open(path) {
+            Err(StorageError::Locked(locked)) => assert_eq!(path, locked),
+            other => panic!("unexpected {:?}", other),
+        }
+        store.close().unwrap();
+        Store::open(path).unwrap();
+    }
+}
@@ -6,29 +6,30 @@
 // is a growable, mutable, owned, UTF-8 encoded string type.
 // Rust std library also includes a number of other string types, such as OsString, OsStr, CString, CStr
 
-use unicode_segmentation;
 use unicode_segmentation::UnicodeSegmentation;
 
 pub fn main() {
-    let mut s = String::new();
+    // an empty string to load data into later
+    #[allow(unused_mut)]
+    let mut _s = String::new();
 
     let data = "initial contents";
-    let s = data.to_string();
-    let s = "initial contents".to_string();
-    let s = String::from("initial contents");
+    let _s = data.to_string();
+    let _s = "initial contents".to_string();
+    let _s = String::from("initial contents");
 
     // String are UTF-8 encoded, we can include any properly encoded data in them
-    let hello = String::from("السلام عليكم");
-    let hello = String::from("Dobrý den");
-    let hello = String::from("Hello");
-    let hello = String::from("שָׁלוֹם");
-    let hello = String::from("नमस्ते");
-    let hello = String::from("こんにちは");
-    let hello = String::from("안녕하세요");
-    let hello = String::from("你好");
-    let hello = String::from("Olá");
-    let hello = String::from("Здравствуйте");
-    let hello = String::from("Hola");
+    let _hello = String::from("السلام عليكم");
+    let _hello = String::from("Dobrý den");
+    let _hello = String::from("Hello");
+    let _hello = String::from("שָׁלוֹם");
+    let _hello = String::from("नमस्ते");
+    let _hello = String::from("こんにちは");
+    let _hello = String::from("안녕하세요");
+    let _hello = String::from("你好");
+    let _hello = String::from("Olá");
+    let _hello = String::from("Здравствуйте");
+    let _hello = String::from("Hola");
 
     let mut s = String::from("foo");
     s += "bar";
@@ -52,7 +53,7 @@ pub fn main() {
     println!("{}", s3);
     println!("{}{}", s1, s2);
 
-    let s1 = String::from("hello");
+    let _s1 = String::from("hello");
     // let h = s1[0]; // strings don't support indexing, because it's UTF-8 encode,
     // which one character may take 1-4 bytes and strings are collections of bytes,
     // so index one byte may make not sense if it don't point to the byte that exactly can represent one character
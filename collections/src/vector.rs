@@ -1,19 +1,25 @@
 pub fn main() {
     // an important point is that vectors are implemented using generics
-    let v: Vec<i32> = Vec::new();
+    let _v: Vec<i32> = Vec::new();
 
     let mut v = vec![1, 2, 3];
     v.push(4);
     v.push(5);
 
-    let mut v = Vec::new();
-    v.push(1);
-    v.push(2);
+    // pushing to a new vector lets Rust infer its type
+    #[allow(clippy::vec_init_then_push)]
+    {
+        let mut v = Vec::new();
+        v.push(1);
+        v.push(2);
+    }
 
     {
-        let v = vec![1, 2, 3, 4];
+        #[allow(clippy::useless_vec)]
+        let _v = vec![1, 2, 3, 4];
     } // dropping a vector drops its elements
 
+    #[allow(clippy::useless_vec)]
     let v = vec![1, 2, 3, 4, 5];
     // we use & here as we just want to refer and not need to care about take owner
     // if index is outbound here program will crash
@@ -38,6 +44,8 @@ pub fn main() {
     }
     println!("{:?}", v);
 
+    // only put in the vector here, never read back out
+    #[allow(dead_code)]
     enum SpreadsheetCell {
         Int(i32),
         Float(f64),
@@ -46,7 +54,8 @@ pub fn main() {
     // use enum to store multiple types in vector
     // Rust needs to know what types will be in the vector at compile time
     // so it knows exactly how much memory on the heap will be needed to store each element
-    let row = vec![
+    #[allow(clippy::useless_vec)]
+    let _row = vec![
         SpreadsheetCell::Int(3),
         SpreadsheetCell::Float(10.12),
         SpreadsheetCell::Text(String::from("blue")),
@@ -0,0 +1,226 @@
+use crate::config::{number, value, ConfigError, ConfigFlags, GameConfig};
+use crate::i18n::Locale;
+use crate::multiplayer::Rules;
+use crate::scores;
+use crate::variants::VariantKind;
+use std::path::PathBuf;
+
+pub const USAGE: &str = "\
+Usage: guessing_game [--difficulty easy|normal|hard] [--min N] [--max N] [--attempts N]
+                     [--variant classic|hot-cold|hints|reverse]
+                     [--name NAME] [--scores-file PATH] [--lang en|zh]
+       guessing_game --scores [--scores-file PATH]
+       guessing_game --solve [game flags] [--seed N]
+       guessing_game --bench GAMES [game flags] [--seed N]
+       guessing_game --host ADDRESS [--turns] [game flags] [--seed N]";
+
+#[derive(Debug, PartialEq)]
+pub enum Mode {
+    Play,
+    /// Show the leaderboard instead of playing.
+    Scores,
+    /// Watch the solver play one game.
+    Solve,
+    /// Let the solver play this many games and show how many attempts they took.
+    Bench {
+        games: u32,
+    },
+    /// Host a game for players connecting over TCP, e.g. on `127.0.0.1:7878`.
+    Host {
+        address: String,
+    },
+}
+
+/// Everything the command line can ask for.
+#[derive(Debug, PartialEq)]
+pub struct Options {
+    pub mode: Mode,
+    pub config: GameConfig,
+    pub player: String,
+    pub scores_file: PathBuf,
+    /// Draws the secrets from a fixed seed instead of a random one.
+    pub seed: Option<u64>,
+    /// How hosted games are played.
+    pub rules: Rules,
+    /// Which game `Mode::Play` plays.
+    pub variant: VariantKind,
+    /// The language to talk in, from `--lang` or else the environment.
+    pub locale: Locale,
+}
+
+impl Options {
+    pub fn from_args(mut args: impl Iterator<Item = String>) -> Result<Options, ConfigError> {
+        args.next();
+
+        let mut mode = Mode::Play;
+        let mut flags = ConfigFlags::default();
+        let mut player = None;
+        let mut scores_file = None;
+        let mut seed = None;
+        let mut rules = Rules::Race;
+        let mut variant = VariantKind::Classic;
+        let mut locale = None;
+        while let Some(flag) = args.next() {
+            if flags.parse(&flag, &mut args)? {
+                continue;
+            }
+            match flag.as_str() {
+                "--scores" => mode = Mode::Scores,
+                "--name" => player = Some(value(&flag, args.next())?),
+                "--scores-file" => scores_file = Some(PathBuf::from(value(&flag, args.next())?)),
+                "--solve" => mode = Mode::Solve,
+                "--bench" => {
+                    mode = Mode::Bench {
+                        games: number(&flag, args.next())?,
+                    }
+                }
+                "--host" => {
+                    mode = Mode::Host {
+                        address: value(&flag, args.next())?,
+                    }
+                }
+                "--turns" => rules = Rules::Turns,
+                "--variant" => {
+                    let name = value(&flag, args.next())?;
+                    variant = VariantKind::parse(&name).ok_or(ConfigError::UnknownVariant(name))?;
+                }
+                "--lang" => {
+                    let tag = value(&flag, args.next())?;
+                    locale = Some(Locale::parse(&tag).ok_or(ConfigError::UnknownLocale(tag))?);
+                }
+                "--seed" => {
+                    let arg = value(&flag, args.next())?;
+                    let parsed = arg.parse().map_err(|_| ConfigError::InvalidValue {
+                        flag: flag.clone(),
+                        value: Some(arg),
+                    })?;
+                    seed = Some(parsed);
+                }
+                _ => return Err(ConfigError::UnknownFlag(flag)),
+            }
+        }
+
+        Ok(Options {
+            mode,
+            config: flags.build()?,
+            player: player.unwrap_or_else(default_player),
+            scores_file: scores_file.unwrap_or_else(scores::default_path),
+            seed,
+            rules,
+            variant,
+            locale: locale.unwrap_or_else(Locale::from_env),
+        })
+    }
+}
+
+fn default_player() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| String::from("anonymous"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Mode, Options};
+    use crate::config::{ConfigError, GameConfig};
+    use crate::i18n::Locale;
+    use crate::multiplayer::Rules;
+    use crate::variants::VariantKind;
+    use std::path::PathBuf;
+
+    fn args(args: &[&str]) -> impl Iterator<Item = String> {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        args.into_iter()
+    }
+
+    #[test]
+    fn game_and_score_flags_mix() {
+        let options = Options::from_args(args(&[
+            "guessing_game",
+            "--name",
+            "Ferris",
+            "--max",
+            "10",
+            "--scores-file",
+            "/tmp/scores",
+        ]))
+        .unwrap();
+
+        assert_eq!(Mode::Play, options.mode);
+        assert_eq!(GameConfig::new(1, 10, None).unwrap(), options.config);
+        assert_eq!("Ferris", options.player);
+        assert_eq!(PathBuf::from("/tmp/scores"), options.scores_file);
+
+        let options = Options::from_args(args(&["guessing_game", "--scores"])).unwrap();
+        assert_eq!(Mode::Scores, options.mode);
+        assert_eq!(GameConfig::default(), options.config);
+    }
+
+    #[test]
+    fn solver_modes() {
+        let options = Options::from_args(args(&["guessing_game", "--solve"])).unwrap();
+        assert_eq!(Mode::Solve, options.mode);
+        assert_eq!(None, options.seed);
+
+        let options = Options::from_args(args(&[
+            "guessing_game",
+            "--bench",
+            "5000",
+            "--difficulty",
+            "hard",
+            "--seed",
+            "18446744073709551615",
+        ]))
+        .unwrap();
+        assert_eq!(Mode::Bench { games: 5000 }, options.mode);
+        assert_eq!(Some(u64::MAX), options.seed);
+
+        assert!(Options::from_args(args(&["guessing_game", "--bench"])).is_err());
+        assert!(Options::from_args(args(&["guessing_game", "--seed", "x"])).is_err());
+    }
+
+    #[test]
+    fn hosting() {
+        let options = Options::from_args(args(&[
+            "guessing_game",
+            "--turns",
+            "--host",
+            "127.0.0.1:7878",
+        ]))
+        .unwrap();
+        assert_eq!(
+            Mode::Host {
+                address: String::from("127.0.0.1:7878")
+            },
+            options.mode
+        );
+        assert_eq!(Rules::Turns, options.rules);
+
+        let options = Options::from_args(args(&["guessing_game"])).unwrap();
+        assert_eq!(Rules::Race, options.rules);
+        assert_eq!(VariantKind::Classic, options.variant);
+        assert!(Options::from_args(args(&["guessing_game", "--host"])).is_err());
+    }
+
+    #[test]
+    fn bad_flags() {
+        assert_eq!(
+            Err(ConfigError::UnknownFlag(String::from("--score"))),
+            Options::from_args(args(&["guessing_game", "--score"]))
+        );
+        assert!(Options::from_args(args(&["guessing_game", "--name"])).is_err());
+        assert_eq!(
+            Err(ConfigError::UnknownVariant(String::from("warmer"))),
+            Options::from_args(args(&["guessing_game", "--variant", "warmer"]))
+        );
+        let options =
+            Options::from_args(args(&["guessing_game", "--variant", "hot-cold"])).unwrap();
+        assert_eq!(VariantKind::HotCold, options.variant);
+        assert_eq!(
+            Err(ConfigError::UnknownLocale(String::from("fr"))),
+            Options::from_args(args(&["guessing_game", "--lang", "fr"]))
+        );
+        let options = Options::from_args(args(&["guessing_game", "--lang", "zh_CN"])).unwrap();
+        assert_eq!(Locale::Zh, options.locale);
+    }
+}
@@ -0,0 +1,293 @@
+use std::error::Error;
+use std::fmt;
+
+/// Preset ranges and attempt limits.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Difficulty {
+    /// 1 to 50, as many attempts as you like.
+    Easy,
+    /// 1 to 100, as many attempts as you like: the classic game.
+    Normal,
+    /// 1 to 1000 in 10 attempts, exactly enough if every guess halves the range.
+    Hard,
+}
+
+impl Difficulty {
+    pub fn parse(name: &str) -> Option<Difficulty> {
+        match name.to_lowercase().as_str() {
+            "easy" => Some(Difficulty::Easy),
+            "normal" => Some(Difficulty::Normal),
+            "hard" => Some(Difficulty::Hard),
+            _ => None,
+        }
+    }
+}
+
+/// The range the secret number is drawn from and how many guesses the player gets.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameConfig {
+    pub min: u32,
+    pub max: u32,
+    /// `None` lets the player keep guessing until they win.
+    pub max_attempts: Option<u32>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ConfigError {
+    /// `min` is greater than `max`.
+    EmptyRange {
+        min: u32,
+        max: u32,
+    },
+    ZeroAttempts,
+    UnknownDifficulty(String),
+    UnknownVariant(String),
+    UnknownLocale(String),
+    /// A flag that needs a value didn't get one that parses.
+    InvalidValue {
+        flag: String,
+        value: Option<String>,
+    },
+    UnknownFlag(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::EmptyRange { min, max } => {
+                write!(f, "the minimum {} is greater than the maximum {}", min, max)
+            }
+            ConfigError::ZeroAttempts => write!(f, "the player needs at least one attempt"),
+            ConfigError::UnknownDifficulty(name) => write!(
+                f,
+                "unknown difficulty `{}`, expected easy, normal or hard",
+                name
+            ),
+            ConfigError::UnknownVariant(name) => write!(
+                f,
+                "unknown variant `{}`, expected classic, hot-cold, hints or reverse",
+                name
+            ),
+            ConfigError::UnknownLocale(name) => {
+                write!(f, "unknown language `{}`, expected en or zh", name)
+            }
+            ConfigError::InvalidValue { flag, value: None } => {
+                write!(f, "{} needs a value", flag)
+            }
+            ConfigError::InvalidValue {
+                flag,
+                value: Some(value),
+            } => write!(f, "invalid value `{}` for {}", value, flag),
+            ConfigError::UnknownFlag(flag) => write!(f, "unknown flag `{}`", flag),
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+impl Default for GameConfig {
+    fn default() -> GameConfig {
+        GameConfig::from_difficulty(Difficulty::Normal)
+    }
+}
+
+impl GameConfig {
+    pub fn new(min: u32, max: u32, max_attempts: Option<u32>) -> Result<GameConfig, ConfigError> {
+        if min > max {
+            return Err(ConfigError::EmptyRange { min, max });
+        }
+        if max_attempts == Some(0) {
+            return Err(ConfigError::ZeroAttempts);
+        }
+
+        Ok(GameConfig {
+            min,
+            max,
+            max_attempts,
+        })
+    }
+
+    pub fn from_difficulty(difficulty: Difficulty) -> GameConfig {
+        match difficulty {
+            Difficulty::Easy => GameConfig {
+                min: 1,
+                max: 50,
+                max_attempts: None,
+            },
+            Difficulty::Normal => GameConfig {
+                min: 1,
+                max: 100,
+                max_attempts: None,
+            },
+            Difficulty::Hard => GameConfig {
+                min: 1,
+                max: 1000,
+                max_attempts: Some(10),
+            },
+        }
+    }
+
+    /// Builds a config from command line flags, skipping the program name:
+    ///
+    /// `--difficulty easy|normal|hard` picks a preset, and `--min N`, `--max N` and
+    /// `--attempts N` override parts of it.
+    pub fn from_args(mut args: impl Iterator<Item = String>) -> Result<GameConfig, ConfigError> {
+        args.next();
+
+        let mut flags = ConfigFlags::default();
+        while let Some(flag) = args.next() {
+            if !flags.parse(&flag, &mut args)? {
+                return Err(ConfigError::UnknownFlag(flag));
+            }
+        }
+        flags.build()
+    }
+
+    pub fn contains(&self, guess: u32) -> bool {
+        (self.min..=self.max).contains(&guess)
+    }
+}
+
+/// The game config flags, collected out of a command line that may have other flags too.
+#[derive(Debug, Default)]
+pub struct ConfigFlags {
+    difficulty: Option<Difficulty>,
+    min: Option<u32>,
+    max: Option<u32>,
+    attempts: Option<u32>,
+}
+
+impl ConfigFlags {
+    /// Takes `flag`, and its value from `args`, if it's a config flag.
+    /// Returns whether it was one.
+    pub fn parse(
+        &mut self,
+        flag: &str,
+        args: &mut impl Iterator<Item = String>,
+    ) -> Result<bool, ConfigError> {
+        match flag {
+            "--difficulty" => {
+                let name = value(flag, args.next())?;
+                let difficulty =
+                    Difficulty::parse(&name).ok_or(ConfigError::UnknownDifficulty(name))?;
+                self.difficulty = Some(difficulty);
+            }
+            "--min" => self.min = Some(number(flag, args.next())?),
+            "--max" => self.max = Some(number(flag, args.next())?),
+            "--attempts" => self.attempts = Some(number(flag, args.next())?),
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    pub fn build(self) -> Result<GameConfig, ConfigError> {
+        let preset = GameConfig::from_difficulty(self.difficulty.unwrap_or(Difficulty::Normal));
+        GameConfig::new(
+            self.min.unwrap_or(preset.min),
+            self.max.unwrap_or(preset.max),
+            self.attempts.or(preset.max_attempts),
+        )
+    }
+}
+
+pub(crate) fn value(flag: &str, value: Option<String>) -> Result<String, ConfigError> {
+    value.ok_or_else(|| ConfigError::InvalidValue {
+        flag: String::from(flag),
+        value: None,
+    })
+}
+
+pub(crate) fn number(flag: &str, arg: Option<String>) -> Result<u32, ConfigError> {
+    let arg = value(flag, arg)?;
+    arg.parse().map_err(|_| ConfigError::InvalidValue {
+        flag: String::from(flag),
+        value: Some(arg),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ConfigError, Difficulty, GameConfig};
+
+    fn args(args: &[&str]) -> impl Iterator<Item = String> {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        args.into_iter()
+    }
+
+    #[test]
+    fn defaults_to_the_classic_game() {
+        assert_eq!(
+            GameConfig::new(1, 100, None).unwrap(),
+            GameConfig::from_args(args(&["guessing_game"])).unwrap()
+        );
+        assert_eq!(
+            GameConfig::default(),
+            GameConfig::new(1, 100, None).unwrap()
+        );
+    }
+
+    #[test]
+    fn presets_and_overrides() {
+        let config = GameConfig::from_args(args(&["guessing_game", "--difficulty", "hard"]));
+        assert_eq!(
+            GameConfig::from_difficulty(Difficulty::Hard),
+            config.unwrap()
+        );
+
+        let config = GameConfig::from_args(args(&[
+            "guessing_game",
+            "--difficulty",
+            "Easy",
+            "--max",
+            "20",
+            "--attempts",
+            "3",
+        ]));
+        assert_eq!(GameConfig::new(1, 20, Some(3)).unwrap(), config.unwrap());
+
+        let config = GameConfig::from_args(args(&["guessing_game", "--min", "10", "--max", "10"]));
+        assert_eq!(GameConfig::new(10, 10, None).unwrap(), config.unwrap());
+    }
+
+    #[test]
+    fn invalid_configs() {
+        assert_eq!(
+            Err(ConfigError::EmptyRange { min: 101, max: 100 }),
+            GameConfig::from_args(args(&["guessing_game", "--min", "101"]))
+        );
+        assert_eq!(
+            Err(ConfigError::ZeroAttempts),
+            GameConfig::new(1, 2, Some(0))
+        );
+        assert_eq!(
+            Err(ConfigError::UnknownDifficulty(String::from("insane"))),
+            GameConfig::from_args(args(&["guessing_game", "--difficulty", "insane"]))
+        );
+        assert_eq!(
+            Err(ConfigError::InvalidValue {
+                flag: String::from("--max"),
+                value: Some(String::from("lots")),
+            }),
+            GameConfig::from_args(args(&["guessing_game", "--max", "lots"]))
+        );
+        assert_eq!(
+            "--attempts needs a value",
+            GameConfig::from_args(args(&["guessing_game", "--attempts"]))
+                .unwrap_err()
+                .to_string()
+        );
+        assert_eq!(
+            Err(ConfigError::UnknownFlag(String::from("--cheat"))),
+            GameConfig::from_args(args(&["guessing_game", "--cheat"]))
+        );
+    }
+
+    #[test]
+    fn contains_is_inclusive() {
+        let config = GameConfig::new(5, 7, None).unwrap();
+        assert!(!config.contains(4));
+        assert!(config.contains(5));
+        assert!(config.contains(7));
+        assert!(!config.contains(8));
+    }
+}
@@ -0,0 +1,410 @@
+use crate::config::GameConfig;
+use crate::i18n::Locale;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cmp::Ordering;
+use std::io::{self, BufRead, Write};
+
+/// Picks secret numbers.
+///
+/// Every `rand::Rng` is one, so games can use `rand::thread_rng()` or be replayed
+/// from a seed with [`seeded`].
+pub trait SecretRng {
+    /// A number in `min..=max`.
+    fn secret(&mut self, min: u32, max: u32) -> u32;
+}
+
+impl<R: Rng> SecretRng for R {
+    fn secret(&mut self, min: u32, max: u32) -> u32 {
+        // the upper bound of gen_range is exclusive,
+        // widened to u64 so that a max of u32::MAX doesn't overflow
+        self.gen_range(min as u64, max as u64 + 1) as u32
+    }
+}
+
+/// A generator that always produces the same secrets for the same seed.
+pub fn seeded(seed: u64) -> StdRng {
+    StdRng::seed_from_u64(seed)
+}
+
+/// What a single guess told the player.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome {
+    /// Outside the configured range, it doesn't count as an attempt.
+    OutOfRange,
+    TooSmall,
+    TooBig,
+    Correct,
+    /// The game had already ended, the guess was ignored.
+    GameOver,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum State {
+    Playing,
+    Won,
+    /// Every attempt was used without finding the secret.
+    Lost,
+}
+
+/// One round of the game: a secret number and the guesses made so far.
+pub struct Game {
+    config: GameConfig,
+    secret: u32,
+    attempts: u32,
+    state: State,
+}
+
+impl Game {
+    pub fn new<R: SecretRng + ?Sized>(config: GameConfig, rng: &mut R) -> Game {
+        let secret = rng.secret(config.min, config.max);
+        Game::with_secret(config, secret)
+    }
+
+    /// # Panics
+    ///
+    /// If `secret` is outside the configured range.
+    pub fn with_secret(config: GameConfig, secret: u32) -> Game {
+        assert!(
+            config.contains(secret),
+            "secret {} is outside {}..={}",
+            secret,
+            config.min,
+            config.max
+        );
+
+        Game {
+            config,
+            secret,
+            attempts: 0,
+            state: State::Playing,
+        }
+    }
+
+    pub fn guess(&mut self, guess: u32) -> Outcome {
+        if self.state != State::Playing {
+            return Outcome::GameOver;
+        }
+        if !self.config.contains(guess) {
+            return Outcome::OutOfRange;
+        }
+
+        self.attempts += 1;
+        let outcome = match guess.cmp(&self.secret) {
+            Ordering::Less => Outcome::TooSmall,
+            Ordering::Greater => Outcome::TooBig,
+            Ordering::Equal => Outcome::Correct,
+        };
+
+        if outcome == Outcome::Correct {
+            self.state = State::Won;
+        } else if Some(self.attempts) == self.config.max_attempts {
+            self.state = State::Lost;
+        }
+        outcome
+    }
+
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+
+    pub fn secret(&self) -> u32 {
+        self.secret
+    }
+
+    /// Guesses made so far, not counting the ones out of range.
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    pub fn state(&self) -> State {
+        self.state
+    }
+}
+
+/// A way of playing on a text interface, one line of input at a time,
+/// in the language of `locale`.
+///
+/// The classic game is a [`Game`] itself, the others are in [`crate::variants`].
+pub trait Variant {
+    /// Explains the game before the first prompt.
+    fn intro(&self, locale: Locale, output: &mut dyn Write) -> io::Result<()>;
+
+    /// Asks for the next line of input.
+    fn prompt(&self, locale: Locale, output: &mut dyn Write) -> io::Result<()>;
+
+    /// Answers one line of input, already trimmed.
+    fn respond(&mut self, line: &str, locale: Locale, output: &mut dyn Write) -> io::Result<()>;
+
+    fn state(&self) -> State;
+}
+
+/// Plays `variant` until it ends or `input` runs out, and returns the state it was left in.
+pub fn run<V: Variant + ?Sized, R: BufRead, W: Write>(
+    variant: &mut V,
+    locale: Locale,
+    mut input: R,
+    mut output: W,
+) -> io::Result<State> {
+    variant.intro(locale, &mut output)?;
+
+    while variant.state() == State::Playing {
+        variant.prompt(locale, &mut output)?;
+
+        let mut line = String::new();
+
+        // read_line returns how many bytes it read, 0 means the input is closed
+        // and nobody is left to play
+        if input.read_line(&mut line)? == 0 {
+            break;
+        }
+
+        // "5\n".trim() = 5, trim eliminates \n
+        variant.respond(line.trim(), locale, &mut output)?;
+    }
+
+    Ok(variant.state())
+}
+
+/// Plays the classic game on a text interface until it ends or `input` runs out,
+/// and returns the state it was left in.
+pub fn play<R: BufRead, W: Write>(
+    game: &mut Game,
+    locale: Locale,
+    input: R,
+    output: W,
+) -> io::Result<State> {
+    run(game, locale, input, output)
+}
+
+/// Makes the guess on `line` and returns it with how it went, unless it wasn't a guess
+/// that counts, which has already been explained to the player.
+pub(crate) fn guess_line(
+    game: &mut Game,
+    line: &str,
+    locale: Locale,
+    output: &mut dyn Write,
+) -> io::Result<Option<(u32, Outcome)>> {
+    // parse method on strings parses a string into some kind of number
+    let guess: u32 = match line.parse() {
+        Ok(num) => num,
+        Err(_) => {
+            writeln!(output, "{}", locale.text("not-a-number"))?;
+            return Ok(None);
+        }
+    };
+
+    match game.guess(guess) {
+        Outcome::OutOfRange => {
+            let config = game.config();
+            let range = [("min", &config.min as _), ("max", &config.max as _)];
+            writeln!(output, "{}", locale.format("out-of-range", &range))?;
+            Ok(None)
+        }
+        Outcome::GameOver => Ok(None),
+        outcome => {
+            writeln!(
+                output,
+                "{}",
+                locale.format("you-guessed", &[("guess", &guess)])
+            )?;
+            Ok(Some((guess, outcome)))
+        }
+    }
+}
+
+/// Tells the player they've lost, if they have.
+pub(crate) fn report_loss(game: &Game, locale: Locale, output: &mut dyn Write) -> io::Result<()> {
+    if game.state() == State::Lost {
+        let secret = game.secret();
+        writeln!(
+            output,
+            "{}",
+            locale.format("you-lose", &[("secret", &secret)])
+        )?;
+    }
+    Ok(())
+}
+
+impl Variant for Game {
+    fn intro(&self, locale: Locale, output: &mut dyn Write) -> io::Result<()> {
+        writeln!(output, "{}", locale.text("guess-the-number"))?;
+        let range = [
+            ("min", &self.config.min as _),
+            ("max", &self.config.max as _),
+        ];
+        writeln!(output, "{}", locale.format("range", &range))?;
+        if let Some(max_attempts) = self.config.max_attempts {
+            let attempts = [("attempts", &max_attempts as _)];
+            writeln!(output, "{}", locale.format("attempts-allowed", &attempts))?;
+        }
+        Ok(())
+    }
+
+    fn prompt(&self, locale: Locale, output: &mut dyn Write) -> io::Result<()> {
+        writeln!(output, "{}", locale.text("input-guess"))
+    }
+
+    fn respond(&mut self, line: &str, locale: Locale, output: &mut dyn Write) -> io::Result<()> {
+        let key = match guess_line(self, line, locale, output)? {
+            Some((_, Outcome::TooSmall)) => "too-small",
+            Some((_, Outcome::TooBig)) => "too-big",
+            Some(_) => "you-win",
+            None => return Ok(()),
+        };
+        writeln!(output, "{}", locale.text(key))?;
+        report_loss(self, locale, output)
+    }
+
+    fn state(&self) -> State {
+        self.state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{play, seeded, Game, Outcome, State};
+    use crate::config::GameConfig;
+    use crate::i18n::Locale;
+    use std::io::Cursor;
+
+    fn session(game: &mut Game, input: &str) -> (State, String) {
+        session_in(Locale::En, game, input)
+    }
+
+    fn session_in(locale: Locale, game: &mut Game, input: &str) -> (State, String) {
+        let mut output = Vec::new();
+        let state = play(game, locale, Cursor::new(input), &mut output).unwrap();
+        (state, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn seeded_games_are_reproducible() {
+        let config = GameConfig::new(1, 1_000_000, None).unwrap();
+        let a = Game::new(config.clone(), &mut seeded(42));
+        let b = Game::new(config.clone(), &mut seeded(42));
+        assert_eq!(a.secret(), b.secret());
+        assert!(config.contains(a.secret()));
+    }
+
+    #[test]
+    fn secrets_stay_in_range() {
+        let mut rng = seeded(7);
+        let config = GameConfig::new(3, 5, None).unwrap();
+        for _ in 0..100 {
+            assert!(config.contains(Game::new(config.clone(), &mut rng).secret()));
+        }
+
+        let config = GameConfig::new(u32::MAX - 1, u32::MAX, None).unwrap();
+        assert!(Game::new(config.clone(), &mut rng).secret() >= u32::MAX - 1);
+    }
+
+    #[test]
+    fn guesses_narrow_down_to_a_win() {
+        let mut game = Game::with_secret(GameConfig::default(), 42);
+        assert_eq!(Outcome::OutOfRange, game.guess(0));
+        assert_eq!(Outcome::OutOfRange, game.guess(101));
+        assert_eq!(0, game.attempts());
+
+        assert_eq!(Outcome::TooSmall, game.guess(10));
+        assert_eq!(Outcome::TooBig, game.guess(50));
+        assert_eq!(Outcome::Correct, game.guess(42));
+        assert_eq!(3, game.attempts());
+        assert_eq!(State::Won, game.state());
+
+        assert_eq!(Outcome::GameOver, game.guess(42));
+        assert_eq!(3, game.attempts());
+    }
+
+    #[test]
+    fn running_out_of_attempts() {
+        let mut game = Game::with_secret(GameConfig::new(1, 10, Some(2)).unwrap(), 7);
+        assert_eq!(Outcome::TooSmall, game.guess(1));
+        assert_eq!(State::Playing, game.state());
+        assert_eq!(Outcome::TooBig, game.guess(9));
+        assert_eq!(State::Lost, game.state());
+        assert_eq!(Outcome::GameOver, game.guess(7));
+    }
+
+    #[test]
+    #[should_panic(expected = "secret 0 is outside 1..=100")]
+    fn secret_must_be_in_range() {
+        Game::with_secret(GameConfig::default(), 0);
+    }
+
+    #[test]
+    fn full_session_with_invalid_input() {
+        let mut game = Game::with_secret(GameConfig::default(), 42);
+        let (state, output) = session(&mut game, "abc\n\n-3\n500\n 50 \n42\nnever read\n");
+
+        assert_eq!(State::Won, state);
+        assert_eq!(2, game.attempts());
+        assert_eq!(
+            "\
+Guess the number!
+It's between 1 and 100.
+Please input your guess.
+Please type a number!
+Please input your guess.
+Please type a number!
+Please input your guess.
+Please type a number!
+Please input your guess.
+The secret number will be between 1 and 100.
+Please input your guess.
+You guessed: 50
+Too big!
+Please input your guess.
+You guessed: 42
+You win!
+",
+            output
+        );
+    }
+
+    #[test]
+    fn full_session_lost() {
+        let mut game = Game::with_secret(GameConfig::new(1, 10, Some(2)).unwrap(), 3);
+        let (state, output) = session(&mut game, "5\n4\n3\n");
+
+        assert_eq!(State::Lost, state);
+        assert!(
+            output.starts_with("Guess the number!\nIt's between 1 and 10.\nYou have 2 attempts.\n")
+        );
+        assert!(output.ends_with("Too big!\nYou lose! The secret number was 3.\n"));
+    }
+
+    #[test]
+    fn session_ends_with_the_input() {
+        let mut game = Game::with_secret(GameConfig::default(), 42);
+        let (state, output) = session(&mut game, "1\n");
+
+        assert_eq!(State::Playing, state);
+        assert!(output.ends_with("Too small!\nPlease input your guess.\n"));
+    }
+
+    #[test]
+    fn session_in_chinese() {
+        let mut game = Game::with_secret(GameConfig::new(1, 10, Some(2)).unwrap(), 3);
+        let (state, output) = session_in(Locale::Zh, &mut game, "x\n5\n4\n");
+
+        assert_eq!(State::Lost, state);
+        assert_eq!(
+            "\
+猜数字！
+数字在 1 到 10 之间。
+你有 2 次机会。
+请输入你的猜测。
+请输入一个数字！
+请输入你的猜测。
+你猜的是：5
+太大了！
+请输入你的猜测。
+你猜的是：4
+太大了！
+你输了！秘密数字是 3。
+",
+            output
+        );
+    }
+}
@@ -0,0 +1,173 @@
+// Every message the game shows comes from a catalog per language in `locales/<code>.txt`,
+// one `key = text` per line with `{name}` placeholders. The catalogs are compiled in so
+// there's nothing to find at runtime. A key a translation is missing falls back to English,
+// and `every_locale_has_every_key` fails the tests before that can ship.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+type Catalog = HashMap<&'static str, &'static str>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Locale {
+    En,
+    Zh,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::En, Locale::Zh];
+
+    /// Understands `--lang` values like `zh` as well as `LANG` values like `zh_CN.UTF-8`.
+    pub fn parse(tag: &str) -> Option<Locale> {
+        let language = tag.split(['_', '-', '.']).next().unwrap_or_default();
+        match language.to_lowercase().as_str() {
+            "en" => Some(Locale::En),
+            "zh" => Some(Locale::Zh),
+            _ => None,
+        }
+    }
+
+    /// The language from the first of `LC_ALL`, `LC_MESSAGES` and `LANG` that's set,
+    /// the same order other programs look in, or English if we don't speak it.
+    pub fn from_env() -> Locale {
+        for var in &["LC_ALL", "LC_MESSAGES", "LANG"] {
+            match std::env::var(var) {
+                Ok(tag) if !tag.is_empty() => return Locale::parse(&tag).unwrap_or(Locale::En),
+                _ => {}
+            }
+        }
+        Locale::En
+    }
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Zh => "zh",
+        }
+    }
+
+    fn catalog(self) -> &'static Catalog {
+        static EN: OnceLock<Catalog> = OnceLock::new();
+        static ZH: OnceLock<Catalog> = OnceLock::new();
+        match self {
+            Locale::En => EN.get_or_init(|| parse(include_str!("../locales/en.txt"))),
+            Locale::Zh => ZH.get_or_init(|| parse(include_str!("../locales/zh.txt"))),
+        }
+    }
+
+    /// The message for `key`, which must be in the English catalog.
+    pub fn text(self, key: &'static str) -> &'static str {
+        match self.catalog().get(key) {
+            Some(text) => text,
+            None if self != Locale::En => Locale::En.text(key),
+            None => {
+                debug_assert!(false, "no message for `{}`", key);
+                key
+            }
+        }
+    }
+
+    /// The message for `key` with its placeholders filled in from `args`.
+    pub fn format(self, key: &'static str, args: &[(&str, &dyn Display)]) -> String {
+        let mut message = String::new();
+        let mut rest = self.text(key);
+        while let Some(start) = rest.find('{') {
+            message.push_str(&rest[..start]);
+            rest = &rest[start..];
+
+            let end = match rest.find('}') {
+                Some(end) => end,
+                None => break,
+            };
+            match args.iter().find(|(name, _)| *name == &rest[1..end]) {
+                Some((_, value)) => message.push_str(&value.to_string()),
+                // leave anything that isn't ours alone
+                None => message.push_str(&rest[..=end]),
+            }
+            rest = &rest[end + 1..];
+        }
+        message.push_str(rest);
+        message
+    }
+}
+
+fn parse(source: &'static str) -> Catalog {
+    source
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, text)| (key.trim(), text.trim()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Locale;
+    use std::collections::BTreeSet;
+
+    fn placeholders(text: &str) -> BTreeSet<&str> {
+        text.split('{')
+            .skip(1)
+            .filter_map(|part| part.split_once('}'))
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    #[test]
+    fn every_locale_has_every_key() {
+        let english = Locale::En.catalog();
+        for locale in Locale::ALL.iter() {
+            let catalog = locale.catalog();
+            for (key, text) in english {
+                let translated = catalog
+                    .get(key)
+                    .unwrap_or_else(|| panic!("{} is missing `{}`", locale.code(), key));
+                assert_eq!(
+                    placeholders(text),
+                    placeholders(translated),
+                    "{} has other placeholders for `{}`",
+                    locale.code(),
+                    key
+                );
+            }
+            for key in catalog.keys() {
+                assert!(
+                    english.contains_key(key),
+                    "{} has `{}`, English doesn't",
+                    locale.code(),
+                    key
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn parses_tags() {
+        assert_eq!(Some(Locale::Zh), Locale::parse("zh_CN.UTF-8"));
+        assert_eq!(Some(Locale::Zh), Locale::parse("zh-TW"));
+        assert_eq!(Some(Locale::En), Locale::parse("EN"));
+        assert_eq!(Some(Locale::En), Locale::parse("en_GB"));
+        assert_eq!(None, Locale::parse("C.UTF-8"));
+        assert_eq!(None, Locale::parse(""));
+    }
+
+    #[test]
+    fn fills_placeholders() {
+        assert_eq!(
+            "It's between 1 and 100.",
+            Locale::En.format("range", &[("min", &1), ("max", &100)])
+        );
+        assert_eq!(
+            "数字在 1 到 100 之间。",
+            Locale::Zh.format("range", &[("max", &100), ("min", &1)])
+        );
+        // a placeholder without an argument stays as it is
+        assert_eq!(
+            "It's between {min} and 100.",
+            Locale::En.format("range", &[("max", &100)])
+        );
+        assert_eq!("Too small!", Locale::En.format("too-small", &[]));
+    }
+}
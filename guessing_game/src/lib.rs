@@ -0,0 +1,19 @@
+//! # Guessing Game
+//!
+//! Guess a secret number, with the range and attempt limit set by a [`GameConfig`].
+
+pub mod cli;
+pub mod config;
+pub mod game;
+pub mod i18n;
+pub mod multiplayer;
+pub mod scores;
+pub mod solver;
+pub mod variants;
+
+pub use cli::{Mode, Options};
+pub use config::{ConfigError, Difficulty, GameConfig};
+pub use game::{play, run, Game, Outcome, SecretRng, State, Variant};
+pub use i18n::Locale;
+pub use solver::Solver;
+pub use variants::VariantKind;
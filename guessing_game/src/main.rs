@@ -1,50 +1,113 @@
-use std::io;
-use rand::Rng;
-use std::cmp::Ordering;
+use guessing_game::game::{seeded, SecretRng};
+use guessing_game::scores::{self, Record};
+use guessing_game::{cli, multiplayer, play, run, solver, Game, Mode, Options, State, VariantKind};
+use std::net::TcpListener;
+use std::time::Instant;
+use std::{env, io, process};
 
 fn main() {
-    println!("Guess the number!");
+    let options = Options::from_args(env::args()).unwrap_or_else(|err| {
+        eprintln!("Problem parsing arguments: {}", err);
+        eprintln!("{}", cli::USAGE);
+        process::exit(1);
+    });
 
-    // random number generator
-    let secret_number = rand::thread_rng().gen_range(1, 101);
-
-    // println!("The secret number is: {}", secret_number);
-
-    loop {
-        println!("Please input your guess.");
+    if options.mode == Mode::Scores {
+        let records = scores::load(&options.scores_file).unwrap_or_else(|err| {
+            eprintln!("Problem reading {}: {}", options.scores_file.display(), err);
+            process::exit(1);
+        });
+        scores::show(&records, options.locale, io::stdout().lock())
+            .expect("Failed to write scores");
+        return;
+    }
 
-        let mut guess = String::new();
+    // random number generator, or a reproducible one when given a seed
+    let mut rng: Box<dyn SecretRng> = match options.seed {
+        Some(seed) => Box::new(seeded(seed)),
+        None => Box::new(rand::thread_rng()),
+    };
 
-        // two lines for two method calls,
-        // stdin is a type that represents a handle to the standard input for your terminal
-        io::stdin().read_line(&mut guess)
-            .expect("Failed to read line");
-        // if Result is Err, expect will cause the program to crash and display the msg you passed
-        // if Result is OK, expect will take the value the Ok is holding and return just that value
+    match options.mode {
+        Mode::Solve => {
+            let mut game = Game::new(options.config, &mut *rng);
+            solver::solve(&mut game, options.locale, io::stdout().lock())
+                .expect("Failed to write guesses");
+            return;
+        }
+        Mode::Bench { games } => {
+            let distribution = solver::benchmark(&options.config, games as usize, &mut *rng);
+            distribution
+                .show(&options.config, io::stdout().lock())
+                .expect("Failed to write results");
+            if distribution.exceeds_bound(&options.config) {
+                process::exit(1);
+            }
+            return;
+        }
+        Mode::Host { address } => {
+            let listener = TcpListener::bind(&address).unwrap_or_else(|err| {
+                eprintln!("Problem listening on {}: {}", address, err);
+                process::exit(1);
+            });
+            println!(
+                "Hosting on {}, connect with e.g. `nc` or `telnet`.",
+                address
+            );
+            if let Err(err) = multiplayer::serve(
+                listener,
+                options.config,
+                options.rules,
+                options.locale,
+                &mut *rng,
+            ) {
+                eprintln!("Problem accepting players: {}", err);
+                process::exit(1);
+            }
+            return;
+        }
+        Mode::Play | Mode::Scores => {}
+    }
 
-        // "5\n".trim() = 5, trim eliminates \n
-        // parse method on strings parses a string into some kind of number
-        // let guess: u32 = guess.trim().parse().expect("Please type a number!");
+    // only classic games go on the leaderboard, the others aren't scored the same way
+    if options.variant != VariantKind::Classic {
+        let mut variant = options.variant.start(options.config, &mut *rng);
+        let stdin = io::stdin();
+        run(
+            &mut *variant,
+            options.locale,
+            stdin.lock(),
+            io::stdout().lock(),
+        )
+        .expect("Failed to read line");
+        return;
+    }
 
-        let guess: u32 = match guess.trim().parse() {
-            Ok(num) => num,
-            Err(_) => continue,
-        };
+    let mut game = Game::new(options.config, &mut *rng);
 
-        if guess < 1 || guess > 100 {
-            println!("The secret number will between 1 and 100.");
-            continue;
-        }
+    // println!("The secret number is: {}", game.secret());
 
-        println!("You guessed: {}", guess);
+    // stdin is a type that represents a handle to the standard input for your terminal,
+    // locking it gives us a BufRead so the game can read whole lines
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let start = Instant::now();
+    let state =
+        play(&mut game, options.locale, stdin.lock(), stdout.lock()).expect("Failed to read line");
 
-        match guess.cmp(&secret_number) {
-            Ordering::Less => println!("Too small!"),
-            Ordering::Greater => println!("Too big!"),
-            Ordering::Equal => {
-                println!("You win!");
-                break;
-            },
-        }
+    // a game abandoned halfway isn't worth a record
+    if state == State::Playing {
+        return;
+    }
+    let record = Record {
+        player: options.player,
+        won: state == State::Won,
+        attempts: game.attempts(),
+        duration: start.elapsed(),
+        min: game.config().min,
+        max: game.config().max,
+    };
+    if let Err(err) = scores::record(&options.scores_file, record) {
+        eprintln!("Problem saving your score: {}", err);
     }
 }
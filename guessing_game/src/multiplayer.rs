@@ -0,0 +1,469 @@
+// Hosting one secret number for several players over TCP, e.g. `nc localhost 7878`.
+//
+// The protocol is plain lines of text both ways: a player's first line is their name and
+// every line after that is a guess. Each connection gets a thread that reads its lines and
+// forwards them over a channel, and a single thread owns the game and answers them in the
+// order they arrive, so there's no locking and everyone sees the same order of events.
+//
+// Every player has their own `Game` for the shared secret, which keeps their own attempt
+// count and limit. The round ends when somebody guesses right or everyone runs out of
+// attempts, and a new one starts straight away with a new secret.
+
+use crate::config::GameConfig;
+use crate::game::{Game, Outcome, SecretRng, State};
+use crate::i18n::Locale;
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::ops::Bound;
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rules {
+    /// Everyone guesses whenever they like and the first right guess wins.
+    Race,
+    /// Players guess one after another, in the order they joined.
+    Turns,
+}
+
+enum Event {
+    Joined(usize, TcpStream),
+    Line(usize, String),
+    Left(usize),
+}
+
+struct Player {
+    stream: TcpStream,
+    /// `None` until they've sent their first line.
+    name: Option<String>,
+    game: Game,
+}
+
+struct Host<'r, R: ?Sized> {
+    config: GameConfig,
+    rules: Rules,
+    locale: Locale,
+    rng: &'r mut R,
+    secret: u32,
+    players: BTreeMap<usize, Player>,
+    /// Whose turn it is, always `None` in a race.
+    turn: Option<usize>,
+}
+
+/// Hosts games for everyone who connects to `listener`, until accepting connections fails.
+/// Everyone is spoken to in `locale`.
+pub fn serve<R: SecretRng + ?Sized>(
+    listener: TcpListener,
+    config: GameConfig,
+    rules: Rules,
+    locale: Locale,
+    rng: &mut R,
+) -> io::Result<()> {
+    let (sender, events) = mpsc::channel();
+    let accepting = thread::spawn(move || accept(listener, sender));
+
+    let mut host = Host {
+        secret: rng.secret(config.min, config.max),
+        config,
+        rules,
+        locale,
+        rng,
+        players: BTreeMap::new(),
+        turn: None,
+    };
+    // only ends once the accepting thread and every reader has hung up
+    for event in events {
+        match event {
+            Event::Joined(id, stream) => host.joined(id, stream),
+            Event::Line(id, line) => host.line(id, line.trim()),
+            Event::Left(id) => host.left(id),
+        }
+    }
+
+    accepting.join().expect("accepting thread panicked")
+}
+
+fn accept(listener: TcpListener, sender: Sender<Event>) -> io::Result<()> {
+    for (id, stream) in listener.incoming().enumerate() {
+        let stream = stream?;
+        // every message is a short line that should go out right away
+        stream.set_nodelay(true)?;
+        let sender = sender.clone();
+        if sender.send(Event::Joined(id, stream.try_clone()?)).is_err() {
+            break;
+        }
+
+        thread::spawn(move || {
+            for line in BufReader::new(stream).lines() {
+                // anything that isn't valid UTF-8 ends the connection like a hang up does
+                let line = match line {
+                    Ok(line) => line,
+                    Err(_) => break,
+                };
+                if sender.send(Event::Line(id, line)).is_err() {
+                    return;
+                }
+            }
+            let _ = sender.send(Event::Left(id));
+        });
+    }
+    Ok(())
+}
+
+/// Writes `message` and its newline in one go, `writeln!` would send them as separate packets.
+fn write_line(stream: &mut TcpStream, message: &str) -> io::Result<()> {
+    stream.write_all(format!("{}\n", message).as_bytes())
+}
+
+impl<'r, R: SecretRng + ?Sized> Host<'r, R> {
+    // write errors are ignored, a player who's gone will show up as Left soon enough
+    fn send(&mut self, id: usize, message: &str) {
+        if let Some(player) = self.players.get_mut(&id) {
+            let _ = write_line(&mut player.stream, message);
+        }
+    }
+
+    /// Sends `message` to every named player except `except`.
+    fn broadcast(&mut self, message: &str, except: Option<usize>) {
+        for (&id, player) in self.players.iter_mut() {
+            if player.name.is_some() && Some(id) != except {
+                let _ = write_line(&mut player.stream, message);
+            }
+        }
+    }
+
+    fn name(&self, id: usize) -> String {
+        self.players[&id].name.clone().unwrap_or_default()
+    }
+
+    fn rules_message(&self) -> String {
+        let (min, max) = (self.config.min, self.config.max);
+        match self.config.max_attempts {
+            Some(attempts) => self.locale.format(
+                "rules-attempts",
+                &[("min", &min), ("max", &max), ("attempts", &attempts)],
+            ),
+            None => self.locale.format("rules", &[("min", &min), ("max", &max)]),
+        }
+    }
+
+    fn joined(&mut self, id: usize, stream: TcpStream) {
+        let player = Player {
+            stream,
+            name: None,
+            game: Game::with_secret(self.config.clone(), self.secret),
+        };
+        self.players.insert(id, player);
+        self.send(id, self.locale.text("welcome"));
+    }
+
+    fn line(&mut self, id: usize, line: &str) {
+        if self.players[&id].name.is_none() {
+            let name = if line.is_empty() {
+                format!("player{}", id + 1)
+            } else {
+                String::from(line)
+            };
+            self.players.get_mut(&id).unwrap().name = Some(name.clone());
+
+            let rules = self.rules_message();
+            let hi = self
+                .locale
+                .format("hi", &[("name", &name), ("rules", &rules)]);
+            self.send(id, &hi);
+            let joined = self.locale.format("joined", &[("name", &name)]);
+            self.broadcast(&joined, Some(id));
+            if self.rules == Rules::Turns && self.turn.is_none() {
+                self.next_turn();
+            }
+            return;
+        }
+
+        if self.rules == Rules::Turns && self.turn != Some(id) {
+            let message = match self.turn {
+                Some(turn) => self
+                    .locale
+                    .format("wait-for-turn", &[("name", &self.name(turn))]),
+                None => String::from(self.locale.text("wait")),
+            };
+            self.send(id, &message);
+            return;
+        }
+
+        let guess: u32 = match line.parse() {
+            Ok(num) => num,
+            Err(_) => return self.send(id, self.locale.text("not-a-number")),
+        };
+
+        let name = self.name(id);
+        let locale = self.locale;
+        let game = &mut self.players.get_mut(&id).unwrap().game;
+        let (own, others) = match game.guess(guess) {
+            Outcome::OutOfRange => {
+                let (min, max) = (self.config.min, self.config.max);
+                let message = locale.format("out-of-range", &[("min", &min), ("max", &max)]);
+                return self.send(id, &message);
+            }
+            Outcome::GameOver => return self.send(id, locale.text("out-of-attempts-wait")),
+            Outcome::Correct => {
+                let message = locale.format("you-guessed", &[("guess", &guess)]);
+                self.send(id, &message);
+                self.send(id, locale.text("you-win"));
+                let args = [("name", &name as _), ("secret", &self.secret as _)];
+                self.broadcast(&locale.format("player-wins", &args), Some(id));
+                return self.new_round();
+            }
+            Outcome::TooSmall => ("too-small", "player-too-small"),
+            Outcome::TooBig => ("too-big", "player-too-big"),
+        };
+        let lost = game.state() == State::Lost;
+
+        self.send(id, &locale.format("you-guessed", &[("guess", &guess)]));
+        self.send(id, locale.text(own));
+        let args = [("name", &name as _), ("guess", &guess as _)];
+        self.broadcast(&locale.format(others, &args), Some(id));
+        if lost {
+            self.send(id, locale.text("out-of-attempts"));
+            let message = locale.format("player-out", &[("name", &name)]);
+            self.broadcast(&message, Some(id));
+        }
+        self.continue_round();
+    }
+
+    fn left(&mut self, id: usize) {
+        let player = match self.players.remove(&id) {
+            Some(player) => player,
+            None => return,
+        };
+        if let Some(name) = player.name {
+            let message = self.locale.format("left", &[("name", &name)]);
+            self.broadcast(&message, None);
+            self.continue_round();
+        }
+    }
+
+    /// Moves on after a wrong guess or a player leaving.
+    fn continue_round(&mut self) {
+        let anyone_playing = self
+            .players
+            .values()
+            .any(|player| player.name.is_some() && player.game.state() == State::Playing);
+        if !anyone_playing {
+            // with nobody named left there's nobody to tell
+            if self.players.values().any(|player| player.name.is_some()) {
+                let message = self
+                    .locale
+                    .format("nobody-won", &[("secret", &self.secret)]);
+                self.broadcast(&message, None);
+            }
+            return self.new_round();
+        }
+        if self.rules == Rules::Turns {
+            self.next_turn();
+        }
+    }
+
+    fn new_round(&mut self) {
+        self.secret = self.rng.secret(self.config.min, self.config.max);
+        for player in self.players.values_mut() {
+            player.game = Game::with_secret(self.config.clone(), self.secret);
+        }
+
+        if self.players.values().any(|player| player.name.is_some()) {
+            let rules = self.rules_message();
+            let message = self.locale.format("new-round", &[("rules", &rules)]);
+            self.broadcast(&message, None);
+            if self.rules == Rules::Turns {
+                self.next_turn();
+            }
+        } else {
+            // so that whoever names themselves next gets the first turn
+            self.turn = None;
+        }
+    }
+
+    /// Passes the turn to the next player in joining order who can still guess.
+    fn next_turn(&mut self) {
+        let can_guess =
+            |player: &&Player| player.name.is_some() && player.game.state() == State::Playing;
+        let after = match self.turn {
+            Some(turn) => Bound::Excluded(turn),
+            None => Bound::Unbounded,
+        };
+        let next = self
+            .players
+            .range((after, Bound::Unbounded))
+            .chain(self.players.iter())
+            .find(|(_, player)| can_guess(player))
+            .map(|(&id, _)| id);
+
+        self.turn = next;
+        if let Some(id) = next {
+            let message = self.locale.format("turn", &[("name", &self.name(id))]);
+            self.broadcast(&message, None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{serve, write_line, Rules};
+    use crate::config::GameConfig;
+    use crate::game::{seeded, SecretRng};
+    use crate::i18n::Locale;
+    use std::io::{BufRead, BufReader};
+    use std::net::{SocketAddr, TcpListener, TcpStream};
+    use std::thread;
+    use std::time::Duration;
+
+    struct Client {
+        reader: BufReader<TcpStream>,
+        writer: TcpStream,
+    }
+
+    impl Client {
+        fn connect(addr: SocketAddr, name: &str) -> Client {
+            let stream = TcpStream::connect(addr).unwrap();
+            // a wrong expectation fails the test instead of hanging it
+            stream
+                .set_read_timeout(Some(Duration::from_secs(5)))
+                .unwrap();
+            let mut client = Client {
+                reader: BufReader::new(stream.try_clone().unwrap()),
+                writer: stream,
+            };
+            client.expect("Welcome to the guessing game! What's your name?");
+            client.send(name);
+            client
+        }
+
+        fn send(&mut self, line: &str) {
+            write_line(&mut self.writer, line).unwrap();
+        }
+
+        fn expect(&mut self, expected: &str) {
+            for expected in expected.lines() {
+                let mut line = String::new();
+                self.reader.read_line(&mut line).unwrap();
+                assert_eq!(expected, line.trim_end());
+            }
+        }
+    }
+
+    // the secrets the server will draw, round by round
+    fn host(config: GameConfig, rules: Rules, seed: u64) -> (SocketAddr, Vec<u32>) {
+        let mut rng = seeded(seed);
+        let secrets = (0..3).map(|_| rng.secret(config.min, config.max)).collect();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        // left running, it goes away with the test process
+        thread::spawn(move || serve(listener, config, rules, Locale::En, &mut seeded(seed)));
+        (addr, secrets)
+    }
+
+    #[test]
+    fn race() {
+        let (addr, secrets) = host(GameConfig::default(), Rules::Race, 1);
+        let mut alice = Client::connect(addr, "alice");
+        alice.expect("Hi alice! Guess the number between 1 and 100.");
+        let mut bob = Client::connect(addr, " bob\r");
+        bob.expect("Hi bob! Guess the number between 1 and 100.");
+        alice.expect("bob joined.");
+
+        let (low, high) = (secrets[0] - 1, secrets[0] + 1);
+        alice.send(&high.to_string());
+        alice.expect(&format!("You guessed: {}\nToo big!", high));
+        bob.expect(&format!("alice guessed {}: too big.", high));
+
+        bob.send("lots");
+        bob.expect("Please type a number!");
+        bob.send("0");
+        bob.expect("The secret number will be between 1 and 100.");
+        bob.send(&low.to_string());
+        bob.expect(&format!("You guessed: {}\nToo small!", low));
+        alice.expect(&format!("bob guessed {}: too small.", low));
+
+        alice.send(&secrets[0].to_string());
+        alice.expect(&format!("You guessed: {}\nYou win!", secrets[0]));
+        bob.expect(&format!(
+            "alice wins! The secret number was {}.",
+            secrets[0]
+        ));
+        for client in [&mut alice, &mut bob].iter_mut() {
+            client.expect("New round! Guess the number between 1 and 100.");
+        }
+
+        drop(bob);
+        alice.expect("bob left.");
+        alice.send(&secrets[1].to_string());
+        alice.expect(&format!("You guessed: {}\nYou win!", secrets[1]));
+        alice.expect("New round! Guess the number between 1 and 100.");
+    }
+
+    fn comparison(guess: u32, secret: u32) -> &'static str {
+        if guess < secret {
+            "small"
+        } else {
+            "big"
+        }
+    }
+
+    #[test]
+    fn turns_and_running_out_of_attempts() {
+        let config = GameConfig::new(1, 10, Some(1)).unwrap();
+        let (addr, secrets) = host(config, Rules::Turns, 2);
+        let wrong = if secrets[0] == 1 { 2 } else { 1 };
+        let too = comparison(wrong, secrets[0]);
+
+        let mut alice = Client::connect(addr, "alice");
+        alice.expect("Hi alice! Guess the number between 1 and 10. You have 1 attempts.");
+        alice.expect("It's alice's turn.");
+        let mut bob = Client::connect(addr, "");
+        bob.expect("Hi player2! Guess the number between 1 and 10. You have 1 attempts.");
+        alice.expect("player2 joined.");
+
+        bob.send("5");
+        bob.expect("Wait for your turn, it's alice's.");
+
+        alice.send(&wrong.to_string());
+        alice.expect(&format!("You guessed: {}\nToo {}!", wrong, too));
+        alice.expect("You're out of attempts.");
+        bob.expect(&format!("alice guessed {}: too {}.", wrong, too));
+        bob.expect("alice is out of attempts.");
+        for client in [&mut alice, &mut bob].iter_mut() {
+            client.expect("It's player2's turn.");
+        }
+
+        bob.send(&wrong.to_string());
+        bob.expect(&format!("You guessed: {}\nToo {}!", wrong, too));
+        bob.expect("You're out of attempts.");
+        alice.expect(&format!("player2 guessed {}: too {}.", wrong, too));
+        alice.expect("player2 is out of attempts.");
+        for client in [&mut alice, &mut bob].iter_mut() {
+            client.expect(&format!(
+                "Nobody guessed it, the secret number was {}.",
+                secrets[0]
+            ));
+            client.expect("New round! Guess the number between 1 and 10. You have 1 attempts.");
+            // the turns keep going round from where they were
+            client.expect("It's alice's turn.");
+        }
+
+        alice.send(&secrets[1].to_string());
+        alice.expect(&format!("You guessed: {}\nYou win!", secrets[1]));
+        bob.expect(&format!(
+            "alice wins! The secret number was {}.",
+            secrets[1]
+        ));
+        for client in [&mut alice, &mut bob].iter_mut() {
+            client.expect("New round! Guess the number between 1 and 10. You have 1 attempts.");
+            client.expect("It's player2's turn.");
+        }
+
+        drop(bob);
+        alice.expect("player2 left.");
+        alice.expect("It's alice's turn.");
+    }
+}
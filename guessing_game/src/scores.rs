@@ -0,0 +1,374 @@
+// The high-score file is plain text, one finished game per line after a version header:
+//
+//     guessing_game scores v1
+//     <player>\t<won: 1 or 0>\t<attempts>\t<duration in ms>\t<min>\t<max>
+//
+// Writers take an exclusive lock on a sidecar `.lock` file, read the current scores, write
+// them plus the new game to a temporary file and rename it over the old one. The lock keeps
+// two games finishing at once from losing each other's record, and the rename means a crash
+// mid-write leaves the previous file intact instead of a truncated one.
+
+use crate::i18n::Locale;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const HEADER: &str = "guessing_game scores";
+pub const VERSION: u32 = 1;
+
+/// One finished game.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    pub player: String,
+    pub won: bool,
+    pub attempts: u32,
+    pub duration: Duration,
+    pub min: u32,
+    pub max: u32,
+}
+
+#[derive(Debug)]
+pub enum ScoreError {
+    Io(io::Error),
+    /// The file was written by a version of the game we don't understand.
+    UnsupportedVersion(String),
+    Corrupt {
+        line: usize,
+        reason: &'static str,
+    },
+}
+
+impl fmt::Display for ScoreError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScoreError::Io(e) => write!(f, "{}", e),
+            ScoreError::UnsupportedVersion(header) => {
+                write!(f, "unsupported score file version `{}`", header)
+            }
+            ScoreError::Corrupt { line, reason } => {
+                write!(f, "score file is corrupt at line {}: {}", line, reason)
+            }
+        }
+    }
+}
+
+impl Error for ScoreError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ScoreError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ScoreError {
+    fn from(e: io::Error) -> ScoreError {
+        ScoreError::Io(e)
+    }
+}
+
+/// `$GUESSING_GAME_SCORES`, or `.guessing_game_scores` in the home directory.
+pub fn default_path() -> PathBuf {
+    if let Some(path) = std::env::var_os("GUESSING_GAME_SCORES") {
+        return PathBuf::from(path);
+    }
+    let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"));
+    match home {
+        Some(home) => Path::new(&home).join(".guessing_game_scores"),
+        None => PathBuf::from(".guessing_game_scores"),
+    }
+}
+
+fn parse(contents: &str) -> Result<Vec<Record>, ScoreError> {
+    let mut lines = contents.lines();
+    match lines.next() {
+        // a file that was never written to has no scores yet
+        None => return Ok(Vec::new()),
+        Some(header) if header == format!("{} v{}", HEADER, VERSION) => {}
+        Some(header) => return Err(ScoreError::UnsupportedVersion(String::from(header))),
+    }
+
+    let mut records = Vec::new();
+    for (i, line) in lines.enumerate() {
+        if line.is_empty() {
+            continue;
+        }
+        let corrupt = |reason| ScoreError::Corrupt {
+            line: i + 2,
+            reason,
+        };
+
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() != 6 {
+            return Err(corrupt("expected 6 fields"));
+        }
+        let number = |field: &str| field.parse::<u32>().map_err(|_| corrupt("not a number"));
+
+        records.push(Record {
+            player: String::from(fields[0]),
+            won: match fields[1] {
+                "1" => true,
+                "0" => false,
+                _ => return Err(corrupt("won must be 1 or 0")),
+            },
+            attempts: number(fields[2])?,
+            duration: Duration::from_millis(
+                fields[3].parse().map_err(|_| corrupt("not a number"))?,
+            ),
+            min: number(fields[4])?,
+            max: number(fields[5])?,
+        });
+    }
+    Ok(records)
+}
+
+fn format(records: &[Record]) -> String {
+    let mut contents = format!("{} v{}\n", HEADER, VERSION);
+    for record in records {
+        // tabs and newlines would break the line format
+        let player: String = record
+            .player
+            .chars()
+            .map(|c| if c.is_control() { ' ' } else { c })
+            .collect();
+        contents.push_str(&format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\n",
+            player,
+            if record.won { 1 } else { 0 },
+            record.attempts,
+            record.duration.as_millis(),
+            record.min,
+            record.max
+        ));
+    }
+    contents
+}
+
+/// Every game recorded in `path`, oldest first. A missing file has no games.
+pub fn load(path: &Path) -> Result<Vec<Record>, ScoreError> {
+    match fs::read_to_string(path) {
+        Ok(contents) => parse(&contents),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// Adds `record` to the scores in `path`, safely against other processes doing the same.
+pub fn record(path: &Path, record: Record) -> Result<(), ScoreError> {
+    if let Some(dir) = path.parent() {
+        if !dir.as_os_str().is_empty() {
+            fs::create_dir_all(dir)?;
+        }
+    }
+
+    // lock a separate file, the score file itself gets replaced by the rename
+    let lock = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(sibling(path, ".lock"))?;
+    lock.lock()?;
+
+    let mut records = load(path)?;
+    records.push(record);
+
+    let tmp = sibling(path, &format!(".tmp{}", std::process::id()));
+    let written = (|| -> io::Result<()> {
+        let mut file = File::create(&tmp)?;
+        file.write_all(format(&records).as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&tmp, path)
+    })();
+    if written.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+
+    // dropping `lock` releases it as well, this just makes the order explicit
+    lock.unlock()?;
+    written.map_err(ScoreError::from)
+}
+
+/// The best won games for each range, fewest attempts then fastest first.
+pub fn leaderboard(records: &[Record], per_range: usize) -> BTreeMap<(u32, u32), Vec<&Record>> {
+    let mut boards: BTreeMap<(u32, u32), Vec<&Record>> = BTreeMap::new();
+    for record in records.iter().filter(|record| record.won) {
+        boards
+            .entry((record.min, record.max))
+            .or_default()
+            .push(record);
+    }
+
+    for board in boards.values_mut() {
+        board.sort_by_key(|record| (record.attempts, record.duration));
+        board.truncate(per_range);
+    }
+    boards
+}
+
+/// Writes the leaderboard as a table per range.
+pub fn show<W: Write>(records: &[Record], locale: Locale, mut output: W) -> io::Result<()> {
+    let boards = leaderboard(records, 10);
+    if boards.is_empty() {
+        return writeln!(output, "{}", locale.text("no-wins"));
+    }
+
+    for ((min, max), board) in boards {
+        let range = locale.format("leaderboard-range", &[("min", &min), ("max", &max)]);
+        writeln!(output, "{}", range)?;
+        for (rank, record) in board.iter().enumerate() {
+            writeln!(
+                output,
+                "{:>3}. {:<20} {:>3} {} {:>8.1}s",
+                rank + 1,
+                record.player,
+                record.attempts,
+                locale.text("leaderboard-attempts"),
+                record.duration.as_secs_f64()
+            )?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    // a directory of its own for a test, removed again when the test is over
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> TempDir {
+            let dir =
+                std::env::temp_dir().join(format!("guessing_game-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            TempDir(dir)
+        }
+
+        fn scores(&self) -> PathBuf {
+            self.0.join("scores")
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn game(player: &str, attempts: u32, millis: u64) -> Record {
+        Record {
+            player: String::from(player),
+            won: true,
+            attempts,
+            duration: Duration::from_millis(millis),
+            min: 1,
+            max: 100,
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let dir = TempDir::new("round-trip");
+        let path = dir.scores();
+        assert_eq!(Vec::<Record>::new(), load(&path).unwrap());
+
+        let mut lost = game("Ferris\tthe crab", 10, 12_345);
+        lost.won = false;
+        record(&path, game("Sally", 7, 30_000)).unwrap();
+        record(&path, lost).unwrap();
+
+        let records = load(&path).unwrap();
+        assert_eq!(2, records.len());
+        assert_eq!(game("Sally", 7, 30_000), records[0]);
+        assert_eq!("Ferris the crab", records[1].player);
+        assert!(!records[1].won);
+
+        let contents = fs::read_to_string(&path).unwrap();
+        assert!(contents.starts_with("guessing_game scores v1\n"));
+    }
+
+    #[test]
+    fn rejects_other_versions_and_corruption() {
+        match parse("guessing_game scores v2\nwhatever") {
+            Err(ScoreError::UnsupportedVersion(header)) => {
+                assert_eq!("guessing_game scores v2", header)
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        match parse("guessing_game scores v1\nSally\t1\t7\t100\t1\n") {
+            Err(ScoreError::Corrupt { line: 2, .. }) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+        match parse("guessing_game scores v1\n\nSally\tyes\t7\t100\t1\t100\n") {
+            Err(ScoreError::Corrupt { line: 3, .. }) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn concurrent_writers_keep_every_record() {
+        let dir = TempDir::new("concurrent");
+        let path = dir.scores();
+        let writers: Vec<_> = (0..8)
+            .map(|writer| {
+                let path = path.clone();
+                thread::spawn(move || {
+                    for attempt in 0..10 {
+                        let player = format!("player{}", writer);
+                        record(&path, game(&player, attempt + 1, 1000)).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        assert_eq!(80, load(&path).unwrap().len());
+    }
+
+    #[test]
+    fn leaderboard_ranks_wins_per_range() {
+        let mut lost = game("Loser", 1, 1);
+        lost.won = false;
+        let mut hard = game("Amir", 9, 60_000);
+        hard.max = 1000;
+        let records = vec![
+            game("Sally", 7, 30_000),
+            game("Leon", 5, 50_000),
+            game("Amir", 5, 20_000),
+            lost,
+            hard,
+        ];
+
+        let boards = leaderboard(&records, 2);
+        let classic: Vec<&str> = boards[&(1, 100)]
+            .iter()
+            .map(|record| record.player.as_str())
+            .collect();
+        assert_eq!(vec!["Amir", "Leon"], classic);
+        assert_eq!(1, boards[&(1, 1000)].len());
+
+        let mut output = Vec::new();
+        show(&records, Locale::En, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("Between 1 and 100:\n  1. Amir"));
+        assert!(output.contains("Between 1 and 1000:\n  1. Amir"));
+
+        let mut output = Vec::new();
+        show(&[], Locale::En, &mut output).unwrap();
+        assert_eq!(b"No games won yet.\n".to_vec(), output);
+    }
+}
@@ -0,0 +1,299 @@
+use crate::config::GameConfig;
+use crate::game::{Game, Outcome, SecretRng, State};
+use crate::i18n::Locale;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+/// Plays the game by binary search: always guess the middle of the numbers still possible.
+pub struct Solver {
+    // the secret is somewhere in low..=high, kept as i64 so that stepping past
+    // 0 or u32::MAX just empties the interval instead of overflowing
+    low: i64,
+    high: i64,
+}
+
+impl Solver {
+    pub fn new(config: &GameConfig) -> Solver {
+        Solver {
+            low: config.min as i64,
+            high: config.max as i64,
+        }
+    }
+
+    /// The middle of the remaining interval, or `None` if the feedback so far contradicts itself.
+    pub fn next_guess(&self) -> Option<u32> {
+        if self.low > self.high {
+            return None;
+        }
+        Some(((self.low + self.high) / 2) as u32)
+    }
+
+    /// Narrows the interval using what the game said about `guess`.
+    pub fn feedback(&mut self, guess: u32, outcome: Outcome) {
+        let guess = guess as i64;
+        match outcome {
+            Outcome::TooSmall => self.low = guess + 1,
+            Outcome::TooBig => self.high = guess - 1,
+            Outcome::Correct => {
+                self.low = guess;
+                self.high = guess;
+            }
+            Outcome::OutOfRange | Outcome::GameOver => {}
+        }
+    }
+}
+
+/// Lets the solver play `game` to the end, calling `each` with every guess and its outcome.
+fn play_out<F>(game: &mut Game, mut each: F) -> io::Result<State>
+where
+    F: FnMut(u32, Outcome) -> io::Result<()>,
+{
+    let mut solver = Solver::new(game.config());
+    while game.state() == State::Playing {
+        let guess = match solver.next_guess() {
+            Some(guess) => guess,
+            None => break,
+        };
+        let outcome = game.guess(guess);
+        if let Outcome::OutOfRange | Outcome::GameOver = outcome {
+            break;
+        }
+        each(guess, outcome)?;
+        solver.feedback(guess, outcome);
+    }
+    Ok(game.state())
+}
+
+/// Lets the solver play `game` to the end, telling `output` about every guess.
+pub fn solve<W: Write>(game: &mut Game, locale: Locale, mut output: W) -> io::Result<State> {
+    play_out(game, |guess, outcome| {
+        let answer = match outcome {
+            Outcome::TooSmall => locale.text("too-small"),
+            Outcome::TooBig => locale.text("too-big"),
+            _ => locale.text("got-it"),
+        };
+        let args = [("guess", &guess as _), ("answer", &answer as _)];
+        writeln!(output, "{}", locale.format("solver-guess", &args))
+    })?;
+
+    let attempts = game.attempts();
+    match game.state() {
+        State::Won => writeln!(
+            output,
+            "{}",
+            locale.format("solved", &[("attempts", &attempts)])
+        )?,
+        _ => {
+            let secret = game.secret();
+            let args = [("attempts", &attempts as _), ("secret", &secret as _)];
+            writeln!(output, "{}", locale.format("solver-lost", &args))?
+        }
+    }
+    Ok(game.state())
+}
+
+/// The most guesses binary search can need for `count` possible numbers.
+///
+/// Each guess either wins or leaves at most half of the other `count - 1` numbers, so `k`
+/// guesses cover `2^k - 1` numbers and the worst case is ⌈log2(count + 1)⌉. That's the same as
+/// ⌈log2(count)⌉ except when `count` is a power of two, e.g. 4 numbers can take 3 guesses.
+pub fn worst_case(count: u64) -> u32 {
+    // ⌈log2(count + 1)⌉ is the bit length of `count`
+    64 - count.leading_zeros()
+}
+
+fn bound(config: &GameConfig) -> u32 {
+    worst_case(config.max as u64 - config.min as u64 + 1)
+}
+
+/// How many attempts the solver needed over many games.
+#[derive(Debug, Default, PartialEq)]
+pub struct Distribution {
+    /// Games won, by the number of attempts they took.
+    pub wins: BTreeMap<u32, usize>,
+    /// Games where the attempt limit ran out first.
+    pub losses: usize,
+}
+
+impl Distribution {
+    pub fn games(&self) -> usize {
+        self.wins.values().sum::<usize>() + self.losses
+    }
+
+    pub fn max(&self) -> Option<u32> {
+        self.wins.keys().next_back().copied()
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        let won: usize = self.wins.values().sum();
+        if won == 0 {
+            return None;
+        }
+        let attempts: usize = self
+            .wins
+            .iter()
+            .map(|(&attempts, &games)| attempts as usize * games)
+            .sum();
+        Some(attempts as f64 / won as f64)
+    }
+
+    /// Whether some game took more attempts than binary search ever should over `config`'s range.
+    pub fn exceeds_bound(&self, config: &GameConfig) -> bool {
+        self.max().is_some_and(|max| max > bound(config))
+    }
+
+    /// Writes a histogram of the attempts and how the worst game compares to the bound.
+    pub fn show<W: Write>(&self, config: &GameConfig, mut output: W) -> io::Result<()> {
+        let most = self.wins.values().copied().max().unwrap_or(0);
+        writeln!(output, "attempts  games")?;
+        for (attempts, &games) in &self.wins {
+            // bars are at most 50 characters wide
+            let bar = "#".repeat((games * 50 + most - 1) / most.max(1));
+            writeln!(output, "{:>8}  {:>5}  {}", attempts, games, bar)?;
+        }
+        if self.losses > 0 {
+            writeln!(output, "    lost  {:>5}", self.losses)?;
+        }
+
+        writeln!(output, "games: {}", self.games())?;
+        if let Some(mean) = self.mean() {
+            writeln!(output, "mean: {:.3}", mean)?;
+        }
+        if let Some(max) = self.max() {
+            let verdict = if self.exceeds_bound(config) {
+                "EXCEEDED"
+            } else {
+                "ok"
+            };
+            writeln!(
+                output,
+                "worst: {} (bound {}, {})",
+                max,
+                bound(config),
+                verdict
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Lets the solver play `games` games with secrets from `rng`.
+pub fn benchmark<R: SecretRng + ?Sized>(
+    config: &GameConfig,
+    games: usize,
+    rng: &mut R,
+) -> Distribution {
+    let mut distribution = Distribution::default();
+    for _ in 0..games {
+        let mut game = Game::new(config.clone(), rng);
+        match play_out(&mut game, |_, _| Ok(())) {
+            Ok(State::Won) => *distribution.wins.entry(game.attempts()).or_insert(0) += 1,
+            _ => distribution.losses += 1,
+        }
+    }
+    distribution
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{benchmark, play_out, solve, worst_case, Distribution};
+    use crate::config::GameConfig;
+    use crate::game::{seeded, Game, State};
+    use crate::i18n::Locale;
+    use std::io;
+
+    fn attempts_for(config: &GameConfig, secret: u32) -> u32 {
+        let mut game = Game::with_secret(config.clone(), secret);
+        assert_eq!(State::Won, play_out(&mut game, |_, _| Ok(())).unwrap());
+        game.attempts()
+    }
+
+    #[test]
+    fn worst_case_bound() {
+        assert_eq!(1, worst_case(1));
+        assert_eq!(2, worst_case(2));
+        assert_eq!(2, worst_case(3));
+        assert_eq!(3, worst_case(4));
+        assert_eq!(7, worst_case(100));
+        assert_eq!(10, worst_case(1000));
+        // every u32, a power of two
+        assert_eq!(33, worst_case(1 << 32));
+    }
+
+    #[test]
+    fn every_secret_within_the_bound() {
+        for count in 1..=130 {
+            let config = GameConfig::new(5, 5 + count - 1, None).unwrap();
+            let worst = (config.min..=config.max)
+                .map(|secret| attempts_for(&config, secret))
+                .max()
+                .unwrap();
+            // binary search meets the bound exactly, it's not just an upper limit
+            assert_eq!(worst_case(count as u64), worst, "{} numbers", count);
+        }
+    }
+
+    #[test]
+    fn extreme_ranges() {
+        let config = GameConfig::new(0, u32::MAX, None).unwrap();
+        for &secret in &[0, 1, u32::MAX / 2, u32::MAX - 1, u32::MAX] {
+            assert!(attempts_for(&config, secret) <= 33);
+        }
+    }
+
+    #[test]
+    fn solve_reports_guesses() {
+        let mut output = Vec::new();
+        let mut game = Game::with_secret(GameConfig::default(), 30);
+        solve(&mut game, Locale::En, &mut output).unwrap();
+
+        assert_eq!(
+            "\
+Guessing 50... Too big!
+Guessing 25... Too small!
+Guessing 37... Too big!
+Guessing 31... Too big!
+Guessing 28... Too small!
+Guessing 29... Too small!
+Guessing 30... Got it!
+Solved in 7 attempts.
+",
+            String::from_utf8(output).unwrap()
+        );
+    }
+
+    #[test]
+    fn too_few_attempts() {
+        let config = GameConfig::new(1, 100, Some(3)).unwrap();
+        let mut game = Game::with_secret(config, 1);
+        assert_eq!(
+            State::Lost,
+            solve(&mut game, Locale::En, io::sink()).unwrap()
+        );
+    }
+
+    #[test]
+    fn benchmark_distribution() {
+        let config = GameConfig::from_difficulty(crate::Difficulty::Hard);
+        let distribution = benchmark(&config, 2000, &mut seeded(1));
+
+        assert_eq!(2000, distribution.games());
+        assert_eq!(0, distribution.losses);
+        assert!(distribution.max().unwrap() <= 10);
+        assert!(distribution.mean().unwrap() > 8.0);
+
+        let mut output = Vec::new();
+        distribution.show(&config, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("attempts  games\n"));
+        assert!(output.contains("games: 2000\n"));
+        assert!(output.ends_with("(bound 10, ok)\n"));
+        assert!(!distribution.exceeds_bound(&config));
+
+        let mut slow = Distribution::default();
+        slow.wins.insert(11, 1);
+        assert!(slow.exceeds_bound(&config));
+
+        assert_eq!(None, Distribution::default().mean());
+    }
+}
@@ -0,0 +1,462 @@
+use crate::config::GameConfig;
+use crate::game::{guess_line, report_loss, Game, Outcome, SecretRng, State, Variant};
+use crate::i18n::Locale;
+use crate::solver::Solver;
+use std::io::{self, Write};
+
+/// The ways there are to play, picked with `--variant`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VariantKind {
+    /// Too small or too big, the way it's always been.
+    Classic,
+    /// How close the guess was instead of which way to go.
+    HotCold,
+    /// The classic game for points, with hints that cost some of them.
+    Hints,
+    /// The player thinks of the number and the computer guesses it.
+    Reverse,
+}
+
+impl VariantKind {
+    pub fn parse(name: &str) -> Option<VariantKind> {
+        match name.to_lowercase().as_str() {
+            "classic" => Some(VariantKind::Classic),
+            "hot-cold" => Some(VariantKind::HotCold),
+            "hints" => Some(VariantKind::Hints),
+            "reverse" => Some(VariantKind::Reverse),
+            _ => None,
+        }
+    }
+
+    pub fn start<R: SecretRng + ?Sized>(self, config: GameConfig, rng: &mut R) -> Box<dyn Variant> {
+        match self {
+            VariantKind::Classic => Box::new(Game::new(config, rng)),
+            VariantKind::HotCold => Box::new(HotCold::new(Game::new(config, rng))),
+            VariantKind::Hints => Box::new(Hints::new(Game::new(config, rng))),
+            VariantKind::Reverse => Box::new(Reverse::new(config)),
+        }
+    }
+}
+
+/// Says how far off a guess was, and whether it was closer than the one before,
+/// but not which way the secret is.
+pub struct HotCold {
+    game: Game,
+    last_distance: Option<u64>,
+}
+
+impl HotCold {
+    pub fn new(game: Game) -> HotCold {
+        HotCold {
+            game,
+            last_distance: None,
+        }
+    }
+
+    /// The message key for how `distance` feels over the game's range: within 5% of it
+    /// is hot, 15% warm, 35% cold and anything further freezing.
+    pub fn temperature(&self, distance: u64) -> &'static str {
+        let config = self.game.config();
+        let span = config.max as u64 - config.min as u64 + 1;
+        match distance * 100 / span {
+            0..=5 => "hot",
+            6..=15 => "warm",
+            16..=35 => "cold",
+            _ => "freezing",
+        }
+    }
+}
+
+impl Variant for HotCold {
+    fn intro(&self, locale: Locale, output: &mut dyn Write) -> io::Result<()> {
+        self.game.intro(locale, output)?;
+        writeln!(output, "{}", locale.text("hot-cold-intro"))
+    }
+
+    fn prompt(&self, locale: Locale, output: &mut dyn Write) -> io::Result<()> {
+        self.game.prompt(locale, output)
+    }
+
+    fn respond(&mut self, line: &str, locale: Locale, output: &mut dyn Write) -> io::Result<()> {
+        let guess = match guess_line(&mut self.game, line, locale, output)? {
+            Some((_, Outcome::Correct)) => return writeln!(output, "{}", locale.text("you-win")),
+            Some((guess, _)) => guess,
+            None => return Ok(()),
+        };
+
+        let distance = (guess as i64 - self.game.secret() as i64).unsigned_abs();
+        let temperature = locale.text(self.temperature(distance));
+        let trend = match self.last_distance {
+            Some(last) if distance < last => Some("warmer"),
+            Some(last) if distance > last => Some("colder"),
+            _ => None,
+        };
+        self.last_distance = Some(distance);
+        match trend {
+            Some(trend) => {
+                let args = [
+                    ("temperature", &temperature as _),
+                    ("trend", &locale.text(trend) as _),
+                ];
+                writeln!(output, "{}", locale.format("temperature-trend", &args))?
+            }
+            None => writeln!(output, "{}", temperature)?,
+        }
+        report_loss(&self.game, locale, output)
+    }
+
+    fn state(&self) -> State {
+        self.game.state()
+    }
+}
+
+/// Points the player starts a `Hints` game with.
+pub const START_POINTS: u32 = 100;
+/// What a wrong guess costs.
+pub const GUESS_COST: u32 = 5;
+/// What asking whether the number is even costs.
+pub const PARITY_COST: u32 = 10;
+/// What asking whether the number is divisible by something costs.
+pub const DIVISIBILITY_COST: u32 = 15;
+
+/// The classic game scored in points, where the player can also buy hints
+/// by typing `parity` or `div N` instead of a guess.
+pub struct Hints {
+    game: Game,
+    points: u32,
+}
+
+impl Hints {
+    pub fn new(game: Game) -> Hints {
+        Hints {
+            game,
+            points: START_POINTS,
+        }
+    }
+
+    pub fn points(&self) -> u32 {
+        self.points
+    }
+
+    fn spend(
+        &mut self,
+        cost: u32,
+        hint: &str,
+        locale: Locale,
+        output: &mut dyn Write,
+    ) -> io::Result<()> {
+        self.points = self.points.saturating_sub(cost);
+        let args = [
+            ("hint", &hint as _),
+            ("cost", &cost as _),
+            ("points", &self.points as _),
+        ];
+        writeln!(output, "{}", locale.format("hint-cost", &args))
+    }
+}
+
+impl Variant for Hints {
+    fn intro(&self, locale: Locale, output: &mut dyn Write) -> io::Result<()> {
+        self.game.intro(locale, output)?;
+        let args = [("points", &self.points as _), ("cost", &GUESS_COST as _)];
+        writeln!(output, "{}", locale.format("points-intro", &args))?;
+        let args = [
+            ("parity", &PARITY_COST as _),
+            ("divisibility", &DIVISIBILITY_COST as _),
+        ];
+        writeln!(output, "{}", locale.format("hints-intro", &args))
+    }
+
+    fn prompt(&self, locale: Locale, output: &mut dyn Write) -> io::Result<()> {
+        self.game.prompt(locale, output)
+    }
+
+    fn respond(&mut self, line: &str, locale: Locale, output: &mut dyn Write) -> io::Result<()> {
+        let secret = self.game.secret();
+        if line == "parity" {
+            let hint = if secret.is_multiple_of(2) {
+                "even"
+            } else {
+                "odd"
+            };
+            return self.spend(PARITY_COST, locale.text(hint), locale, output);
+        }
+        if let Some(divisor) = line.strip_prefix("div ") {
+            return match divisor.trim().parse::<u32>() {
+                Ok(divisor) if divisor >= 2 => {
+                    let key = if secret.is_multiple_of(divisor) {
+                        "divisible"
+                    } else {
+                        "not-divisible"
+                    };
+                    let hint = locale.format(key, &[("divisor", &divisor)]);
+                    self.spend(DIVISIBILITY_COST, &hint, locale, output)
+                }
+                _ => writeln!(output, "{}", locale.text("div-usage")),
+            };
+        }
+
+        let key = match guess_line(&mut self.game, line, locale, output)? {
+            Some((_, Outcome::Correct)) => {
+                let points = [("points", &self.points as _)];
+                return writeln!(output, "{}", locale.format("win-with-points", &points));
+            }
+            Some((_, Outcome::TooSmall)) => "too-small",
+            Some(_) => "too-big",
+            None => return Ok(()),
+        };
+        writeln!(output, "{}", locale.text(key))?;
+        self.points = self.points.saturating_sub(GUESS_COST);
+        report_loss(&self.game, locale, output)
+    }
+
+    fn state(&self) -> State {
+        self.game.state()
+    }
+}
+
+/// The player picks the number and answers the computer's guesses. The computer wins
+/// when it finds the number, and loses when it runs out of attempts or the answers
+/// contradict each other.
+pub struct Reverse {
+    config: GameConfig,
+    solver: Solver,
+    guess: Option<u32>,
+    attempts: u32,
+    state: State,
+}
+
+impl Reverse {
+    pub fn new(config: GameConfig) -> Reverse {
+        let solver = Solver::new(&config);
+        Reverse {
+            guess: solver.next_guess(),
+            config,
+            solver,
+            attempts: 0,
+            state: State::Playing,
+        }
+    }
+
+    /// Guesses made so far.
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+}
+
+/// What `answer` says about the last guess, in the words of `locale` or in English.
+fn parse_answer(answer: &str, locale: Locale) -> Option<Outcome> {
+    let answer = answer.to_lowercase();
+    // the answers say where the secret is, so "higher" means the guess was too small
+    let outcomes = [
+        ("answers-higher", Outcome::TooSmall),
+        ("answers-lower", Outcome::TooBig),
+        ("answers-correct", Outcome::Correct),
+    ];
+    for &(key, outcome) in outcomes.iter() {
+        let words = locale.text(key).split_whitespace();
+        if words
+            .chain(Locale::En.text(key).split_whitespace())
+            .any(|word| word == answer)
+        {
+            return Some(outcome);
+        }
+    }
+    None
+}
+
+impl Variant for Reverse {
+    fn intro(&self, locale: Locale, output: &mut dyn Write) -> io::Result<()> {
+        let range = [
+            ("min", &self.config.min as _),
+            ("max", &self.config.max as _),
+        ];
+        writeln!(output, "{}", locale.format("reverse-intro", &range))?;
+        writeln!(output, "{}", locale.text("reverse-help"))?;
+        if let Some(max_attempts) = self.config.max_attempts {
+            let attempts = [("attempts", &max_attempts as _)];
+            writeln!(output, "{}", locale.format("reverse-attempts", &attempts))?;
+        }
+        Ok(())
+    }
+
+    fn prompt(&self, locale: Locale, output: &mut dyn Write) -> io::Result<()> {
+        match self.guess {
+            Some(guess) => writeln!(
+                output,
+                "{}",
+                locale.format("reverse-guess", &[("guess", &guess)])
+            ),
+            None => Ok(()),
+        }
+    }
+
+    fn respond(&mut self, line: &str, locale: Locale, output: &mut dyn Write) -> io::Result<()> {
+        let guess = match self.guess {
+            Some(guess) => guess,
+            None => return Ok(()),
+        };
+        let outcome = match parse_answer(line, locale) {
+            Some(outcome) => outcome,
+            None => return writeln!(output, "{}", locale.text("reverse-answer-help")),
+        };
+        self.attempts += 1;
+
+        if outcome == Outcome::Correct {
+            self.state = State::Won;
+            let attempts = [("attempts", &self.attempts as _)];
+            return writeln!(output, "{}", locale.format("reverse-won", &attempts));
+        }
+        self.solver.feedback(guess, outcome);
+        self.guess = self.solver.next_guess();
+
+        if self.guess.is_none() {
+            self.state = State::Lost;
+            writeln!(output, "{}", locale.text("reverse-contradiction"))
+        } else if Some(self.attempts) == self.config.max_attempts {
+            self.state = State::Lost;
+            writeln!(output, "{}", locale.text("reverse-give-up"))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn state(&self) -> State {
+        self.state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Hints, HotCold, Reverse, VariantKind};
+    use crate::config::GameConfig;
+    use crate::game::{run, seeded, Game, State, Variant};
+    use crate::i18n::Locale;
+    use std::io::Cursor;
+
+    fn session<V: Variant + ?Sized>(variant: &mut V, input: &str) -> (State, String) {
+        session_in(Locale::En, variant, input)
+    }
+
+    fn session_in<V: Variant + ?Sized>(
+        locale: Locale,
+        variant: &mut V,
+        input: &str,
+    ) -> (State, String) {
+        let mut output = Vec::new();
+        let state = run(variant, locale, Cursor::new(input), &mut output).unwrap();
+        (state, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn kinds() {
+        assert_eq!(Some(VariantKind::HotCold), VariantKind::parse("Hot-Cold"));
+        assert_eq!(Some(VariantKind::Reverse), VariantKind::parse("reverse"));
+        assert_eq!(None, VariantKind::parse("hot"));
+
+        let mut rng = seeded(1);
+        let mut classic = VariantKind::Classic.start(GameConfig::default(), &mut rng);
+        let (_, output) = session(&mut *classic, "");
+        assert!(output.starts_with("Guess the number!\n"));
+    }
+
+    #[test]
+    fn hot_cold() {
+        let mut game = HotCold::new(Game::with_secret(GameConfig::default(), 40));
+        assert_eq!("hot", game.temperature(5));
+        assert_eq!("warm", game.temperature(6));
+        assert_eq!("cold", game.temperature(35));
+        assert_eq!("freezing", game.temperature(36));
+
+        let (state, output) = session(&mut game, "100\n70\n90\n35\n40\n");
+        assert_eq!(State::Won, state);
+        assert_eq!(
+            "\
+Guess the number!
+It's between 1 and 100.
+I'll tell you how hot or cold each guess is.
+Please input your guess.
+You guessed: 100
+Freezing!
+Please input your guess.
+You guessed: 70
+Cold. Getting warmer.
+Please input your guess.
+You guessed: 90
+Freezing! Getting colder.
+Please input your guess.
+You guessed: 35
+Hot! Getting warmer.
+Please input your guess.
+You guessed: 40
+You win!
+",
+            output
+        );
+    }
+
+    #[test]
+    fn hints_cost_points() {
+        let mut game = Hints::new(Game::with_secret(GameConfig::default(), 42));
+        let (state, output) = session(&mut game, "parity\ndiv 7\ndiv 5\ndiv 1\n50\n101\n42\n");
+
+        assert_eq!(State::Won, state);
+        assert_eq!(100 - 10 - 15 - 15 - 5, game.points());
+        assert!(output.contains("It's even. (-10 points, 90 left)\n"));
+        assert!(output.contains("It's divisible by 7. (-15 points, 75 left)\n"));
+        assert!(output.contains("It's not divisible by 5. (-15 points, 60 left)\n"));
+        assert!(output.contains("Try `div N` with N of 2 or more.\n"));
+        assert!(output.ends_with("You guessed: 42\nYou win with 55 points!\n"));
+
+        // points don't go below nothing
+        let mut game = Hints::new(Game::with_secret(GameConfig::default(), 1));
+        session(&mut game, &"parity\n".repeat(11));
+        assert_eq!(0, game.points());
+    }
+
+    #[test]
+    fn reverse() {
+        let mut game = Reverse::new(GameConfig::default());
+        let (state, output) = session(&mut game, "lower\nwhat?\nh\nHigher\ncorrect\n");
+
+        assert_eq!(State::Won, state);
+        assert_eq!(4, game.attempts());
+        assert_eq!(
+            "\
+Think of a number between 1 and 100 and I'll guess it.
+Answer each guess with higher, lower or correct.
+Is it 50?
+Is it 25?
+Please answer higher, lower or correct.
+Is it 25?
+Is it 37?
+Is it 43?
+Got it in 4 attempts!
+",
+            output
+        );
+    }
+
+    #[test]
+    fn reverse_in_chinese() {
+        let mut game = Reverse::new(GameConfig::default());
+        let (state, output) = session_in(Locale::Zh, &mut game, "小\nhigher\n对\n");
+
+        assert_eq!(State::Won, state);
+        assert!(output.contains("是 50 吗？\n是 25 吗？\n是 37 吗？\n"));
+        assert!(output.ends_with("我猜了 3 次就猜中了！\n"));
+    }
+
+    #[test]
+    fn reverse_catches_contradictions_and_gives_up() {
+        let mut game = Reverse::new(GameConfig::new(1, 3, None).unwrap());
+        let (state, output) = session(&mut game, "higher\nlower\n");
+        assert_eq!(State::Lost, state);
+        assert!(output.ends_with("That can't be right, no number fits your answers.\n"));
+
+        let mut game = Reverse::new(GameConfig::new(1, 100, Some(2)).unwrap());
+        let (state, output) = session(&mut game, "+\n+\n+\n");
+        assert_eq!(State::Lost, state);
+        assert!(output.ends_with("Is it 75?\nI give up, you win!\n"));
+    }
+}
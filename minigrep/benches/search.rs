@@ -0,0 +1,44 @@
+// a plain `harness = false` benchmark so it runs on stable without extra dependencies
+// cargo bench
+// MINIGREP_SAVE_BASELINE=1 cargo bench   (rewrites benches/baseline.txt)
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use std::path::Path;
+use std::time::Duration;
+
+const CORPUS_SIZE: usize = 8 * 1024 * 1024;
+
+fn main() {
+    let baseline = common::load_baseline(Path::new(common::BASELINE_FILE));
+    let mut results = Vec::new();
+
+    println!(
+        "{:<36} {:>12} {:>12} {:>8} {:>8}",
+        "benchmark", "MB/s", "baseline", "change", "speedup"
+    );
+    for mut bench in common::benches(CORPUS_SIZE) {
+        let (mbps, speedup) = common::measure(&mut bench, Duration::from_secs(2));
+        match baseline.get(&bench.name) {
+            Some(base) => println!(
+                "{:<36} {:>12.1} {:>12.1} {:>+7.1}% {:>7.2}x",
+                bench.name,
+                mbps,
+                base.mbps,
+                (mbps / base.mbps - 1.0) * 100.0,
+                speedup
+            ),
+            None => println!(
+                "{:<36} {:>12.1} {:>12} {:>8} {:>7.2}x",
+                bench.name, mbps, "-", "-", speedup
+            ),
+        }
+        results.push((bench.name, common::Baseline { mbps, speedup }));
+    }
+
+    if std::env::var("MINIGREP_SAVE_BASELINE").is_ok() {
+        common::save_baseline(Path::new(common::BASELINE_FILE), &results)
+            .expect("failed to save baseline");
+        println!("saved baseline to {}", common::BASELINE_FILE);
+    }
+}
@@ -1,78 +1,267 @@
 use std::env;
-use std::env::Args;
 use std::error::Error;
 use std::fs;
+use std::io::{self, Write};
+
+mod literal;
+pub mod server;
+pub mod tui;
 
 pub struct Config {
     pub query: String,
-    pub filename: String,
+    pub filenames: Vec<String>,
     pub case_sensitive: bool,
+    // browse the hits in the terminal instead of printing them
+    pub interactive: bool,
+    // stop reading a file after this many matching lines
+    pub max_count: Option<usize>,
+    // stop the whole search after this many matching lines
+    pub limit: Option<usize>,
+    // print nothing and stop at the first match, only the exit status tells whether there was one
+    pub quiet: bool,
 }
 impl Config {
-    // env:args() returns an iterator
-    pub fn new(mut args: Args) -> Result<Config, &'static str> {
+    // env:args() returns an iterator, taking any iterator of strings lets tests build a Config too
+    pub fn new(mut args: impl Iterator<Item = String>) -> Result<Config, &'static str> {
         args.next();
 
-        let query = match args.next() {
+        let mut interactive = false;
+        let mut max_count = None;
+        let mut limit = None;
+        let mut quiet = false;
+        let mut positional = Vec::new();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-I" | "--interactive" => interactive = true,
+                "-q" | "--quiet" => quiet = true,
+                "-m" | "--max-count" => {
+                    max_count = Some(count(args.next(), "--max-count needs a number")?)
+                }
+                "--limit" => limit = Some(count(args.next(), "--limit needs a number")?),
+                _ => positional.push(arg),
+            }
+        }
+        let mut positional = positional.into_iter();
+
+        let query = match positional.next() {
             Some(arg) => arg,
             None => return Err("Didn't get a query string"),
         };
 
-        let filename = match args.next() {
-            Some(arg) => arg,
-            None => return Err("Didn't get a file name"),
-        };
+        let filenames: Vec<String> = positional.collect();
+        if filenames.is_empty() {
+            return Err("Didn't get a file name");
+        }
+        if interactive && filenames.len() > 1 {
+            return Err("Interactive mode takes a single file");
+        }
 
         // we don't care about the value of the env var, just whether it's set or unset
         let case_sensitive = env::var("CASE_INSENSITIVE").is_err();
 
         Ok(Config {
             query,
-            filename,
+            filenames,
             case_sensitive,
+            interactive,
+            max_count,
+            limit,
+            quiet,
         })
     }
 }
 
+fn count(arg: Option<String>, err: &'static str) -> Result<usize, &'static str> {
+    arg.and_then(|arg| arg.parse().ok()).ok_or(err)
+}
+
 // trait object: Box<dyn Error>, means the function will return a type that implements the Error trait,
 // but we don't have to specify what particular type the return value will be.
 // This gives us flexibility to return error values may be of different types in different error cases.
-pub fn run(config: Config) -> Result<(), Box<dyn Error>> {
-    let contents = fs::read_to_string(config.filename)?;
+// Returns whether any line matched.
+pub fn run(config: Config) -> Result<bool, Box<dyn Error>> {
+    if config.interactive {
+        let filename = &config.filenames[0];
+        let contents = fs::read_to_string(filename)?;
+        // only the picked hit goes to stdout, so editors can read it
+        return match tui::browse(&config.query, &contents, config.case_sensitive)? {
+            Some(line) => {
+                println!("{}:{}", filename, line);
+                Ok(true)
+            }
+            None => Ok(false),
+        };
+    }
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    search_files(&config, &mut out)
+}
+
+fn search_files<W: Write>(config: &Config, out: &mut W) -> Result<bool, Box<dyn Error>> {
+    // like grep, only prefix lines with their file when there's more than one
+    let with_filename = config.filenames.len() > 1;
+    let mut remaining = config.limit.unwrap_or(usize::MAX);
+    let mut matched = false;
+
+    for filename in &config.filenames {
+        if remaining == 0 {
+            break;
+        }
+        let contents = fs::read_to_string(filename)?;
 
-    let results = if config.case_sensitive {
-        search(&config.query, &contents)
-    } else {
-        search_case_insensitive(&config.query, &contents)
-    };
+        // the iterators are lazy, so stopping here also stops the search itself
+        let results: Box<dyn Iterator<Item = &str>> = if config.case_sensitive {
+            Box::new(search_iter(&config.query, &contents))
+        } else {
+            Box::new(search_case_insensitive_iter(&config.query, &contents))
+        };
+        let take = config.max_count.unwrap_or(usize::MAX).min(remaining);
 
-    println!("{}", results.join("\n"));
+        for line in results.take(take) {
+            matched = true;
+            if config.quiet {
+                return Ok(true);
+            }
+            if with_filename {
+                writeln!(out, "{}:{}", filename, line)?;
+            } else {
+                writeln!(out, "{}", line)?;
+            }
+            remaining -= 1;
+        }
+    }
 
-    Ok(())
+    Ok(matched)
 }
 
 pub fn search<'a>(query: &'a str, contents: &'a str) -> Vec<&'a str> {
-    // the iterator performance is faster than for loop
-    // iterators, although a high-level abstraction, get complied down to roughly the same code
-    // as if you'd written the lower-level code. `zero-cost abstractions`
-    contents
-        .lines()
-        .filter(|line| line.contains(query))
-        .collect()
+    search_iter(query, contents).collect()
 }
 
 pub fn search_case_insensitive<'a>(query: &'a str, contents: &'a str) -> Vec<&'a str> {
+    search_case_insensitive_iter(query, contents).collect()
+}
+
+/// Lazily yields the lines of `contents` containing `query`, so callers can stop early.
+pub fn search_iter<'a>(query: &'a str, contents: &'a str) -> impl Iterator<Item = &'a str> {
+    // same result as `contents.lines().filter(|line| line.contains(query))`, but instead of
+    // visiting every line we scan the whole buffer for the query and only then find its line
+    literal::MatchingLines::new(query, contents)
+}
+
+/// Like `search_iter`, ignoring case.
+pub fn search_case_insensitive_iter<'a>(
+    query: &str,
+    contents: &'a str,
+) -> impl Iterator<Item = &'a str> {
     let query = query.to_lowercase();
 
     contents
         .lines()
-        .filter(|line| line.to_lowercase().contains(&query))
-        .collect()
+        .filter(move |line| line.to_lowercase().contains(&query))
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{search, search_case_insensitive};
+    use crate::{search, search_case_insensitive, search_files, search_iter, Config};
+
+    fn args(args: &[&str]) -> impl Iterator<Item = String> {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        args.into_iter()
+    }
+
+    fn output(config: &Config) -> (bool, String) {
+        let mut out = Vec::new();
+        let matched = search_files(config, &mut out).unwrap();
+        (matched, String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn config_from_args() {
+        let config = Config::new(args(&["minigrep", "duct", "poem.txt"])).unwrap();
+        assert_eq!("duct", config.query);
+        assert_eq!(vec!["poem.txt"], config.filenames);
+        assert!(!config.interactive);
+        assert!(!config.quiet);
+        assert_eq!(None, config.max_count);
+        assert_eq!(None, config.limit);
+
+        let config = Config::new(args(&["minigrep", "--interactive", "duct", "poem.txt"])).unwrap();
+        assert!(config.interactive);
+        assert_eq!("duct", config.query);
+
+        let config = Config::new(args(&[
+            "minigrep",
+            "-q",
+            "-m",
+            "2",
+            "to",
+            "poem.txt",
+            "--limit",
+            "3",
+            "output.txt",
+        ]))
+        .unwrap();
+        assert!(config.quiet);
+        assert_eq!(Some(2), config.max_count);
+        assert_eq!(Some(3), config.limit);
+        assert_eq!(vec!["poem.txt", "output.txt"], config.filenames);
+
+        assert!(Config::new(args(&["minigrep", "-I", "duct"])).is_err());
+        assert!(Config::new(args(&["minigrep", "-I", "duct", "a.txt", "b.txt"])).is_err());
+        assert!(Config::new(args(&["minigrep", "--max-count", "many", "duct", "a.txt"])).is_err());
+        assert!(Config::new(args(&["minigrep", "duct", "a.txt", "--limit"])).is_err());
+    }
+
+    #[test]
+    fn search_iter_is_lazy() {
+        let contents = "one\ntwo\nthree\ntwo again";
+        let mut results = search_iter("two", contents);
+        assert_eq!(Some("two"), results.next());
+        assert_eq!(Some("two again"), results.next());
+        assert_eq!(None, results.next());
+    }
+
+    #[test]
+    fn max_count_per_file() {
+        let mut config = Config::new(args(&["minigrep", "-m", "1", "body", "poem.txt"])).unwrap();
+        config.case_sensitive = true;
+        assert_eq!(
+            (true, String::from("I'm nobody! Who are you?\n")),
+            output(&config)
+        );
+
+        config.filenames.push(String::from("poem.txt"));
+        let (_, out) = output(&config);
+        assert_eq!(
+            "poem.txt:I'm nobody! Who are you?\npoem.txt:I'm nobody! Who are you?\n",
+            out
+        );
+    }
+
+    #[test]
+    fn global_limit() {
+        let mut config = Config::new(args(&[
+            "minigrep", "--limit", "3", "body", "poem.txt", "poem.txt",
+        ]))
+        .unwrap();
+        config.case_sensitive = true;
+        let (matched, out) = output(&config);
+        assert!(matched);
+        assert_eq!(3, out.lines().count());
+        assert!(out.lines().all(|line| line.starts_with("poem.txt:")));
+    }
+
+    #[test]
+    fn quiet_prints_nothing() {
+        let mut config = Config::new(args(&["minigrep", "-q", "frog", "poem.txt"])).unwrap();
+        config.case_sensitive = true;
+        assert_eq!((true, String::new()), output(&config));
+
+        config.query = String::from("no such line");
+        assert_eq!((false, String::new()), output(&config));
+    }
 
     #[test]
     fn one_result() {
@@ -0,0 +1,386 @@
+// Fast literal search over a whole buffer.
+//
+// Instead of splitting every line and calling `contains` on each one, we scan for the rarest
+// byte of the query paired with one of its end bytes, sixteen bytes at a time with SSE2 on
+// x86_64 and a word at a time (SWAR) elsewhere, verify the full query at each candidate, and
+// only then expand outwards to the enclosing line. Lines without a candidate are never looked
+// at individually, which is where the speedup on large files comes from.
+
+use std::mem::size_of;
+
+const LO: usize = usize::MAX / 255; // 0x0101...01
+const HI: usize = LO * 0x80; // 0x8080...80
+const WORD: usize = size_of::<usize>();
+
+// bytes in roughly descending order of frequency in text and logs;
+// anything not listed is treated as rarer than everything listed
+const FREQUENT: &[u8] = b" etaoinsrhldcumfpgwybvkxjqz\n\
+    ETAOINSRHLDCUMFPGWYBVKXJQZ0123456789.,-_:/=\"'()[]{}\t;<>";
+
+/// How common `byte` is, higher means more common.
+fn rank(byte: u8) -> usize {
+    match FREQUENT.iter().position(|&b| b == byte) {
+        Some(i) => 2 * FREQUENT.len() - i,
+        // UTF-8 continuation bytes show up in every non-ASCII character
+        None if (0x80..0xc0).contains(&byte) => FREQUENT.len() + 1,
+        // leading bytes are shared by whole scripts, e.g. 0xe4..0xe9 for most CJK
+        None if byte >= 0xc0 => FREQUENT.len(),
+        None => 0,
+    }
+}
+
+// marks every zero byte of `x` with its high bit; a 0x01 byte right above a zero byte can be
+// marked too, which is fine for us because every candidate gets verified anyway
+#[inline(always)]
+fn zero_bytes(x: usize) -> usize {
+    x.wrapping_sub(LO) & !x & HI
+}
+
+#[inline(always)]
+fn contains_zero_byte(x: usize) -> bool {
+    zero_bytes(x) != 0
+}
+
+#[inline(always)]
+fn read_word(bytes: &[u8], at: usize) -> usize {
+    let mut buf = [0u8; WORD];
+    buf.copy_from_slice(&bytes[at..at + WORD]);
+    usize::from_ne_bytes(buf)
+}
+
+// offset in memory of the lowest-addressed byte marked in `mask`, and `mask` without it
+#[inline(always)]
+fn pop_first_byte(mask: usize) -> (usize, usize) {
+    if cfg!(target_endian = "little") {
+        (mask.trailing_zeros() as usize / 8, mask & (mask - 1))
+    } else {
+        let bit = usize::BITS - 1 - mask.leading_zeros();
+        ((usize::BITS - 1 - bit) as usize / 8, mask ^ (1 << bit))
+    }
+}
+
+/// Returns the index of the first `needle` byte in `haystack`.
+pub fn memchr(needle: u8, haystack: &[u8]) -> Option<usize> {
+    let splat = LO * needle as usize;
+    let mut i = 0;
+
+    while i + WORD <= haystack.len() {
+        if contains_zero_byte(read_word(haystack, i) ^ splat) {
+            break;
+        }
+        i += WORD;
+    }
+
+    haystack[i..]
+        .iter()
+        .position(|&b| b == needle)
+        .map(|pos| i + pos)
+}
+
+/// Returns the index of the last `needle` byte in `haystack`.
+pub fn memrchr(needle: u8, haystack: &[u8]) -> Option<usize> {
+    let splat = LO * needle as usize;
+    let mut end = haystack.len();
+
+    while end >= WORD {
+        if contains_zero_byte(read_word(haystack, end - WORD) ^ splat) {
+            break;
+        }
+        end -= WORD;
+    }
+
+    haystack[..end].iter().rposition(|&b| b == needle)
+}
+
+/// A precomputed literal search for one query.
+pub struct Finder<'q> {
+    needle: &'q [u8],
+    // indexes into `needle` of the two bytes every candidate must have
+    rare1: usize,
+    rare2: usize,
+}
+
+impl<'q> Finder<'q> {
+    pub fn new(needle: &'q str) -> Finder<'q> {
+        let needle = needle.as_bytes();
+
+        let rare1 = (0..needle.len()).min_by_key(|&i| rank(needle[i])).unwrap_or(0);
+        // pair it with a byte at one end of the query, where it's least correlated with
+        // `rare1`, e.g. for "dogs" in text full of "dog" we want the `s` rather than the `d`
+        let last = needle.len().saturating_sub(1);
+        let rare2 = if rare1 == last { 0 } else { last };
+
+        Finder {
+            needle,
+            rare1,
+            rare2,
+        }
+    }
+
+    /// Returns the start of the first occurrence of the query at or after `start`.
+    pub fn find(&self, haystack: &[u8], start: usize) -> Option<usize> {
+        let needle = self.needle;
+        if needle.is_empty() {
+            return if start <= haystack.len() { Some(start) } else { None };
+        }
+        if haystack.len() < needle.len() {
+            return None;
+        }
+
+        // the last position a match could start at
+        let last = haystack.len() - needle.len();
+        let (i1, i2) = (self.rare1, self.rare2);
+        let (b1, b2) = (needle[i1], needle[i2]);
+        let (splat1, splat2) = (LO * b1 as usize, LO * b2 as usize);
+
+        // test WORD candidate starts at once: a byte of the mask is marked when the candidate
+        // starting there has both filter bytes in the right places
+        let candidates = |at: usize| {
+            zero_bytes(read_word(haystack, at + i1) ^ splat1)
+                & zero_bytes(read_word(haystack, at + i2) ^ splat2)
+        };
+        let verify = |at: usize, mut mask: usize| {
+            while mask != 0 {
+                let (offset, rest) = pop_first_byte(mask);
+                let candidate = at + offset;
+                if &haystack[candidate..candidate + needle.len()] == needle {
+                    return Some(candidate);
+                }
+                mask = rest;
+            }
+            None
+        };
+
+        #[allow(unused_mut)]
+        let mut pos = start;
+
+        #[cfg(target_arch = "x86_64")]
+        match sse2::find(haystack, needle, (i1, b1), (i2, b2), pos) {
+            Ok(found) => return Some(found),
+            Err(reached) => pos = reached,
+        }
+
+        // four words per iteration keeps the loop branch out of the way when candidates are rare
+        while pos + 4 * WORD <= last + 1 {
+            let masks = [
+                candidates(pos),
+                candidates(pos + WORD),
+                candidates(pos + 2 * WORD),
+                candidates(pos + 3 * WORD),
+            ];
+            if masks[0] | masks[1] | masks[2] | masks[3] != 0 {
+                for (i, &mask) in masks.iter().enumerate() {
+                    if let Some(found) = verify(pos + i * WORD, mask) {
+                        return Some(found);
+                    }
+                }
+            }
+            pos += 4 * WORD;
+        }
+        while pos + WORD <= last + 1 {
+            if let Some(found) = verify(pos, candidates(pos)) {
+                return Some(found);
+            }
+            pos += WORD;
+        }
+
+        (pos..=last).find(|&candidate| {
+            haystack[candidate + i1] == b1
+                && haystack[candidate + i2] == b2
+                && &haystack[candidate..candidate + needle.len()] == needle
+        })
+    }
+}
+
+// SSE2 is part of the x86_64 baseline, so this needs no runtime feature detection
+#[cfg(target_arch = "x86_64")]
+mod sse2 {
+    use std::arch::x86_64::{
+        __m128i, _mm_and_si128, _mm_cmpeq_epi8, _mm_loadu_si128, _mm_movemask_epi8, _mm_set1_epi8,
+    };
+
+    const LANES: usize = 16;
+
+    /// The same two-byte filter as the SWAR loop, sixteen candidates at a time.
+    ///
+    /// Returns `Err` with the first position left unchecked when fewer than sixteen remain.
+    pub fn find(
+        haystack: &[u8],
+        needle: &[u8],
+        (i1, b1): (usize, u8),
+        (i2, b2): (usize, u8),
+        mut pos: usize,
+    ) -> Result<usize, usize> {
+        let last = haystack.len() - needle.len();
+
+        // SAFETY: SSE2 is always available on x86_64, and both loads end at most at
+        // `pos + LANES - 1 + needle.len() - 1`, which is `<= last + needle.len() - 1`,
+        // the last byte of `haystack`
+        unsafe {
+            let splat1 = _mm_set1_epi8(b1 as i8);
+            let splat2 = _mm_set1_epi8(b2 as i8);
+
+            while pos + LANES <= last + 1 {
+                let at = haystack.as_ptr().add(pos);
+                let a = _mm_loadu_si128(at.add(i1) as *const __m128i);
+                let b = _mm_loadu_si128(at.add(i2) as *const __m128i);
+                let both = _mm_and_si128(_mm_cmpeq_epi8(a, splat1), _mm_cmpeq_epi8(b, splat2));
+
+                let mut mask = _mm_movemask_epi8(both) as u32;
+                while mask != 0 {
+                    let candidate = pos + mask.trailing_zeros() as usize;
+                    if &haystack[candidate..candidate + needle.len()] == needle {
+                        return Ok(candidate);
+                    }
+                    mask &= mask - 1;
+                }
+                pos += LANES;
+            }
+        }
+
+        Err(pos)
+    }
+}
+
+/// An iterator over the lines of `contents` that contain the query, in order.
+///
+/// Lines are split exactly like `str::lines`: on `\n`, with a trailing `\r` removed.
+pub struct MatchingLines<'q, 'a> {
+    finder: Finder<'q>,
+    contents: &'a str,
+    pos: usize,
+}
+
+impl<'q, 'a> MatchingLines<'q, 'a> {
+    pub fn new(query: &'q str, contents: &'a str) -> MatchingLines<'q, 'a> {
+        // a line never contains `\n`, so such a query can't match anything
+        let pos = if query.contains('\n') {
+            contents.len()
+        } else {
+            0
+        };
+
+        MatchingLines {
+            finder: Finder::new(query),
+            contents,
+            pos,
+        }
+    }
+}
+
+impl<'q, 'a> Iterator for MatchingLines<'q, 'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        let bytes = self.contents.as_bytes();
+
+        while self.pos < bytes.len() {
+            let found = self.finder.find(bytes, self.pos)?;
+
+            let line_start = memrchr(b'\n', &bytes[..found]).map_or(0, |i| i + 1);
+            let (line_end, next) = match memchr(b'\n', &bytes[found..]) {
+                Some(i) => (found + i, found + i + 1),
+                None => (bytes.len(), bytes.len()),
+            };
+            self.pos = next;
+
+            let mut content_end = line_end;
+            if line_end < bytes.len() && line_end > line_start && bytes[line_end - 1] == b'\r' {
+                content_end -= 1;
+            }
+
+            // the match may run into the `\r` that `str::lines` strips, in which case
+            // no later match on this line can fit either
+            if found + self.finder.needle.len() <= content_end {
+                // both ends sit next to a `\n` or the ends of the buffer, so they're char boundaries
+                return Some(&self.contents[line_start..content_end]);
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{memchr, memrchr, MatchingLines};
+
+    fn naive<'a>(query: &str, contents: &'a str) -> Vec<&'a str> {
+        contents
+            .lines()
+            .filter(|line| line.contains(query))
+            .collect()
+    }
+
+    #[test]
+    fn memchr_finds_every_position() {
+        let haystack: Vec<u8> = (0..100).collect();
+        for needle in 0..100 {
+            for start in 0..=needle {
+                assert_eq!(Some(needle - start), memchr(needle as u8, &haystack[start..]));
+            }
+            for end in needle + 1..=100 {
+                assert_eq!(Some(needle), memrchr(needle as u8, &haystack[..end]));
+            }
+        }
+        assert_eq!(None, memchr(200, &haystack));
+        assert_eq!(None, memrchr(200, &haystack));
+        assert_eq!(None, memchr(b'a', b""));
+    }
+
+    #[test]
+    fn same_lines_as_str_lines() {
+        let contents = "\
+Rust:
+safe, fast, productive.\r
+Pick three.
+Duct tape.\r
+\r
+
+duct duct duct
+über straße 日本語
+trailing duct";
+
+        let queries = [
+            "duct", "Duct", "t", "", " ", "\r", "e.\r", "tape.", "straße", "日本", "本語", ":",
+            "duct\n", "nope", "trailing duct", "Rust:\nsafe",
+        ];
+        for query in queries.iter() {
+            assert_eq!(
+                naive(query, contents),
+                MatchingLines::new(query, contents).collect::<Vec<_>>(),
+                "query {:?}",
+                query
+            );
+        }
+    }
+
+    #[test]
+    fn matches_at_the_edges() {
+        assert_eq!(vec!["ab"], MatchingLines::new("ab", "ab").collect::<Vec<_>>());
+        assert_eq!(vec!["ab"], MatchingLines::new("b", "\nab\n").collect::<Vec<_>>());
+        assert_eq!(Vec::<&str>::new(), MatchingLines::new("abc", "ab").collect::<Vec<_>>());
+        assert_eq!(Vec::<&str>::new(), MatchingLines::new("a", "").collect::<Vec<_>>());
+        assert_eq!(vec!["", ""], MatchingLines::new("", "\n\n").collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn long_haystacks() {
+        let line = "the quick brown fox jumps over the lazy dog ".repeat(40);
+        let mut contents = String::new();
+        for i in 0..200 {
+            contents.push_str(&line);
+            if i % 7 == 0 {
+                contents.push_str("needle");
+            }
+            contents.push('\n');
+        }
+
+        for query in ["needle", "dog needle", "fox", "zebra", "e"].iter() {
+            assert_eq!(
+                naive(query, &contents),
+                MatchingLines::new(query, &contents).collect::<Vec<_>>()
+            );
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use minigrep::Config;
-use std::{env, process};
+use std::{env, io, process};
 
 // Separation of Concerns for Binary Projects
 // 1.split your program into a main.rs and a lib.rs and move you program's logic to lib.rs
@@ -13,6 +13,19 @@ use std::{env, process};
 // Handling the error if run returns an error
 
 fn main() {
+    // a search always needs a file, so a lone `serve` can't be mistaken for one
+    if env::args()
+        .skip(1)
+        .eq(["serve"].iter().map(|arg| arg.to_string()))
+    {
+        let stdin = io::stdin();
+        if let Err(e) = minigrep::server::serve(stdin.lock(), io::stdout()) {
+            eprintln!("Server error: {}", e);
+            process::exit(2);
+        }
+        return;
+    }
+
     // env::args() return an iter and can consume values in it thus not to borrow string value
     let config = Config::new(env::args()).unwrap_or_else(|err| {
         // stdout
@@ -20,11 +33,18 @@ fn main() {
         // stderr
         eprintln!("Problem parsing arguments: {}", err);
         // return type ! can match any type.
-        process::exit(1);
+        // exit status 2 like grep, 1 is left for a quiet search that found nothing
+        process::exit(2);
     });
 
-    if let Err(e) = minigrep::run(config) {
-        eprintln!("Application error: {}", e);
-        process::exit(1);
+    let quiet = config.quiet;
+    match minigrep::run(config) {
+        // like grep, a quiet search reports whether anything matched through its exit status
+        Ok(false) if quiet => process::exit(1),
+        Ok(_) => {}
+        Err(e) => {
+            eprintln!("Application error: {}", e);
+            process::exit(2);
+        }
     }
 }
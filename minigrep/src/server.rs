@@ -0,0 +1,713 @@
+// `minigrep serve`: JSON-RPC 2.0 over stdio, framed like the Language Server Protocol
+//
+//     Content-Length: <bytes>\r\n
+//     \r\n
+//     <json>
+//
+// Requests
+//   search    {"query", "paths": [...], "caseSensitive"?, "maxCount"?, "limit"?}
+//             streams a `search/match` notification {"id", "path", "line", "text"} per hit,
+//             then responds with {"matches", "errors": [{"path", "message"}]},
+//             or with the RequestCancelled error if it was cancelled first
+//   shutdown  responds with null, after which only `exit` is accepted
+// Notifications
+//   $/cancelRequest {"id"}  cancels a running search
+//   exit                    cancels whatever is still running and stops the server
+//
+// Every search runs on its own thread, so requests and cancellations keep being read meanwhile.
+
+use serde_json::{json, Value};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+pub const REQUEST_CANCELLED: i64 = -32800;
+
+// the longest message body accepted, so a bogus Content-Length can't exhaust memory
+pub const MAX_MESSAGE: usize = 8 * 1024 * 1024;
+
+// how much of a file is read, and then searched, between two looks at the cancellation flag
+const CHUNK: usize = 64 * 1024;
+
+/// Reads one framed message, or `None` at the end of the input.
+pub fn read_message<R: BufRead>(input: &mut R) -> io::Result<Option<String>> {
+    let mut length = None;
+    let mut header = String::new();
+    loop {
+        header.clear();
+        if input.read_line(&mut header)? == 0 {
+            return if length.is_none() {
+                Ok(None)
+            } else {
+                Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "truncated header",
+                ))
+            };
+        }
+
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("Content-Length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    let length = length.ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidData, "missing Content-Length header")
+    })?;
+    if length > MAX_MESSAGE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("message of {} bytes is over the limit", length),
+        ));
+    }
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+
+    String::from_utf8(body)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+pub fn write_message<W: Write>(output: &mut W, message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
+
+fn response(id: &Value, result: Value) -> Value {
+    json!({"jsonrpc": "2.0", "id": id, "result": result})
+}
+
+fn error(id: &Value, code: i64, message: &str) -> Value {
+    json!({"jsonrpc": "2.0", "id": id, "error": {"code": code, "message": message}})
+}
+
+type Output<W> = Arc<Mutex<W>>;
+
+fn send<W: Write>(output: &Output<W>, message: &Value) -> io::Result<()> {
+    // a poisoned lock only means another worker panicked mid-write, keep serving
+    let mut output = output.lock().unwrap_or_else(|e| e.into_inner());
+    write_message(&mut *output, message)
+}
+
+pub struct SearchRequest {
+    pub query: String,
+    pub paths: Vec<String>,
+    pub case_sensitive: bool,
+    pub max_count: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+impl SearchRequest {
+    pub fn from_params(params: &Value) -> Result<SearchRequest, &'static str> {
+        let query = params
+            .get("query")
+            .and_then(Value::as_str)
+            .ok_or("search needs a string `query`")?;
+        let paths = params
+            .get("paths")
+            .and_then(Value::as_array)
+            .ok_or("search needs an array of `paths`")?
+            .iter()
+            .map(|path| path.as_str().map(String::from))
+            .collect::<Option<Vec<_>>>()
+            .ok_or("`paths` must only contain strings")?;
+
+        let count = |name: &str| match params.get(name) {
+            None | Some(Value::Null) => Ok(None),
+            Some(value) => value
+                .as_u64()
+                .map(|n| Some(n as usize))
+                .ok_or("`maxCount` and `limit` must be non-negative integers"),
+        };
+
+        Ok(SearchRequest {
+            query: String::from(query),
+            paths,
+            case_sensitive: params
+                .get("caseSensitive")
+                .and_then(Value::as_bool)
+                .unwrap_or(true),
+            max_count: count("maxCount")?,
+            limit: count("limit")?,
+        })
+    }
+
+    /// Runs the search, calling `notify` with a `search/match` notification for every hit.
+    ///
+    /// Returns the final result, or `None` when `cancelled` was set before it finished.
+    pub fn run<F>(
+        &self,
+        id: &Value,
+        cancelled: &AtomicBool,
+        mut notify: F,
+    ) -> io::Result<Option<Value>>
+    where
+        F: FnMut(&Value) -> io::Result<()>,
+    {
+        let mut remaining = self.limit.unwrap_or(usize::MAX);
+        let mut matches = 0;
+        let mut errors = Vec::new();
+
+        for path in &self.paths {
+            if remaining == 0 {
+                break;
+            }
+            if cancelled.load(Ordering::Relaxed) {
+                return Ok(None);
+            }
+
+            let contents = match read(path, cancelled) {
+                Ok(Some(contents)) => contents,
+                Ok(None) => return Ok(None),
+                Err(e) => {
+                    errors.push(json!({"path": path, "message": e.to_string()}));
+                    continue;
+                }
+            };
+
+            // searched a chunk of lines at a time, so a file with few hits still stops soon
+            let lines = chunks(&contents)
+                .take_while(|_| !cancelled.load(Ordering::Relaxed))
+                .flat_map(|chunk| self.lines(chunk));
+            let take = self.max_count.unwrap_or(usize::MAX).min(remaining);
+            for (line, text) in numbered(&contents, lines).take(take) {
+                if cancelled.load(Ordering::Relaxed) {
+                    return Ok(None);
+                }
+                notify(&json!({
+                    "jsonrpc": "2.0",
+                    "method": "search/match",
+                    "params": {"id": id, "path": path, "line": line, "text": text},
+                }))?;
+                matches += 1;
+                remaining -= 1;
+            }
+            if cancelled.load(Ordering::Relaxed) {
+                return Ok(None);
+            }
+        }
+
+        Ok(Some(json!({"matches": matches, "errors": errors})))
+    }
+
+    fn lines<'a>(&'a self, contents: &'a str) -> Box<dyn Iterator<Item = &'a str> + 'a> {
+        if self.case_sensitive {
+            Box::new(crate::search_iter(&self.query, contents))
+        } else {
+            Box::new(crate::search_case_insensitive_iter(&self.query, contents))
+        }
+    }
+}
+
+// reads the file a chunk at a time, giving up with `None` once `cancelled` is set
+fn read(path: &str, cancelled: &AtomicBool) -> io::Result<Option<String>> {
+    let mut file = File::open(path)?;
+    let mut contents = Vec::new();
+    loop {
+        if cancelled.load(Ordering::Relaxed) {
+            return Ok(None);
+        }
+        if (&mut file).take(CHUNK as u64).read_to_end(&mut contents)? == 0 {
+            break;
+        }
+    }
+    String::from_utf8(contents)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+// splits `contents` into slices of whole lines, each a little over `CHUNK` bytes
+fn chunks(contents: &str) -> impl Iterator<Item = &str> {
+    let mut rest = contents;
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let end = match rest.as_bytes().iter().skip(CHUNK).position(|&b| b == b'\n') {
+            Some(newline) => CHUNK + newline + 1,
+            None => rest.len(),
+        };
+        let (chunk, next) = rest.split_at(end);
+        rest = next;
+        Some(chunk)
+    })
+}
+
+/// The cancellation flags of the searches still running, keyed by their serialized id.
+#[derive(Default)]
+struct Running(Mutex<HashMap<String, Arc<AtomicBool>>>);
+
+impl Running {
+    /// The flag to cancel a new search with, or `None` while one with the same id is running.
+    fn start(&self, key: &str) -> Option<Arc<AtomicBool>> {
+        match self.0.lock().unwrap().entry(String::from(key)) {
+            Entry::Occupied(_) => None,
+            Entry::Vacant(entry) => Some(Arc::clone(entry.insert(Arc::default()))),
+        }
+    }
+
+    fn finish(&self, key: &str) {
+        self.0.lock().unwrap().remove(key);
+    }
+
+    fn cancel(&self, key: &str) {
+        if let Some(cancelled) = self.0.lock().unwrap().get(key) {
+            cancelled.store(true, Ordering::Relaxed);
+        }
+    }
+
+    fn cancel_all(&self) {
+        for cancelled in self.0.lock().unwrap().values() {
+            cancelled.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+// pairs every line yielded by a search with its 1-based line number, counting the newlines
+// between consecutive hits instead of keeping a counter over every line
+fn numbered<'a>(
+    contents: &'a str,
+    lines: impl Iterator<Item = &'a str>,
+) -> impl Iterator<Item = (usize, &'a str)> {
+    let base = contents.as_ptr() as usize;
+    let mut counted = 0;
+    let mut line_number = 1;
+
+    lines.map(move |line| {
+        // the search yields slices of `contents`, so the pointer difference is the offset
+        let offset = line.as_ptr() as usize - base;
+        line_number += contents.as_bytes()[counted..offset]
+            .iter()
+            .filter(|&&b| b == b'\n')
+            .count();
+        counted = offset;
+        (line_number, line)
+    })
+}
+
+/// Serves requests read from `input` until `exit` or the end of the input.
+pub fn serve<R, W>(mut input: R, output: W) -> io::Result<()>
+where
+    R: BufRead,
+    W: Write + Send + 'static,
+{
+    let output = Arc::new(Mutex::new(output));
+    let running = Arc::new(Running::default());
+    let mut workers: Vec<JoinHandle<()>> = Vec::new();
+    let mut shutting_down = false;
+
+    while let Some(body) = read_message(&mut input)? {
+        let message: Value = match serde_json::from_str(&body) {
+            Ok(message) => message,
+            Err(e) => {
+                send(&output, &error(&Value::Null, PARSE_ERROR, &e.to_string()))?;
+                continue;
+            }
+        };
+        let id = message.get("id").cloned();
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        match (message.get("method").and_then(Value::as_str), id) {
+            (Some("exit"), None) => {
+                running.cancel_all();
+                break;
+            }
+            (Some(_), Some(id)) if shutting_down => {
+                send(
+                    &output,
+                    &error(&id, INVALID_REQUEST, "server is shutting down"),
+                )?;
+            }
+            (Some("shutdown"), Some(id)) => {
+                shutting_down = true;
+                send(&output, &response(&id, Value::Null))?;
+            }
+            (Some("search"), Some(id)) => {
+                let request = match SearchRequest::from_params(&params) {
+                    Ok(request) => request,
+                    Err(message) => {
+                        send(&output, &error(&id, INVALID_PARAMS, message))?;
+                        continue;
+                    }
+                };
+
+                let key = id.to_string();
+                let cancelled = match running.start(&key) {
+                    Some(cancelled) => cancelled,
+                    None => {
+                        let message = "a search with this id is still running";
+                        send(&output, &error(&id, INVALID_REQUEST, message))?;
+                        continue;
+                    }
+                };
+
+                let output = Arc::clone(&output);
+                let running = Arc::clone(&running);
+                workers.push(thread::spawn(move || {
+                    let result =
+                        request.run(&id, &cancelled, |notification| send(&output, notification));
+                    running.finish(&key);
+
+                    let reply = match result {
+                        Ok(Some(result)) => response(&id, result),
+                        Ok(None) => error(&id, REQUEST_CANCELLED, "search was cancelled"),
+                        Err(e) => error(&id, INTERNAL_ERROR, &e.to_string()),
+                    };
+                    // nobody is left to tell if the output itself is gone
+                    let _ = send(&output, &reply);
+                }));
+            }
+            (Some("$/cancelRequest"), None) => {
+                if let Some(id) = params.get("id") {
+                    running.cancel(&id.to_string());
+                }
+            }
+            (Some(method), Some(id)) => {
+                let message = format!("unknown method `{}`", method);
+                send(&output, &error(&id, METHOD_NOT_FOUND, &message))?;
+            }
+            // unknown notifications are ignored, like in the Language Server Protocol
+            (Some(_), None) => {}
+            (None, id) => {
+                let id = id.unwrap_or(Value::Null);
+                send(&output, &error(&id, INVALID_REQUEST, "missing `method`"))?;
+            }
+        }
+
+        workers.retain(|worker| !worker.is_finished());
+    }
+
+    // let the searches still running finish, so every response gets written
+    for worker in workers {
+        let _ = worker.join();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use std::io::{BufReader, Cursor};
+    use std::sync::mpsc::{self, Receiver, Sender};
+
+    #[derive(Clone)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn session(messages: &[Value]) -> Vec<Value> {
+        let mut input = Vec::new();
+        for message in messages {
+            write_message(&mut input, message).unwrap();
+        }
+
+        let output = SharedBuffer(Arc::new(Mutex::new(Vec::new())));
+        serve(Cursor::new(input), output.clone()).unwrap();
+
+        let output = output.0.lock().unwrap().clone();
+        let mut output = Cursor::new(output);
+        let mut replies = Vec::new();
+        while let Some(body) = read_message(&mut output).unwrap() {
+            replies.push(serde_json::from_str(&body).unwrap());
+        }
+        replies
+    }
+
+    fn reply_to(replies: &[Value], id: i64) -> &Value {
+        replies
+            .iter()
+            .find(|reply| reply["id"] == json!(id) && reply.get("method").is_none())
+            .unwrap()
+    }
+
+    #[test]
+    fn framing_round_trip() {
+        let mut buffer = Vec::new();
+        write_message(&mut buffer, &json!({"text": "straße"})).unwrap();
+        write_message(&mut buffer, &json!([1, 2])).unwrap();
+
+        let mut input = Cursor::new(buffer);
+        assert_eq!(
+            Some(String::from("{\"text\":\"straße\"}")),
+            read_message(&mut input).unwrap()
+        );
+        assert_eq!(
+            Some(String::from("[1,2]")),
+            read_message(&mut input).unwrap()
+        );
+        assert_eq!(None, read_message(&mut input).unwrap());
+
+        let mut missing = Cursor::new(b"Content-Type: text\r\n\r\n{}".to_vec());
+        assert!(read_message(&mut missing).is_err());
+
+        let mut huge = Cursor::new(b"Content-Length: 18446744073709551615\r\n\r\n{}".to_vec());
+        let err = read_message(&mut huge).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    }
+
+    #[test]
+    fn search_streams_matches() {
+        let replies = session(&[
+            json!({"jsonrpc": "2.0", "id": 1, "method": "search", "params": {
+                "query": "BODY", "paths": ["poem.txt", "missing.txt"], "caseSensitive": false,
+            }}),
+            json!({"jsonrpc": "2.0", "id": 2, "method": "shutdown"}),
+        ]);
+
+        let matches: Vec<&Value> = replies
+            .iter()
+            .filter(|reply| reply["method"] == "search/match")
+            .collect();
+        assert_eq!(
+            vec![1, 2, 6],
+            matches
+                .iter()
+                .map(|m| m["params"]["line"].as_u64().unwrap())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!("Are you nobody, too?", matches[1]["params"]["text"]);
+        assert_eq!("poem.txt", matches[1]["params"]["path"]);
+
+        let result = &reply_to(&replies, 1)["result"];
+        assert_eq!(3, result["matches"]);
+        assert_eq!("missing.txt", result["errors"][0]["path"]);
+        assert_eq!(Value::Null, reply_to(&replies, 2)["result"]);
+    }
+
+    #[test]
+    fn search_limits() {
+        let replies = session(&[
+            json!({"jsonrpc": "2.0", "id": 7, "method": "search", "params": {
+                "query": "o", "paths": ["poem.txt", "poem.txt"], "maxCount": 2, "limit": 3,
+            }}),
+        ]);
+
+        assert_eq!(3, reply_to(&replies, 7)["result"]["matches"]);
+        let lines: Vec<u64> = replies
+            .iter()
+            .filter(|reply| reply["method"] == "search/match")
+            .map(|m| m["params"]["line"].as_u64().unwrap())
+            .collect();
+        assert_eq!(vec![1, 2, 1], lines);
+    }
+
+    #[test]
+    fn cancelled_search() {
+        let request =
+            SearchRequest::from_params(&json!({"query": "o", "paths": ["poem.txt"]})).unwrap();
+        let cancelled = AtomicBool::new(true);
+        let mut notified = 0;
+        let result = request
+            .run(&json!(1), &cancelled, |_| {
+                notified += 1;
+                Ok(())
+            })
+            .unwrap();
+
+        assert_eq!(None, result);
+        assert_eq!(0, notified);
+
+        // cancelling something that isn't running is harmless
+        let replies =
+            session(&[json!({"jsonrpc": "2.0", "method": "$/cancelRequest", "params": {"id": 3}})]);
+        assert!(replies.is_empty());
+    }
+
+    // hands `serve` the search, then the rest of the messages once the search has found
+    // something, and tells the search to go on when asked for more after those
+    struct Script {
+        messages: Vec<Vec<u8>>,
+        found: Receiver<()>,
+        go_on: Sender<()>,
+    }
+
+    impl Read for Script {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.messages.is_empty() {
+                let _ = self.go_on.send(());
+                return Ok(0);
+            }
+            if self.messages.len() == 1 {
+                self.found.recv().unwrap();
+            }
+            let message = self.messages.remove(0);
+            buf[..message.len()].copy_from_slice(&message);
+            Ok(message.len())
+        }
+    }
+
+    // holds up the first write, the search's first match, until the script says to go on
+    struct Paused {
+        buffer: Arc<Mutex<Vec<u8>>>,
+        found: Option<Sender<()>>,
+        go_on: Option<Receiver<()>>,
+    }
+
+    impl Write for Paused {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if let (Some(found), Some(go_on)) = (self.found.take(), self.go_on.take()) {
+                found.send(()).unwrap();
+                go_on.recv().unwrap();
+            }
+            self.buffer.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn cancelled_mid_search() {
+        // one hit right at the start, then a lot of lines to go through without any
+        let path = env::temp_dir().join(format!("minigrep-cancel-{}.txt", std::process::id()));
+        let contents = String::from("needle\n") + &"hay\n".repeat(CHUNK);
+        fs::write(&path, contents).unwrap();
+
+        let mut search = Vec::new();
+        write_message(
+            &mut search,
+            &json!({"jsonrpc": "2.0", "id": 1, "method": "search", "params": {
+                "query": "needle", "paths": [path.to_str().unwrap()],
+            }}),
+        )
+        .unwrap();
+        let mut cancel = Vec::new();
+        write_message(
+            &mut cancel,
+            &json!({"jsonrpc": "2.0", "method": "$/cancelRequest", "params": {"id": 1}}),
+        )
+        .unwrap();
+
+        let (found, found_rx) = mpsc::channel();
+        let (go_on, go_on_rx) = mpsc::channel();
+        let input = Script {
+            messages: vec![search, cancel],
+            found: found_rx,
+            go_on,
+        };
+        let buffer = Arc::new(Mutex::new(Vec::new()));
+        let output = Paused {
+            buffer: Arc::clone(&buffer),
+            found: Some(found),
+            go_on: Some(go_on_rx),
+        };
+        serve(BufReader::new(input), output).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let output = buffer.lock().unwrap().clone();
+        let mut output = Cursor::new(output);
+        let mut replies = Vec::new();
+        while let Some(body) = read_message(&mut output).unwrap() {
+            replies.push(serde_json::from_str::<Value>(&body).unwrap());
+        }
+        assert_eq!("search/match", replies[0]["method"]);
+        assert_eq!(
+            json!(REQUEST_CANCELLED),
+            reply_to(&replies, 1)["error"]["code"]
+        );
+    }
+
+    #[test]
+    fn one_search_per_id() {
+        let running = Running::default();
+        let cancelled = running.start("1").unwrap();
+        assert!(running.start("1").is_none());
+
+        running.cancel("1");
+        assert!(cancelled.load(Ordering::Relaxed));
+        running.finish("1");
+        assert!(!running.start("1").unwrap().load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn chunks_keep_lines_whole() {
+        let line = "0123456789abcde\n";
+        let contents = line.repeat(CHUNK / 8);
+        let pieces: Vec<&str> = chunks(&contents).collect();
+        assert_eq!(2, pieces.len());
+        assert!(pieces.iter().all(|piece| piece.ends_with('\n')));
+        assert_eq!(contents, pieces.concat());
+        assert_eq!(None, chunks("").next());
+    }
+
+    #[test]
+    fn exit_stops_reading() {
+        let replies = session(&[
+            json!({"jsonrpc": "2.0", "method": "exit"}),
+            json!({"jsonrpc": "2.0", "id": 1, "method": "shutdown"}),
+        ]);
+        assert!(replies.is_empty());
+    }
+
+    #[test]
+    fn protocol_errors() {
+        let mut input = Vec::new();
+        write!(input, "Content-Length: 5\r\n\r\nnope!").unwrap();
+        for message in &[
+            json!({"jsonrpc": "2.0", "id": 1, "method": "frobnicate"}),
+            json!({"jsonrpc": "2.0", "id": 2, "method": "search", "params": {"query": "x"}}),
+            json!({"jsonrpc": "2.0", "id": 3}),
+            json!({"jsonrpc": "2.0", "id": 4, "method": "shutdown"}),
+            json!({"jsonrpc": "2.0", "id": 5, "method": "search", "params": {"query": "x", "paths": []}}),
+        ] {
+            write_message(&mut input, message).unwrap();
+        }
+
+        let output = SharedBuffer(Arc::new(Mutex::new(Vec::new())));
+        serve(Cursor::new(input), output.clone()).unwrap();
+        let output = output.0.lock().unwrap().clone();
+        let mut output = Cursor::new(output);
+        let mut codes = Vec::new();
+        while let Some(body) = read_message(&mut output).unwrap() {
+            let reply: Value = serde_json::from_str(&body).unwrap();
+            codes.push(reply["error"]["code"].as_i64());
+        }
+
+        assert_eq!(
+            vec![
+                Some(PARSE_ERROR),
+                Some(METHOD_NOT_FOUND),
+                Some(INVALID_PARAMS),
+                Some(INVALID_REQUEST),
+                None,
+                Some(INVALID_REQUEST),
+            ],
+            codes
+        );
+    }
+
+    #[test]
+    fn line_numbers() {
+        let contents = "a\nb\r\nab\n\nb";
+        let hits: Vec<(usize, &str)> =
+            numbered(contents, crate::search_iter("b", contents)).collect();
+        assert_eq!(vec![(2, "b"), (3, "ab"), (5, "b")], hits);
+    }
+}
@@ -0,0 +1,347 @@
+// Interactive result browser: `minigrep --interactive query file`
+//
+// The screen is drawn on stderr so that stdout only ever carries the `file:line` picked on exit,
+// which lets editors do things like `vim $(minigrep -I todo src/lib.rs)`.
+//
+// Keys: type to refine the query, Backspace to widen it, Up/Down/PageUp/PageDown to move,
+// Enter to pick the selected hit, Esc or Ctrl-C to quit without picking anything.
+
+use crossterm::cursor::MoveTo;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use std::io::{self, Write};
+
+/// Lines of context shown above and below the selected hit in the preview pane.
+const CONTEXT: usize = 3;
+
+/// The state of the browser, kept apart from the terminal so it can be tested.
+pub struct Browser<'a> {
+    lines: Vec<&'a str>,
+    query: String,
+    case_sensitive: bool,
+    // indexes into `lines` of the lines containing `query`
+    hits: Vec<usize>,
+    selected: usize,
+    // index into `hits` of the first row shown in the list
+    scroll: usize,
+}
+
+impl<'a> Browser<'a> {
+    pub fn new(query: &str, contents: &'a str, case_sensitive: bool) -> Browser<'a> {
+        let mut browser = Browser {
+            lines: contents.lines().collect(),
+            query: String::from(query),
+            case_sensitive,
+            hits: Vec::new(),
+            selected: 0,
+            scroll: 0,
+        };
+        browser.refresh();
+        browser
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn hits(&self) -> &[usize] {
+        &self.hits
+    }
+
+    /// The 1-based line number of the selected hit.
+    pub fn selection(&self) -> Option<usize> {
+        self.hits.get(self.selected).map(|i| i + 1)
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.query.push(c);
+        self.refresh();
+    }
+
+    pub fn pop(&mut self) {
+        self.query.pop();
+        self.refresh();
+    }
+
+    pub fn up(&mut self, by: usize) {
+        self.selected = self.selected.saturating_sub(by);
+    }
+
+    pub fn down(&mut self, by: usize) {
+        self.selected = (self.selected + by).min(self.hits.len().saturating_sub(1));
+    }
+
+    fn refresh(&mut self) {
+        // keep the same line selected if it still matches
+        let previous = self.hits.get(self.selected).copied();
+
+        let query = if self.case_sensitive {
+            self.query.clone()
+        } else {
+            self.query.to_lowercase()
+        };
+        let case_sensitive = self.case_sensitive;
+        self.hits = self
+            .lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| {
+                if case_sensitive {
+                    line.contains(&query)
+                } else {
+                    line.to_lowercase().contains(&query)
+                }
+            })
+            .map(|(i, _)| i)
+            .collect();
+
+        self.selected = previous
+            .and_then(|line| self.hits.iter().position(|&hit| hit >= line))
+            .unwrap_or(0)
+            .min(self.hits.len().saturating_sub(1));
+        self.scroll = self.scroll.min(self.selected);
+    }
+
+    /// Draws the prompt, the list of hits and the preview pane into a `width` x `height` screen.
+    pub fn render<W: Write>(&mut self, out: &mut W, width: u16, height: u16) -> io::Result<()> {
+        let width = width as usize;
+        let height = height as usize;
+        // prompt, list, separator and preview share the screen
+        let list_height = height.saturating_sub(2) / 2;
+        let preview_height = height.saturating_sub(2 + list_height);
+
+        if self.selected < self.scroll {
+            self.scroll = self.selected;
+        } else if list_height > 0 && self.selected >= self.scroll + list_height {
+            self.scroll = self.selected + 1 - list_height;
+        }
+
+        queue!(out, Clear(ClearType::All), MoveTo(0, 0))?;
+        let prompt = format!("query> {}  ({} hits)", self.query, self.hits.len());
+        queue!(out, Print(truncate(&prompt, width)))?;
+
+        for row in 0..list_height {
+            let i = self.scroll + row;
+            let line = match self.hits.get(i) {
+                Some(&line) => line,
+                None => break,
+            };
+            let text = format!("{:>6}: {}", line + 1, self.lines[line]);
+            queue!(out, MoveTo(0, (row + 1) as u16))?;
+            if i == self.selected {
+                queue!(
+                    out,
+                    SetAttribute(Attribute::Reverse),
+                    Print(truncate(&text, width)),
+                    SetAttribute(Attribute::Reset)
+                )?;
+            } else {
+                queue!(out, Print(truncate(&text, width)))?;
+            }
+        }
+
+        let separator_row = list_height + 1;
+        queue!(
+            out,
+            MoveTo(0, separator_row as u16),
+            Print("-".repeat(width))
+        )?;
+
+        if let Some(&hit) = self.hits.get(self.selected) {
+            let first = hit.saturating_sub(CONTEXT);
+            let last = (hit + CONTEXT).min(self.lines.len() - 1);
+            for (row, line) in (first..=last).take(preview_height).enumerate() {
+                let text = format!("{:>6}  {}", line + 1, self.lines[line]);
+                queue!(out, MoveTo(0, (separator_row + 1 + row) as u16))?;
+                if line == hit {
+                    queue!(
+                        out,
+                        SetAttribute(Attribute::Bold),
+                        Print(truncate(&text, width)),
+                        SetAttribute(Attribute::Reset)
+                    )?;
+                } else {
+                    queue!(out, Print(truncate(&text, width)))?;
+                }
+            }
+        }
+
+        // leave the cursor at the end of the query so typing feels natural
+        let cursor = ("query> ".len() + self.query.chars().count()).min(width.saturating_sub(1));
+        queue!(out, MoveTo(cursor as u16, 0))?;
+        out.flush()
+    }
+
+    /// Applies one key press, returning what the browser should do next.
+    pub fn handle(&mut self, key: KeyEvent, page: usize) -> Action {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => return Action::Quit,
+            KeyCode::Char('c') if ctrl => return Action::Quit,
+            KeyCode::Enter => return Action::Pick,
+            KeyCode::Up => self.up(1),
+            KeyCode::Down => self.down(1),
+            KeyCode::PageUp => self.up(page),
+            KeyCode::PageDown => self.down(page),
+            KeyCode::Home => self.up(self.selected),
+            KeyCode::End => self.down(self.hits.len()),
+            KeyCode::Backspace => self.pop(),
+            KeyCode::Char(c) if !ctrl => self.push(c),
+            _ => {}
+        }
+        Action::Continue
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Action {
+    Continue,
+    Pick,
+    Quit,
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    // tabs would move the cursor past where we think it is
+    text.replace('\t', "    ").chars().take(width).collect()
+}
+
+/// Raw mode and the alternate screen for as long as it's alive. Dropping it puts the
+/// terminal back however the browser ends, even on a panic or an early `?` return.
+struct RawTerminal;
+
+impl RawTerminal {
+    fn enter(out: &mut impl Write) -> io::Result<RawTerminal> {
+        terminal::enable_raw_mode()?;
+        // from here on the guard exists, so raw mode is undone even if this fails
+        let guard = RawTerminal;
+        execute!(out, EnterAlternateScreen)?;
+        Ok(guard)
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        // nothing useful to do if restoring fails, and a drop mustn't panic
+        let _ = execute!(io::stderr(), LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Runs the browser until the user picks a hit or quits, returning the picked 1-based line.
+pub fn browse(query: &str, contents: &str, case_sensitive: bool) -> io::Result<Option<usize>> {
+    let mut browser = Browser::new(query, contents, case_sensitive);
+    let mut out = io::stderr();
+
+    let _terminal = RawTerminal::enter(&mut out)?;
+    event_loop(&mut browser, &mut out)
+}
+
+fn event_loop<W: Write>(browser: &mut Browser, out: &mut W) -> io::Result<Option<usize>> {
+    loop {
+        let (width, height) = terminal::size()?;
+        browser.render(out, width, height)?;
+
+        if let Event::Key(key) = event::read()? {
+            // Windows also reports key releases
+            if key.kind == KeyEventKind::Release {
+                continue;
+            }
+            let page = (height as usize).saturating_sub(2) / 2;
+            match browser.handle(key, page.max(1)) {
+                Action::Continue => {}
+                Action::Pick => return Ok(browser.selection()),
+                Action::Quit => return Ok(None),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Action, Browser};
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    const CONTENTS: &str = "\
+Rust:
+safe, fast, productive.
+Pick three.
+Duct tape.
+Trust me.";
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn refines_as_you_type() {
+        let mut browser = Browser::new("t", CONTENTS, true);
+        assert_eq!(&[0, 1, 2, 3, 4], browser.hits());
+
+        browser.push('h');
+        assert_eq!(&[2], browser.hits());
+        assert_eq!(Some(3), browser.selection());
+
+        browser.pop();
+        assert_eq!("t", browser.query());
+        assert_eq!(5, browser.hits().len());
+    }
+
+    #[test]
+    fn keeps_selection_while_refining() {
+        let mut browser = Browser::new("", CONTENTS, false);
+        browser.down(3);
+        assert_eq!(Some(4), browser.selection());
+
+        for c in "uct".chars() {
+            browser.push(c);
+        }
+        assert_eq!(Some(4), browser.selection());
+
+        browser.push('x');
+        assert_eq!(None, browser.selection());
+    }
+
+    #[test]
+    fn case_insensitive_hits() {
+        let browser = Browser::new("rUsT", CONTENTS, false);
+        assert_eq!(&[0, 4], browser.hits());
+    }
+
+    #[test]
+    fn keys_move_pick_and_quit() {
+        let mut browser = Browser::new("", CONTENTS, true);
+        assert_eq!(Action::Continue, browser.handle(key(KeyCode::End), 2));
+        assert_eq!(Some(5), browser.selection());
+        browser.handle(key(KeyCode::PageUp), 2);
+        assert_eq!(Some(3), browser.selection());
+        browser.handle(key(KeyCode::Up), 2);
+        browser.handle(key(KeyCode::Up), 2);
+        browser.handle(key(KeyCode::Up), 2);
+        assert_eq!(Some(1), browser.selection());
+
+        browser.handle(key(KeyCode::Char('D')), 2);
+        assert_eq!("D", browser.query());
+        assert_eq!(Action::Pick, browser.handle(key(KeyCode::Enter), 2));
+        assert_eq!(Some(4), browser.selection());
+
+        let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert_eq!(Action::Quit, browser.handle(ctrl_c, 2));
+        assert_eq!(Action::Quit, browser.handle(key(KeyCode::Esc), 2));
+    }
+
+    #[test]
+    fn renders_hits_and_preview() {
+        let mut browser = Browser::new("ck", CONTENTS, true);
+        let mut screen = Vec::new();
+        browser.render(&mut screen, 40, 12).unwrap();
+
+        let screen = String::from_utf8(screen).unwrap();
+        assert!(screen.contains("query> ck  (1 hits)"));
+        assert!(screen.contains("     3: Pick three."));
+        // context around the hit in the preview
+        assert!(screen.contains("     1  Rust:"));
+        assert!(screen.contains("     5  Trust me."));
+    }
+}
@@ -0,0 +1,319 @@
+// shared by the throughput regression test and the benchmark harness in benches/search.rs,
+// which pulls this file in with `#[path]`, so it must not depend on anything test-only
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+pub const BASELINE_FILE: &str = "benches/baseline.txt";
+
+const ASCII_WORDS: &[&str] = &[
+    "the",
+    "quick",
+    "brown",
+    "fox",
+    "jumps",
+    "over",
+    "lazy",
+    "dog",
+    "rust",
+    "safe",
+    "fast",
+    "productive",
+    "memory",
+    "thread",
+    "borrow",
+    "lifetime",
+    "trait",
+    "iterator",
+    "closure",
+];
+
+const UNICODE_WORDS: &[&str] = &[
+    "über",
+    "straße",
+    "café",
+    "naïve",
+    "日本語",
+    "中文",
+    "한국어",
+    "Ελληνικά",
+    "кириллица",
+    "emoji🦀",
+    "ÅNGSTRÖM",
+    "ǅemal",
+    "İstanbul",
+    "ﬁnal",
+];
+
+/// A tiny xorshift generator so corpora are identical on every run and machine.
+pub struct Xorshift(u64);
+
+impl Xorshift {
+    pub fn new(seed: u64) -> Xorshift {
+        Xorshift(seed.max(1))
+    }
+
+    pub fn next(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x
+    }
+
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+fn text(words: &[&str], size: usize, words_per_line: usize, seed: u64) -> String {
+    let mut rng = Xorshift::new(seed);
+    let mut contents = String::with_capacity(size + 64);
+    while contents.len() < size {
+        let count = 1 + rng.below(words_per_line);
+        for i in 0..count {
+            if i > 0 {
+                contents.push(' ');
+            }
+            contents.push_str(words[rng.below(words.len())]);
+        }
+        contents.push('\n');
+    }
+    contents
+}
+
+/// Plain English-like text with short lines.
+pub fn ascii(size: usize) -> String {
+    text(ASCII_WORDS, size, 12, 1)
+}
+
+/// Mostly multi-byte text, including characters whose lowercase form changes length.
+pub fn unicode(size: usize) -> String {
+    text(UNICODE_WORDS, size, 12, 2)
+}
+
+/// A few very long lines, so per-line overhead is negligible.
+pub fn long_lines(size: usize) -> String {
+    text(ASCII_WORDS, size, 4000, 3)
+}
+
+/// Many small files, each a handful of lines.
+pub fn short_files(count: usize) -> Vec<String> {
+    (0..count as u64)
+        .map(|i| text(ASCII_WORDS, 512, 8, 10 + i))
+        .collect()
+}
+
+/// A directory of corpus files, removed with everything in it when dropped.
+pub struct TempDir {
+    dir: PathBuf,
+    pub paths: Vec<PathBuf>,
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Writes `files` into a fresh directory under the system temp dir.
+pub fn write_files(name: &str, files: &[String]) -> TempDir {
+    let dir = std::env::temp_dir().join(format!("minigrep-{}-{}", name, std::process::id()));
+    fs::create_dir_all(&dir).expect("failed to create corpus dir");
+    let paths = files
+        .iter()
+        .enumerate()
+        .map(|(i, contents)| {
+            let path = dir.join(format!("{}.txt", i));
+            fs::write(&path, contents).expect("failed to write corpus file");
+            path
+        })
+        .collect();
+    TempDir { dir, paths }
+}
+
+/// Runs `f` repeatedly for at least `budget` and returns the best observed throughput
+/// in MB/s, where `bytes` is the amount of input a single call processes.
+pub fn throughput<F: FnMut()>(bytes: usize, budget: Duration, mut f: F) -> f64 {
+    // warm up caches and the allocator before timing anything
+    f();
+
+    let start = Instant::now();
+    let mut best = Duration::MAX;
+    let mut runs = 0;
+    while runs < 3 || start.elapsed() < budget {
+        let t = Instant::now();
+        f();
+        best = best.min(t.elapsed());
+        runs += 1;
+    }
+    bytes as f64 / best.as_secs_f64() / 1_000_000.0
+}
+
+/// How fast a benchmark was when the baseline was measured.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Baseline {
+    pub mbps: f64,
+    /// How many times faster than its `reference`, which unlike MB/s doesn't depend on
+    /// the machine much.
+    pub speedup: f64,
+}
+
+/// Measures `bench` and its reference for `budget` each, returning the benchmark's
+/// throughput in MB/s and how many times faster it is than the reference. The two take
+/// turns, so whatever else the machine is busy with slows both down alike.
+pub fn measure(bench: &mut Bench, budget: Duration) -> (f64, f64) {
+    const ROUNDS: u32 = 5;
+    let (mut mbps, mut reference) = (0.0f64, 0.0f64);
+    for _ in 0..ROUNDS {
+        mbps = mbps.max(throughput(bench.bytes, budget / ROUNDS, &mut bench.f));
+        reference = reference.max(throughput(
+            bench.bytes,
+            budget / ROUNDS,
+            &mut bench.reference,
+        ));
+    }
+    (mbps, mbps / reference)
+}
+
+/// Baselines keyed by benchmark name, one `name mbps speedup` line each.
+pub fn load_baseline(path: &Path) -> HashMap<String, Baseline> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(_) => return HashMap::new(),
+    };
+
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let name = parts.next()?;
+            let mbps = parts.next()?.parse().ok()?;
+            let speedup = parts.next()?.parse().ok()?;
+            Some((name.to_string(), Baseline { mbps, speedup }))
+        })
+        .collect()
+}
+
+pub fn save_baseline(path: &Path, results: &[(String, Baseline)]) -> std::io::Result<()> {
+    let mut contents = String::from(
+        "# minigrep throughput baseline, measured with `cargo bench`: MB/s, then how many\n\
+         # times faster than the naive scan from the Book\n\
+         # regenerate with `MINIGREP_SAVE_BASELINE=1 cargo bench`\n",
+    );
+    for (name, baseline) in results {
+        contents.push_str(&format!(
+            "{} {:.1} {:.2}\n",
+            name, baseline.mbps, baseline.speedup
+        ));
+    }
+    fs::write(path, contents)
+}
+
+pub struct Bench {
+    pub name: String,
+    /// Bytes of input processed by one call of `f`.
+    pub bytes: usize,
+    pub f: Box<dyn FnMut()>,
+    /// The same search done the naive way the Book first wrote it, timed in the same run
+    /// so the comparison holds on any machine.
+    pub reference: Box<dyn FnMut()>,
+    /// The files `f` and `reference` read, if any. Declared after them so it's dropped,
+    /// and the files removed, only once they're gone.
+    pub files: Option<TempDir>,
+}
+
+/// The Book's `search`, a line at a time with `str::contains`.
+fn naive_search<'a>(query: &str, contents: &'a str) -> Vec<&'a str> {
+    contents
+        .lines()
+        .filter(|line| line.contains(query))
+        .collect()
+}
+
+/// The Book's `search_case_insensitive`, lowercasing every line.
+fn naive_search_case_insensitive<'a>(query: &str, contents: &'a str) -> Vec<&'a str> {
+    let query = query.to_lowercase();
+    contents
+        .lines()
+        .filter(|line| line.to_lowercase().contains(&query))
+        .collect()
+}
+
+/// Every benchmark we track, over corpora of roughly `size` bytes each.
+pub fn benches(size: usize) -> Vec<Bench> {
+    let corpora = vec![
+        ("ascii", ascii(size), "lazy dog"),
+        ("unicode", unicode(size), "straße"),
+        ("long_lines", long_lines(size), "dogs"),
+    ];
+
+    let mut benches = Vec::new();
+    for (name, contents, query) in corpora {
+        let bytes = contents.len();
+        let [sensitive, naive, insensitive, naive_insensitive] = [0; 4].map(|_| contents.clone());
+        benches.push(Bench {
+            name: format!("search/{}", name),
+            bytes,
+            f: Box::new(move || {
+                std::hint::black_box(minigrep::search(query, &sensitive));
+            }),
+            reference: Box::new(move || {
+                std::hint::black_box(naive_search(query, &naive));
+            }),
+            files: None,
+        });
+        benches.push(Bench {
+            name: format!("search_case_insensitive/{}", name),
+            bytes,
+            f: Box::new(move || {
+                std::hint::black_box(minigrep::search_case_insensitive(query, &insensitive));
+            }),
+            reference: Box::new(move || {
+                std::hint::black_box(naive_search_case_insensitive(query, &naive_insensitive));
+            }),
+            files: None,
+        });
+    }
+
+    let files = short_files(size / 512);
+    let bytes = files.iter().map(String::len).sum();
+    let corpus = write_files("short-files", &files);
+    let paths = corpus.paths.clone();
+    let naive_paths = corpus.paths.clone();
+    benches.push(Bench {
+        name: String::from("run/short_files"),
+        bytes,
+        f: Box::new(move || {
+            let config = minigrep::Config {
+                // no corpus word contains a digit, so `run` prints nothing
+                query: String::from("0"),
+                filenames: paths
+                    .iter()
+                    .map(|path| path.to_string_lossy().into_owned())
+                    .collect(),
+                case_sensitive: true,
+                interactive: false,
+                max_count: None,
+                limit: None,
+                quiet: false,
+            };
+            minigrep::run(config).unwrap();
+        }),
+        reference: Box::new(move || {
+            for path in &naive_paths {
+                let contents = fs::read_to_string(path).unwrap();
+                std::hint::black_box(naive_search("0", &contents));
+            }
+        }),
+        files: Some(corpus),
+    });
+
+    benches
+}
@@ -0,0 +1,70 @@
+// compares each benchmark against the naive scan from the Book, timed in the same run, and
+// fails when it's lost too much of the speedup recorded in benches/baseline.txt. A ratio
+// rather than MB/s, so it holds on whatever machine runs it. It's still timed by the wall
+// clock, so it only runs when asked for, on a machine that's otherwise idle:
+//     cargo test --test throughput -- --ignored --test-threads=1
+// MINIGREP_REGRESSION_THRESHOLD=0.5 allows each speedup to drop to half of its baseline
+mod common;
+
+use std::path::Path;
+use std::time::Duration;
+
+const CORPUS_SIZE: usize = 1024 * 1024;
+const DEFAULT_THRESHOLD: f64 = 0.6;
+
+#[test]
+#[ignore = "timing-sensitive, run with --ignored on an idle machine"]
+fn throughput_has_not_regressed() {
+    let baseline = common::load_baseline(Path::new(common::BASELINE_FILE));
+    assert!(
+        !baseline.is_empty(),
+        "no baseline at {}",
+        common::BASELINE_FILE
+    );
+
+    let threshold = std::env::var("MINIGREP_REGRESSION_THRESHOLD")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(DEFAULT_THRESHOLD);
+
+    let mut regressions = Vec::new();
+    for mut bench in common::benches(CORPUS_SIZE) {
+        let base = match baseline.get(&bench.name) {
+            Some(base) => *base,
+            None => continue,
+        };
+        let (_, speedup) = common::measure(&mut bench, Duration::from_millis(200));
+        if speedup < base.speedup * threshold {
+            regressions.push(format!(
+                "{}: {:.2}x the naive scan, baseline {:.2}x",
+                bench.name, speedup, base.speedup
+            ));
+        }
+    }
+
+    assert!(
+        regressions.is_empty(),
+        "speedup over the naive scan regressed below {:.0}% of baseline:\n{}",
+        threshold * 100.0,
+        regressions.join("\n")
+    );
+}
+
+#[test]
+fn corpora_are_deterministic() {
+    assert_eq!(common::ascii(4096), common::ascii(4096));
+    assert_eq!(common::unicode(4096), common::unicode(4096));
+    assert_eq!(common::short_files(3), common::short_files(3));
+}
+
+#[test]
+fn corpora_match_their_shape() {
+    let unicode = common::unicode(64 * 1024);
+    assert!(unicode.chars().filter(|c| !c.is_ascii()).count() > unicode.len() / 8);
+
+    let long_lines = common::long_lines(64 * 1024);
+    assert!(long_lines.lines().count() < 64);
+
+    let search_hits = minigrep::search("lazy dog", &common::ascii(64 * 1024)).len();
+    assert!(search_hits > 0);
+}